@@ -5,9 +5,22 @@ extern crate test;
 
 use test::Bencher;
 
-use blacklog::Logger;
+use blacklog::{Handle, Logger, Record};
+use blacklog::filter::FilterAction;
 use blacklog::logger::{ActorLogger, SeverityFilteredLoggerAdapter, SyncLogger};
 
+struct DenyHandle;
+
+impl Handle for DenyHandle {
+    fn filter(&self, _rec: &Record) -> FilterAction {
+        FilterAction::Deny
+    }
+
+    fn handle(&self, _rec: &mut Record) -> Result<(), ::std::io::Error> {
+        unreachable!();
+    }
+}
+
 #[bench]
 fn sync_log(b: &mut Bencher) {
     let log = SyncLogger::new(vec![]);
@@ -92,3 +105,15 @@ fn sync_log_with_format_and_meta6_with_sev_adapter_deny(b: &mut Bencher) {
         });
     });
 }
+
+/// All handles deny records, so the message is never formatted.
+#[bench]
+fn sync_log_with_format_and_handles_deny(b: &mut Bencher) {
+    let log = SyncLogger::new(vec![Box::new(DenyHandle), Box::new(DenyHandle)]);
+
+    b.iter(|| {
+        log!(log, 0, "file does not exist: {}", ["/var/www/favicon.ico"], {
+            path1: "/home1",
+        });
+    });
+}
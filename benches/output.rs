@@ -0,0 +1,84 @@
+#![feature(test)]
+
+extern crate blacklog;
+extern crate test;
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+
+use test::Bencher;
+
+use blacklog::{MetaLink, Output, Record};
+use blacklog::output::{FileOutput, ShardedFileOutput};
+
+const THREADS: usize = 4;
+const RECORDS: usize = 1000;
+
+fn write_concurrently(output: Arc<Output>) {
+    let threads = (0..THREADS).map(|_| {
+        let output = output.clone();
+
+        thread::spawn(move || {
+            let metalink = MetaLink::new(&[]);
+            let rec = Record::new(0, 0, "", &metalink);
+
+            for _ in 0..RECORDS {
+                output.write(&rec, b"file does not exist: /var/www/favicon.ico").unwrap();
+            }
+        })
+    }).collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[bench]
+fn file_concurrent(b: &mut Bencher) {
+    let dir = env::temp_dir().join("blacklog-bench-file");
+    fs::create_dir_all(&dir).unwrap();
+
+    {
+        let output = Arc::new(FileOutput::new(&format!("{}/app.log", dir.display())).unwrap());
+
+        b.iter(|| write_concurrently(output.clone()));
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[bench]
+fn sharded_file_concurrent(b: &mut Bencher) {
+    let dir = env::temp_dir().join("blacklog-bench-sharded-file");
+    fs::create_dir_all(&dir).unwrap();
+
+    {
+        let output = Arc::new(ShardedFileOutput::new(dir.join("app.log"), THREADS).unwrap());
+
+        b.iter(|| write_concurrently(output.clone()));
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[bench]
+fn stdout_acquire_per_write(b: &mut Bencher) {
+    b.iter(|| {
+        let stdout = io::stdout();
+        let mut wr = stdout.lock();
+        wr.write_all(b"").unwrap();
+    });
+}
+
+#[bench]
+fn stdout_held(b: &mut Bencher) {
+    let stdout = io::stdout();
+
+    b.iter(|| {
+        let mut wr = stdout.lock();
+        wr.write_all(b"").unwrap();
+    });
+}
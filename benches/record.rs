@@ -40,6 +40,16 @@ fn new(b: &mut Bencher) {
     });
 }
 
+/// The same as above, but with the thread id resolved, which is deferred until required.
+#[bench]
+fn new_with_thread(b: &mut Bencher) {
+    b.iter(|| {
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, line!(), module_path!(), &metalink);
+        test::black_box(rec.thread());
+    });
+}
+
 /// This benchmark demonstrates, that creating an inactive record is very cheap, even with meta
 /// attributes.
 #[bench]
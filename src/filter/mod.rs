@@ -1,10 +1,15 @@
 use record::Record;
 
 mod null;
+mod throttle;
+mod time;
 
 pub use self::null::NullFilter;
+pub use self::throttle::ThrottleFilter;
+pub use self::time::TimeWindowFilter;
 
 /// Filtering result.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum FilterAction {
     /// The record should be dropped immediately.
     Deny,
@@ -17,6 +22,17 @@ pub enum FilterAction {
     Neutral,
 }
 
+impl From<bool> for FilterAction {
+    /// Converts `true` into `Accept` and `false` into `Deny`.
+    fn from(val: bool) -> FilterAction {
+        if val {
+            FilterAction::Accept
+        } else {
+            FilterAction::Deny
+        }
+    }
+}
+
 /// Filters are responsible for filtering logging events.
 ///
 /// Filters may be configured in one of three locations:
@@ -37,3 +53,56 @@ impl<F> Filter for F
         self(rec)
     }
 }
+
+/// Wraps a boolean predicate making it a filter.
+///
+/// Records, for which the predicate returns `true`, are accepted, all others are denied. A
+/// wrapper is required, because closures returning `FilterAction` are already filters.
+///
+/// # Examples
+///
+/// ```
+/// use blacklog::Record;
+/// use blacklog::filter::{Filter, Predicate};
+///
+/// let filter: Box<Filter> = Box::new(Predicate(|rec: &Record| rec.severity() >= 3));
+/// ```
+pub struct Predicate<F>(pub F);
+
+impl<F> Filter for Predicate<F>
+    where F: Fn(&Record) -> bool + Send + Sync
+{
+    fn filter(&self, rec: &Record) -> FilterAction {
+        From::from((self.0)(rec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {MetaLink, Record};
+
+    use super::{Filter, FilterAction, Predicate};
+
+    #[test]
+    fn from_bool() {
+        assert_eq!(FilterAction::Accept, FilterAction::from(true));
+        assert_eq!(FilterAction::Deny, FilterAction::from(false));
+    }
+
+    #[test]
+    fn predicate() {
+        let filter = Predicate(|rec: &Record| rec.severity() >= 3);
+
+        let metalink = MetaLink::new(&[]);
+        assert_eq!(FilterAction::Deny, filter.filter(&Record::new(2, 0, "", &metalink)));
+        assert_eq!(FilterAction::Accept, filter.filter(&Record::new(3, 0, "", &metalink)));
+    }
+
+    #[test]
+    fn closure() {
+        let filter = |rec: &Record| -> FilterAction { (rec.severity() >= 3).into() };
+
+        let metalink = MetaLink::new(&[]);
+        assert_eq!(FilterAction::Deny, filter.filter(&Record::new(2, 0, "", &metalink)));
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::error;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use {Config, Format, Formatter, Registry};
+use factory::Factory;
+use record::Record;
+
+use super::{Filter, FilterAction};
+
+/// A filter that passes the first occurrence of a record and suppresses identical ones for the
+/// given cooldown period.
+///
+/// This is useful for flapping conditions: the first record is logged immediately, then all
+/// identical follow-ups are denied until the cooldown elapses, after which the next one is
+/// accepted again, starting a new cooldown.
+///
+/// Records are identified either by a meta attribute value, if the key is configured, or by the
+/// message together with the call site. Note, that handle filters are applied before the record
+/// activation, i.e. when its message is still empty, so identification degrades to the call site
+/// only. Records without the configured meta attribute are passed through as neutral.
+///
+/// The state is kept for every distinct key seen, so keys are expected to have low cardinality.
+pub struct ThrottleFilter {
+    cooldown: Duration,
+    key: Option<String>,
+    state: Mutex<HashMap<String, Instant>>,
+}
+
+impl ThrottleFilter {
+    /// Constructs a filter, that identifies records by their message and call site.
+    pub fn new(cooldown: Duration) -> ThrottleFilter {
+        ThrottleFilter {
+            cooldown: cooldown,
+            key: None,
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Identifies records by the value of the meta attribute with the given name instead.
+    pub fn key<K: Into<String>>(mut self, key: K) -> ThrottleFilter {
+        self.key = Some(key.into());
+        self
+    }
+
+    fn key_of(&self, rec: &Record) -> Option<String> {
+        match self.key {
+            Some(ref name) => {
+                let meta = match rec.iter().find(|meta| meta.name == name) {
+                    Some(meta) => meta,
+                    None => return None,
+                };
+
+                let mut buf = Vec::new();
+                if meta.value.format(&mut Formatter::new(&mut buf, Default::default())).is_err() {
+                    return None;
+                }
+
+                Some(String::from_utf8_lossy(&buf).into_owned())
+            }
+            None => Some(format!("{}:{}:{}", rec.module(), rec.line(), rec.message())),
+        }
+    }
+
+    fn throttle(&self, key: String, now: Instant) -> FilterAction {
+        let mut state = self.state.lock().unwrap();
+
+        match state.get(&key) {
+            Some(&timestamp) if now.duration_since(timestamp) < self.cooldown => {
+                return FilterAction::Deny;
+            }
+            Some(..) | None => {}
+        }
+
+        state.insert(key, now);
+        FilterAction::Accept
+    }
+}
+
+impl Filter for ThrottleFilter {
+    fn filter(&self, rec: &Record) -> FilterAction {
+        match self.key_of(rec) {
+            Some(key) => self.throttle(key, Instant::now()),
+            None => FilterAction::Neutral,
+        }
+    }
+}
+
+impl Factory for ThrottleFilter {
+    type Item = Filter;
+
+    fn ty() -> &'static str {
+        "throttle"
+    }
+
+    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Filter>, Box<error::Error>> {
+        let cooldown = cfg.find("cooldown")
+            .ok_or("field \"cooldown\" is required")?
+            .as_u64()
+            .ok_or("field \"cooldown\" must be an unsigned integer of milliseconds")?;
+
+        let mut res = ThrottleFilter::new(Duration::from_millis(cooldown));
+
+        if let Some(key) = cfg.find("key") {
+            res = res.key(key.as_string().ok_or("field \"key\" must be a string")?);
+        }
+
+        Ok(box res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use {Meta, MetaLink, Record};
+    use filter::{Filter, FilterAction};
+
+    use super::ThrottleFilter;
+
+    #[test]
+    fn throttle_bursts() {
+        let filter = ThrottleFilter::new(Duration::from_secs(10));
+        let now = Instant::now();
+
+        let mut accepted = 0;
+        for &offset in &[0, 1, 2, 9, 10, 11, 15] {
+            let now = now + Duration::from_secs(offset);
+            if filter.throttle("key".into(), now) == FilterAction::Accept {
+                accepted += 1;
+            }
+        }
+
+        // Accepted at 0s and 10s, when the first cooldown has elapsed.
+        assert_eq!(2, accepted);
+    }
+
+    #[test]
+    fn throttle_distinct_keys_independently() {
+        let filter = ThrottleFilter::new(Duration::from_secs(10));
+        let now = Instant::now();
+
+        assert_eq!(FilterAction::Accept, filter.throttle("first".into(), now));
+        assert_eq!(FilterAction::Accept, filter.throttle("second".into(), now));
+        assert_eq!(FilterAction::Deny, filter.throttle("first".into(), now));
+    }
+
+    #[test]
+    fn filter_by_meta_key() {
+        let filter = ThrottleFilter::new(Duration::from_secs(60)).key("host");
+
+        let host = "db1";
+        let meta = [Meta::new("host", &host)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        assert_eq!(FilterAction::Accept, filter.filter(&rec));
+        assert_eq!(FilterAction::Deny, filter.filter(&rec));
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+        assert_eq!(FilterAction::Neutral, filter.filter(&rec));
+    }
+
+    #[test]
+    fn filter_by_message() {
+        let filter = ThrottleFilter::new(Duration::from_secs(60));
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 42, "app", &metalink);
+        rec.activate(format_args!("connection refused"));
+
+        assert_eq!(FilterAction::Accept, filter.filter(&rec));
+        assert_eq!(FilterAction::Deny, filter.filter(&rec));
+
+        let mut rec = Record::new(0, 42, "app", &metalink);
+        rec.activate(format_args!("connection reset"));
+        assert_eq!(FilterAction::Accept, filter.filter(&rec));
+    }
+}
@@ -0,0 +1,164 @@
+use std::error;
+
+use chrono::{NaiveTime, UTC};
+use chrono::offset::local::Local;
+
+use {Config, Registry};
+use factory::Factory;
+use record::Record;
+
+use super::{Filter, FilterAction};
+
+/// A filter that accepts records created within the given time of day window.
+///
+/// The window is inclusive from both sides, i.e. `[start, end]`. If the start is greater than the
+/// end, the window is considered to wrap midnight, for example `22:00 - 06:00` accepts records
+/// created overnight. All other records are denied.
+///
+/// By default record timestamps are compared in UTC, which can be changed to the local timezone.
+///
+/// Filters are usually called before records are activated, i.e. before they are stamped. In that
+/// case the current time is used instead.
+pub struct TimeWindowFilter {
+    start: NaiveTime,
+    end: NaiveTime,
+    local: bool,
+}
+
+impl TimeWindowFilter {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> TimeWindowFilter {
+        TimeWindowFilter {
+            start: start,
+            end: end,
+            local: false,
+        }
+    }
+
+    /// Switches between comparing times in the local timezone and in UTC.
+    pub fn local(mut self, local: bool) -> TimeWindowFilter {
+        self.local = local;
+        self
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time <= self.end
+        } else {
+            self.start <= time || time <= self.end
+        }
+    }
+}
+
+impl Filter for TimeWindowFilter {
+    fn filter(&self, rec: &Record) -> FilterAction {
+        let datetime = if rec.is_active() {
+            rec.datetime()
+        } else {
+            UTC::now()
+        };
+
+        let time = if self.local {
+            datetime.with_timezone(&Local).naive_local().time()
+        } else {
+            datetime.naive_utc().time()
+        };
+
+        self.contains(time).into()
+    }
+}
+
+impl Factory for TimeWindowFilter {
+    type Item = Filter;
+
+    fn ty() -> &'static str {
+        "time_window"
+    }
+
+    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Filter>, Box<error::Error>> {
+        let time = |name: &str| -> Result<NaiveTime, Box<error::Error>> {
+            let time = cfg.find(name)
+                .ok_or_else(|| format!("field \"{}\" is required", name))?
+                .as_string()
+                .ok_or_else(|| format!("field \"{}\" must be a string", name))?;
+
+            NaiveTime::parse_from_str(time, "%H:%M").map_err(|err| {
+                format!("field \"{}\" must be in HH:MM format: {}", name, err).into()
+            })
+        };
+
+        let local = match cfg.find("timezone").map(|tz| tz.as_string()) {
+            Some(Some("local")) => true,
+            Some(Some("utc")) | None => false,
+            Some(..) => return Err("field \"timezone\" must be either \"utc\" or \"local\"".into()),
+        };
+
+        Ok(box TimeWindowFilter::new(time("start")?, time("end")?).local(local))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveTime, UTC};
+
+    use {MetaLink, Record};
+    use filter::{Filter, FilterAction};
+
+    use super::TimeWindowFilter;
+
+    fn time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms(hour, min, 0)
+    }
+
+    #[test]
+    fn contains() {
+        let filter = TimeWindowFilter::new(time(9, 0), time(18, 0));
+
+        assert!(filter.contains(time(9, 0)));
+        assert!(filter.contains(time(12, 30)));
+        assert!(filter.contains(time(18, 0)));
+        assert!(!filter.contains(time(8, 59)));
+        assert!(!filter.contains(time(18, 1)));
+        assert!(!filter.contains(time(0, 0)));
+    }
+
+    #[test]
+    fn contains_wrapping_midnight() {
+        let filter = TimeWindowFilter::new(time(22, 0), time(6, 0));
+
+        assert!(filter.contains(time(22, 0)));
+        assert!(filter.contains(time(23, 59)));
+        assert!(filter.contains(time(0, 0)));
+        assert!(filter.contains(time(6, 0)));
+        assert!(!filter.contains(time(6, 1)));
+        assert!(!filter.contains(time(12, 0)));
+        assert!(!filter.contains(time(21, 59)));
+    }
+
+    #[test]
+    fn filter_inactive_record_by_current_time() {
+        // Inactive records are not stamped yet, so the current time must be used instead.
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+        assert!(!rec.is_active());
+
+        let now = UTC::now().naive_utc().time();
+
+        let around = TimeWindowFilter::new(now - Duration::minutes(5), now + Duration::minutes(5));
+        assert_eq!(FilterAction::Accept, around.filter(&rec));
+
+        let later = TimeWindowFilter::new(now + Duration::hours(2), now + Duration::hours(3));
+        assert_eq!(FilterAction::Deny, later.filter(&rec));
+    }
+
+    #[test]
+    fn filter_active_record() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+
+        let time = rec.datetime().naive_utc().time();
+
+        let around = TimeWindowFilter::new(time, time);
+        assert_eq!(FilterAction::Accept, around.filter(&rec));
+    }
+}
@@ -0,0 +1,369 @@
+use std::error;
+use std::io::Error;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use {Config, Handle, Record, Registry};
+
+use factory::Factory;
+use filter::FilterAction;
+use handle::SyncHandle;
+use record::RecordBuf;
+
+/// Describes what to do with a record when the queue of an asynchronous handle is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverflowPolicy {
+    /// Block the caller until there is a free slot in the queue.
+    Block,
+    /// Silently drop the record.
+    Drop,
+}
+
+enum Event {
+    Record(RecordBuf),
+    Shutdown,
+}
+
+/// A handle that offloads handling of records to a background thread.
+///
+/// Unlike `ActorLogger`, which makes the entire logger asynchronous, this handle allows to move
+/// only slow parts, like disk or network I/O, out of the caller's thread while other handles of
+/// the same logger stay synchronous.
+///
+/// Records are queued through a bounded channel. When it's full the behavior is controlled by the
+/// `OverflowPolicy` given. Filtering is still performed synchronously by the wrapped handle.
+///
+/// Errors of the wrapped handle can't be returned to the caller, because records are handled in
+/// the background, instead they are counted, see `AsyncHandle::errors`.
+///
+/// Dropping the handle blocks until all queued records are processed.
+///
+/// When constructed from a config, the handle accepts the same `layout`, `outputs`, `filter` and
+/// `name` fields as `SyncHandle`, which is then wrapped, so formatting happens in the background
+/// thread as well. The queue capacity is configured using `capacity` field, 1024 by default, while
+/// the `overflow` field accepts either `"block"` (the default) or `"drop"`.
+pub struct AsyncHandle {
+    handle: Arc<Box<Handle>>,
+    /// Number of records, that the wrapped handle has failed to handle.
+    errors: Arc<AtomicUsize>,
+    policy: OverflowPolicy,
+    tx: Mutex<SyncSender<Event>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AsyncHandle {
+    /// Constructs a new asynchronous handle by wrapping the given one.
+    ///
+    /// The `capacity` specifies how many records can be queued before applying the overflow
+    /// policy.
+    pub fn new(handle: Box<Handle>, capacity: usize, policy: OverflowPolicy) -> AsyncHandle {
+        let handle = Arc::new(handle);
+        let errors = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = mpsc::sync_channel(capacity);
+
+        let thread = {
+            let handle = handle.clone();
+            let errors = errors.clone();
+
+            thread::spawn(move || {
+                for event in rx {
+                    match event {
+                        Event::Record(rec) => {
+                            rec.borrow_and(|rec| {
+                                if let Err(..) = handle.handle(rec) {
+                                    errors.fetch_add(1, Ordering::Relaxed);
+                                }
+                            });
+                        }
+                        Event::Shutdown => break,
+                    }
+                }
+            })
+        };
+
+        AsyncHandle {
+            handle: handle,
+            errors: errors,
+            policy: policy,
+            tx: Mutex::new(tx),
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the number of records, that the wrapped handle has failed to handle so far.
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+}
+
+impl Handle for AsyncHandle {
+    fn filter(&self, rec: &Record) -> FilterAction {
+        self.handle.filter(rec)
+    }
+
+    fn handle(&self, rec: &mut Record) -> Result<(), Error> {
+        let event = Event::Record(RecordBuf::from(&*rec));
+        let tx = self.tx.lock().unwrap();
+
+        match self.policy {
+            OverflowPolicy::Block => {
+                if let Err(..) = tx.send(event) {
+                    // The worker is gone, nothing to do here.
+                }
+            }
+            OverflowPolicy::Drop => {
+                if let Err(..) = tx.try_send(event) {
+                    // Either the queue is full or the worker is gone, drop the record.
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AsyncHandle {
+    fn drop(&mut self) {
+        if let Err(..) = self.tx.lock().unwrap().send(Event::Shutdown) {
+            // Ignore, but the thread should join anyway.
+        }
+        if let Err(..) = self.thread.take().unwrap().join() {
+            // The wrapped handle has panicked, nowhere to report it.
+        }
+    }
+}
+
+impl Factory for AsyncHandle {
+    type Item = Handle;
+
+    fn ty() -> &'static str {
+        "async"
+    }
+
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Handle>, Box<error::Error>> {
+        let handle = <SyncHandle as Factory>::from(cfg, registry)?;
+        let (capacity, policy) = queue(cfg)?;
+
+        Ok(box AsyncHandle::new(handle, capacity, policy))
+    }
+}
+
+/// Parses the queue capacity and the overflow policy from the given handle config.
+fn queue(cfg: &Config) -> Result<(usize, OverflowPolicy), Box<error::Error>> {
+    let capacity = match cfg.find("capacity") {
+        Some(capacity) => {
+            capacity.as_u64().ok_or("field \"capacity\" must be an integer")? as usize
+        }
+        None => 1024,
+    };
+
+    let policy = match cfg.find("overflow") {
+        Some(policy) => {
+            match policy.as_string() {
+                Some("block") => OverflowPolicy::Block,
+                Some("drop") => OverflowPolicy::Drop,
+                _ => return Err("field \"overflow\" must be either \"block\" or \"drop\"".into()),
+            }
+        }
+        None => OverflowPolicy::Block,
+    };
+
+    Ok((capacity, policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind};
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::{self, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    use serde_json;
+
+    use {Handle, MetaLink, Record, Registry};
+    use factory::Factory;
+    use handle::SyncHandle;
+    use layout::PatternLayout;
+    use output::MemoryOutput;
+
+    use super::{queue, AsyncHandle, OverflowPolicy};
+
+    struct CountingHandle {
+        counter: Arc<AtomicUsize>,
+        // Allows to block the worker thread until the test releases the lock.
+        gate: Arc<Mutex<()>>,
+        // Notifies the test that a record has been taken by the worker thread.
+        started: Mutex<Sender<()>>,
+    }
+
+    impl Handle for CountingHandle {
+        fn handle(&self, rec: &mut Record) -> Result<(), Error> {
+            assert_eq!("value", rec.message());
+
+            self.started.lock().unwrap().send(()).unwrap();
+            let _guard = self.gate.lock().unwrap();
+            self.counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    struct FailingHandle;
+
+    impl Handle for FailingHandle {
+        fn handle(&self, _rec: &mut Record) -> Result<(), Error> {
+            Err(Error::new(ErrorKind::Other, "failed"))
+        }
+    }
+
+    struct PanickingHandle;
+
+    impl Handle for PanickingHandle {
+        fn handle(&self, _rec: &mut Record) -> Result<(), Error> {
+            panic!("failed");
+        }
+    }
+
+    fn log(handle: &Handle) {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+
+        handle.handle(&mut rec).unwrap();
+    }
+
+    #[test]
+    fn records_are_processed_after_caller_returns() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let gate = Arc::new(Mutex::new(()));
+        let (tx, rx) = mpsc::channel();
+
+        let handle = AsyncHandle::new(box CountingHandle {
+            counter: counter.clone(),
+            gate: gate.clone(),
+            started: Mutex::new(tx),
+        }, 16, OverflowPolicy::Block);
+
+        {
+            let _guard = gate.lock().unwrap();
+            log(&handle);
+            log(&handle);
+
+            // The caller has returned, but the wrapped handle is still blocked.
+            rx.recv().unwrap();
+            assert_eq!(0, counter.load(Ordering::SeqCst));
+        }
+
+        drop(handle);
+
+        assert_eq!(2, counter.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn drop_records_on_overflow() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let gate = Arc::new(Mutex::new(()));
+        let (tx, rx) = mpsc::channel();
+
+        let handle = AsyncHandle::new(box CountingHandle {
+            counter: counter.clone(),
+            gate: gate.clone(),
+            started: Mutex::new(tx),
+        }, 1, OverflowPolicy::Drop);
+
+        {
+            let _guard = gate.lock().unwrap();
+
+            // The first record is taken by the worker, which is then blocked.
+            log(&handle);
+            rx.recv().unwrap();
+
+            // The second one occupies the only slot in the queue, while the third one is dropped.
+            log(&handle);
+            log(&handle);
+        }
+
+        drop(handle);
+
+        assert_eq!(2, counter.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn count_errors() {
+        let handle = AsyncHandle::new(box FailingHandle, 16, OverflowPolicy::Block);
+        assert_eq!(0, handle.errors());
+
+        log(&handle);
+        log(&handle);
+
+        // Records are handled in the background, so wait for the worker for a while.
+        for _ in 0..1000 {
+            if handle.errors() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(2, handle.errors());
+    }
+
+    #[test]
+    fn drop_after_handle_panicked() {
+        log(&AsyncHandle::new(box PanickingHandle, 16, OverflowPolicy::Block));
+    }
+
+    #[test]
+    fn format_and_write_in_background() {
+        let output = MemoryOutput::new();
+        let layout = PatternLayout::new("[{severity:d}] {message}").unwrap();
+
+        let handle = AsyncHandle::new(box SyncHandle::new(box layout, vec![box output.clone()]),
+            16, OverflowPolicy::Block);
+
+        log(&handle);
+        log(&handle);
+
+        // Dropping the handle waits for all queued records.
+        drop(handle);
+
+        assert_eq!(vec![b"[0] value".to_vec(), b"[0] value".to_vec()], output.messages());
+    }
+
+    #[test]
+    fn queue_from_config() {
+        let cfg = serde_json::from_str(r#"{"capacity": 16, "overflow": "drop"}"#).unwrap();
+        assert_eq!((16, OverflowPolicy::Drop), queue(&cfg).unwrap());
+
+        let cfg = serde_json::from_str(r#"{"overflow": "block"}"#).unwrap();
+        assert_eq!((1024, OverflowPolicy::Block), queue(&cfg).unwrap());
+
+        let cfg = serde_json::from_str(r#"{}"#).unwrap();
+        assert_eq!((1024, OverflowPolicy::Block), queue(&cfg).unwrap());
+    }
+
+    #[test]
+    fn fail_queue_from_invalid_config() {
+        let cfg = serde_json::from_str(r#"{"capacity": "16"}"#).unwrap();
+        assert!(queue(&cfg).is_err());
+
+        let cfg = serde_json::from_str(r#"{"overflow": "reject"}"#).unwrap();
+        assert!(queue(&cfg).is_err());
+    }
+
+    #[test]
+    fn from_config() {
+        let cfg = serde_json::from_str(r#"{
+            "type": "async",
+            "capacity": 16,
+            "overflow": "drop",
+            "layout": {"type": "pattern", "pattern": "{message}"},
+            "outputs": [{"type": "null"}]
+        }"#).unwrap();
+
+        let handle = <AsyncHandle as Factory>::from(&cfg, &Registry::new()).unwrap();
+        log(&*handle);
+    }
+}
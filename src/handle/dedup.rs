@@ -0,0 +1,148 @@
+use std::io::Error;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use {Handle, Record};
+
+use layout::Layout;
+use output::Output;
+
+struct State {
+    /// Last rendered line written into outputs.
+    last: Vec<u8>,
+    /// Number of suppressed repeats of the last line.
+    repeated: usize,
+    /// Time point the last line was written at.
+    timestamp: Instant,
+}
+
+/// A handle that suppresses identical consecutive lines within the given time window.
+///
+/// Unlike message-level filtering this handle compares fully rendered lines, i.e. after layout,
+/// which makes it suitable for collapsing repeated stack traces and other multiline noise.
+/// Suppressed repeats are reported by a summary line, which is written right before the next
+/// line, that passes through, i.e. either a different line or the same one after the window has
+/// expired. There is no timer, so the summary is delayed until the next record arrives.
+///
+/// # Note
+///
+/// Repeats still pending when the handle is dropped are never reported, because there is no
+/// record to write the summary with.
+pub struct DedupHandle {
+    layout: Box<Layout>,
+    outputs: Vec<Box<Output>>,
+    window: Duration,
+    state: Mutex<State>,
+}
+
+impl DedupHandle {
+    pub fn new(layout: Box<Layout>, outputs: Vec<Box<Output>>, window: Duration) -> DedupHandle {
+        DedupHandle {
+            layout: layout,
+            outputs: outputs,
+            window: window,
+            state: Mutex::new(State {
+                last: Vec::new(),
+                repeated: 0,
+                timestamp: Instant::now(),
+            }),
+        }
+    }
+
+    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+        for output in &self.outputs {
+            output.write(rec, message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Handle for DedupHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), Error> {
+        let mut wr = Vec::new();
+        self.layout.format(rec, &mut wr)?;
+
+        let mut state = self.state.lock().unwrap();
+
+        if state.last == wr && state.timestamp.elapsed() < self.window {
+            state.repeated += 1;
+            return Ok(());
+        }
+
+        if state.repeated > 0 {
+            let summary = format!("last message repeated {} times", state.repeated);
+            self.write(rec, summary.as_bytes())?;
+        }
+
+        self.write(rec, &wr)?;
+
+        state.last = wr;
+        state.repeated = 0;
+        state.timestamp = Instant::now();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use {Handle, MetaLink, Output, Record};
+    use layout::PatternLayout;
+
+    use super::DedupHandle;
+
+    struct MockOutput {
+        messages: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Output for MockOutput {
+        fn write(&self, _rec: &Record, message: &[u8]) -> Result<(), ::std::io::Error> {
+            self.messages.lock().unwrap().push(from_utf8(message).unwrap().into());
+            Ok(())
+        }
+    }
+
+    fn handle(window: Duration) -> (DedupHandle, Arc<Mutex<Vec<String>>>) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let output = MockOutput { messages: messages.clone() };
+        let handle = DedupHandle::new(box PatternLayout::new("{message}").unwrap(),
+            vec![box output], window);
+
+        (handle, messages)
+    }
+
+    fn log(handle: &DedupHandle, message: &str) {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("{}", message));
+        handle.handle(&mut rec).unwrap();
+    }
+
+    #[test]
+    fn suppress_repeats() {
+        let (handle, messages) = handle(Duration::from_secs(60));
+
+        log(&handle, "first");
+        log(&handle, "first");
+        log(&handle, "first");
+        log(&handle, "second");
+
+        let expected = vec!["first", "last message repeated 2 times", "second"];
+        assert_eq!(expected, *messages.lock().unwrap());
+    }
+
+    #[test]
+    fn pass_repeats_after_window() {
+        let (handle, messages) = handle(Duration::from_secs(0));
+
+        log(&handle, "first");
+        log(&handle, "first");
+
+        assert_eq!(vec!["first", "first"], *messages.lock().unwrap());
+    }
+}
@@ -2,64 +2,192 @@ use std::io::{stdout, Write};
 
 use libc;
 
-use meta::format::{FormatSpec, Formatter};
+use meta::format::{Alignment, Format, FormatSpec, Formatter};
 use handle::Handle;
 use record::Record;
+use severity::severity_color;
 
+/// Minimum width of the severity field, so the following fields start at the same column for all
+/// one- and two-character severities.
+const SEVERITY_WIDTH: usize = 2;
+
+/// A handle, that prints records into the terminal in an eye-candy colored manner.
+///
+/// Severities are colored according to the crate's severity levels, i.e. `log::LogLevel::Error`
+/// is red and `log::LogLevel::Warn` is yellow. See `severity_color` for the full mapping.
+///
+/// Intended for development only.
 pub struct Dev;
 
+impl Dev {
+    /// Replaces the severity color mapping with the given one.
+    ///
+    /// The function must return a 256-color ANSI terminal palette index, which is useful for
+    /// custom severity scales.
+    pub fn color(self, color: fn(i32) -> u8) -> ColoredDev {
+        ColoredDev {
+            color: color,
+        }
+    }
+}
+
 impl Handle for Dev {
     fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
-        let mut buf = Vec::with_capacity(512);
+        print(rec, severity_color)
+    }
+}
 
-        // TODO: Use nearly liked terminal crate for coloring.
-        write!(buf, "\x1B[2;m")?;
-        write!(buf, "{}", rec.datetime().format("%Y-%m-%d %H:%M:%S%.6f"))?;
-        write!(buf, "\x1B[0m")?;
+/// A development handle with custom severity colors.
+///
+/// Constructed using `Dev::color`, otherwise behaves exactly like `Dev`.
+pub struct ColoredDev {
+    color: fn(i32) -> u8,
+}
 
-        buf.write_all(b" ")?;
-        let mut spec = FormatSpec::default();
-        spec.precision = Some(1);
-        let sev = rec.severity();
-        write!(buf, "\x1B[")?;
-        let color = match sev {
-            1 => 9,
-            2 => 3,
-            3 => 2,
-            4 => 10,
-            _ => 11,
-        };
-        write!(buf, "38;5;{}m", color)?;
-        rec.severity_format()(sev, &mut Formatter::new(&mut buf, spec))?;
-        write!(buf, "\x1B[0m")?;
+impl Handle for ColoredDev {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        print(rec, self.color)
+    }
+}
 
-        write!(buf, "\x1B[2;m")?;
-        write!(buf, " [{:#x}/{}]", rec.thread(), unsafe { libc::getpid() })?;
+fn print(rec: &Record, color: fn(i32) -> u8) -> Result<(), ::std::io::Error> {
+    let mut buf = Vec::with_capacity(512);
+    format(rec, color, &mut buf)?;
 
-        buf.write_all(b" - ")?;
-        write!(buf, "\x1B[0m")?;
+    let out = stdout();
+    let mut wr = out.lock();
+    wr.write_all(&buf)
+}
+
+fn format(rec: &Record, color: fn(i32) -> u8, buf: &mut Vec<u8>) -> Result<(), ::std::io::Error> {
+    // TODO: Use nearly liked terminal crate for coloring.
+    write!(buf, "\x1B[2;m")?;
+    write!(buf, "{}", rec.datetime().format("%Y-%m-%d %H:%M:%S%.6f"))?;
+    write!(buf, "\x1B[0m")?;
+
+    buf.write_all(b" ")?;
+    let mut spec = FormatSpec::default();
+    spec.align = Alignment::AlignLeft;
+    spec.precision = Some(1);
+    spec.width = SEVERITY_WIDTH;
+    let sev = rec.severity();
+    write!(buf, "\x1B[")?;
+    write!(buf, "38;5;{}m", color(sev))?;
+    rec.severity_name().format(&mut Formatter::new(buf, spec))?;
+    write!(buf, "\x1B[0m")?;
 
+    write!(buf, "\x1B[2;m")?;
+    write!(buf, " [{:#x}/{}]", rec.thread(), unsafe { libc::getpid() })?;
+
+    buf.write_all(b" - ")?;
+    write!(buf, "\x1B[0m")?;
+
+    write!(buf, "\x1B[")?;
+    write!(buf, "37m")?;
+    buf.write_all(rec.message().as_bytes())?;
+    write!(buf, "\x1B[0m")?;
+    buf.write_all(b"\r\n")?;
+
+    for meta in rec.iter() {
+        buf.write_all(b"\t")?;
         write!(buf, "\x1B[")?;
         write!(buf, "37m")?;
-        buf.write_all(rec.message().as_bytes())?;
+        write!(buf, "{}", meta.name)?;
+        write!(buf, "\x1B[0m")?;
+        buf.write_all(b": ")?;
+        write!(buf, "\x1B[2;m")?;
+        meta.value.format(&mut Formatter::new(buf, Default::default()))?;
         write!(buf, "\x1B[0m")?;
         buf.write_all(b"\r\n")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use log::LogLevel;
+
+    use {MetaLink, Record};
+    use severity::severity_color;
+
+    use super::{format, Dev};
+
+    fn render(color: fn(i32) -> u8, sev: i32) -> String {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(sev, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+
+        let mut buf = Vec::new();
+        format(&rec, color, &mut buf).unwrap();
+
+        from_utf8(&buf[..]).unwrap().into()
+    }
+
+    /// Removes ANSI escape sequences, leaving only visible characters.
+    fn strip(text: &str) -> String {
+        let mut result = String::new();
+        let mut escaped = false;
+
+        for ch in text.chars() {
+            match ch {
+                '\x1B' => escaped = true,
+                'm' if escaped => escaped = false,
+                ch if !escaped => result.push(ch),
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    #[test]
+    fn default_color() {
+        assert!(render(severity_color, 4).contains("\x1B[38;5;9m4 \x1B[0m"));
+        assert!(render(severity_color, 1).contains("\x1B[38;5;10m1 \x1B[0m"));
+        assert!(render(severity_color, 7).contains("\x1B[38;5;9m7 \x1B[0m"));
+    }
+
+    #[test]
+    fn color_log_levels() {
+        let levels = [
+            (LogLevel::Error, "Error", 9),
+            (LogLevel::Warn, "Warn", 3),
+            (LogLevel::Info, "Info", 2),
+            (LogLevel::Debug, "Debug", 10),
+            (LogLevel::Trace, "Trace", 11),
+        ];
+
+        for &(level, name, color) in levels.iter() {
+            let metalink = MetaLink::new(&[]);
+            let mut rec = Record::new(level, 0, "", &metalink);
+            rec.activate(format_args!("value"));
+
+            let mut buf = Vec::new();
+            format(&rec, severity_color, &mut buf).unwrap();
+
+            let expected = format!("\x1B[38;5;{}m{} \x1B[0m", color, &name[..1]);
+            assert!(from_utf8(&buf[..]).unwrap().contains(&expected), "{:?}", level);
+        }
+    }
+
+    #[test]
+    fn severity_padded_to_fixed_width() {
+        let offsets = [-1, 0, 1, 4, 10].iter()
+            .map(|&sev| strip(&render(severity_color, sev)).find(" [").unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(offsets.iter().all(|&offset| offset == offsets[0]), "{:?}", offsets);
+    }
 
-        for meta in rec.iter() {
-            buf.write_all(b"\t")?;
-            write!(buf, "\x1B[")?;
-            write!(buf, "37m")?;
-            write!(buf, "{}", meta.name)?;
-            write!(buf, "\x1B[0m")?;
-            buf.write_all(b": ")?;
-            write!(buf, "\x1B[2;m")?;
-            meta.value.format(&mut Formatter::new(&mut buf, Default::default()))?;
-            write!(buf, "\x1B[0m")?;
-            buf.write_all(b"\r\n")?;
+    #[test]
+    fn custom_color() {
+        fn color(sev: i32) -> u8 {
+            160 + sev as u8
         }
 
-        let out = stdout();
-        let mut wr = out.lock();
-        wr.write_all(&buf)
+        assert!(render(Dev.color(color).color, 7).contains("\x1B[38;5;167m7 \x1B[0m"));
     }
 }
@@ -1,9 +1,15 @@
 use Record;
 
+use filter::FilterAction;
+
+mod async;
+mod dedup;
 mod dev;
 mod sync;
 
-pub use self::dev::Dev;
+pub use self::async::{AsyncHandle, OverflowPolicy};
+pub use self::dedup::DedupHandle;
+pub use self::dev::{ColoredDev, Dev};
 pub use self::sync::SyncHandle;
 
 /// Combines a filter, layout and outputs together.
@@ -11,10 +17,22 @@ pub use self::sync::SyncHandle;
 /// Handles are responsible for combining a filter, layout and many outputs together becoming an
 /// entry point for logging event after primary filtering in the logger.
 pub trait Handle: Send + Sync {
+    /// Filters the given record before it is activated.
+    ///
+    /// Loggers call this method before activating a record, which allows to avoid message
+    /// formatting entirely if all handles deny a record. That's why neither message nor timestamp
+    /// are available here.
+    ///
+    /// By default a handle is neutral to all records.
+    fn filter(&self, rec: &Record) -> FilterAction {
+        let _ = rec;
+        FilterAction::Neutral
+    }
+
     /// Handles the given record.
     ///
-    /// Typically this method should filter, mutate and format a record and send it to one or many
-    /// outputs. Implementations are free to do it either in synchronous or asynchronous way.
+    /// Typically this method should mutate and format a record and send it to one or many outputs.
+    /// Implementations are free to do it either in synchronous or asynchronous way.
     ///
     /// Note, that filtering out a record is not considered as error.
     fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error>;
@@ -1,19 +1,62 @@
-use {Config, Handle, Record, Registry};
+use {Config, Handle, Meta, Record, Registry};
 
+use filter::{Filter, FilterAction, NullFilter};
 use layout::Layout;
 use output::Output;
 
 use factory::Factory;
 
+/// A handle, that formats records using the given layout and passes them to all of its outputs
+/// sequentially in the caller's thread.
+///
+/// A handle may have a name, which is useful for debugging when several handles feed the same
+/// logger. The name is attached to records as the `handle` meta attribute while they are being
+/// formatted and written, so it can be rendered by layouts, for example using `{handle}` token in
+/// a pattern.
+///
+/// The `handle` attribute is appended after the record's own attributes, so it shadows any user
+/// attribute with the same name in lookups taking the last one, like `Record::meta_map`. Note,
+/// that pattern tokens take the first attribute with a name and thus still see the user one.
 pub struct SyncHandle {
+    name: Option<String>,
+    filter: Box<Filter>,
     layout: Box<Layout>,
     outputs: Vec<Box<Output>>,
+    /// Whether all outputs are structured, i.e. there is no need to format a record at all.
+    structured: bool,
 }
 
-impl Handle for SyncHandle {
-    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+impl SyncHandle {
+    pub fn new(layout: Box<Layout>, outputs: Vec<Box<Output>>) -> SyncHandle {
+        let structured = !outputs.is_empty() && outputs.iter().all(|output| output.structured());
+
+        SyncHandle {
+            name: None,
+            filter: box NullFilter,
+            layout: layout,
+            outputs: outputs,
+            structured: structured,
+        }
+    }
+
+    /// Sets the filter, that is applied to records before their activation.
+    pub fn with_filter(mut self, filter: Box<Filter>) -> SyncHandle {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets the name of this handle, which is attached to records as the `handle` meta attribute.
+    pub fn with_name(mut self, name: &str) -> SyncHandle {
+        self.name = Some(name.into());
+        self
+    }
+
+    fn write(&self, rec: &Record) -> Result<(), ::std::io::Error> {
         let mut wr = Vec::new();
-        self.layout.format(rec, &mut wr).unwrap();
+
+        if !self.structured {
+            self.layout.format(rec, &mut wr)?;
+        }
 
         for output in &self.outputs {
             output.write(rec, &wr)?;
@@ -23,6 +66,25 @@ impl Handle for SyncHandle {
     }
 }
 
+impl Handle for SyncHandle {
+    fn filter(&self, rec: &Record) -> FilterAction {
+        self.filter.filter(rec)
+    }
+
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        match self.name {
+            Some(ref name) => {
+                let meta = [Meta::new("handle", name)];
+
+                let mut result = Ok(());
+                rec.with_meta(&meta, |rec| result = self.write(rec));
+                result
+            }
+            None => self.write(rec),
+        }
+    }
+}
+
 impl Factory for SyncHandle {
     type Item = Handle;
 
@@ -39,13 +101,97 @@ impl Factory for SyncHandle {
             .ok_or("section \"outputs\" must be an array")?
             .iter()
             .map(|o| registry.output(o))
-            .collect()?;
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let res = SyncHandle {
-            layout: layout,
-            outputs: outputs,
-        };
+        let mut res = SyncHandle::new(layout, outputs);
+
+        if let Some(filter) = cfg.find("filter") {
+            res = res.with_filter(registry.filter(filter)?);
+        }
+
+        if let Some(name) = cfg.find("name") {
+            res = res.with_name(name.as_string().ok_or("field \"name\" must be a string")?);
+        }
 
         Ok(box res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use {Handle, Layout, MetaLink, Output, Record};
+    use layout::{Error, PatternLayout};
+    use output::InMemoryRingOutput;
+    use record::RecordBuf;
+
+    use super::SyncHandle;
+
+    struct PanicLayout;
+
+    impl Layout for PanicLayout {
+        fn format(&self, _rec: &Record, _wr: &mut Write) -> Result<(), Error> {
+            panic!("layout must not be invoked for structured outputs");
+        }
+    }
+
+    /// Structured output example, that stores owned records instead of rendered messages.
+    struct StructuredOutput {
+        records: Arc<Mutex<Vec<RecordBuf>>>,
+    }
+
+    impl Output for StructuredOutput {
+        fn write(&self, rec: &Record, _message: &[u8]) -> Result<(), ::std::io::Error> {
+            self.records.lock().unwrap().push(RecordBuf::from(rec));
+            Ok(())
+        }
+
+        fn structured(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn skip_layout_for_structured_outputs() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let output = StructuredOutput { records: records.clone() };
+        let handle = SyncHandle::new(box PanicLayout, vec![box output]);
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+        handle.handle(&mut rec).unwrap();
+
+        let records = records.lock().unwrap();
+        assert_eq!(1, records.len());
+        records[0].borrow_and(|rec| {
+            assert_eq!("value", rec.message());
+        });
+    }
+
+    #[test]
+    fn render_handle_name() {
+        let first = InMemoryRingOutput::new(10);
+        let second = InMemoryRingOutput::new(10);
+
+        let handles = vec![
+            SyncHandle::new(box PatternLayout::new("{handle}: {message}").unwrap(),
+                vec![box first.clone()]).with_name("first"),
+            SyncHandle::new(box PatternLayout::new("{handle}: {message}").unwrap(),
+                vec![box second.clone()]).with_name("second"),
+        ];
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+
+        for handle in &handles {
+            handle.handle(&mut rec).unwrap();
+        }
+
+        assert_eq!(vec!["first: value"], first.lines());
+        assert_eq!(vec!["second: value"], second.lines());
+    }
+}
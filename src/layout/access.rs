@@ -0,0 +1,180 @@
+use std::error;
+use std::io::Write;
+
+use {Config, Format, Formatter, Record, Registry};
+use factory::Factory;
+
+use super::{Error, Layout};
+
+/// A layout, that formats records as lines of an HTTP access log in the Combined Log Format, used
+/// by Apache and Nginx.
+///
+/// Each line looks like `%h %l %u [%t] "%r" %>s %b "%{Referer}i" "%{User-Agent}i"`, where fields
+/// are taken from the following meta attributes: `remote_addr`, `remote_user`, `request`,
+/// `status`, `bytes`, `referer` and `user_agent`. The identity field is never known and the time
+/// is the record's timestamp. Missing or empty attributes are substituted with `-`.
+///
+/// Quoted fields have quotes and backslashes escaped, so the output can be parsed back by common
+/// log analyzers. Both the message and the severity are ignored.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::Logger;
+/// use blacklog::layout::AccessLogLayout;
+/// use blacklog::logger::SyncLogger;
+///
+/// fn main() {
+///     let log = SyncLogger::new(vec![]);
+///
+///     log!(log, 2, "request processed", {
+///         remote_addr: "127.0.0.1",
+///         request: "GET /index.html HTTP/1.1",
+///         status: 200,
+///         bytes: 2326,
+///     });
+/// }
+/// ```
+pub struct AccessLogLayout;
+
+impl AccessLogLayout {
+    pub fn new() -> AccessLogLayout {
+        AccessLogLayout
+    }
+
+    /// Formats the value of the meta attribute with the given name, returning an empty string if
+    /// there is no such attribute.
+    fn field(rec: &Record, name: &str) -> Result<String, Error> {
+        let mut buf = Vec::new();
+
+        if let Some(meta) = rec.iter().find(|meta| meta.name == name) {
+            meta.value.format(&mut Formatter::new(&mut buf, Default::default()))?;
+        }
+
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    fn write_plain(wr: &mut Write, value: &str) -> Result<(), Error> {
+        if value.is_empty() {
+            wr.write_all(b"-")?;
+        } else {
+            wr.write_all(value.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_quoted(wr: &mut Write, value: &str) -> Result<(), Error> {
+        wr.write_all(b"\"")?;
+
+        if value.is_empty() {
+            wr.write_all(b"-")?;
+        } else {
+            for ch in value.chars() {
+                match ch {
+                    '"' => wr.write_all(b"\\\"")?,
+                    '\\' => wr.write_all(b"\\\\")?,
+                    ch => write!(wr, "{}", ch)?,
+                }
+            }
+        }
+
+        wr.write_all(b"\"")?;
+
+        Ok(())
+    }
+}
+
+impl Default for AccessLogLayout {
+    fn default() -> AccessLogLayout {
+        AccessLogLayout::new()
+    }
+}
+
+impl Layout for AccessLogLayout {
+    fn format(&self, rec: &Record, wr: &mut Write) -> Result<(), Error> {
+        AccessLogLayout::write_plain(wr, &AccessLogLayout::field(rec, "remote_addr")?)?;
+        wr.write_all(b" - ")?;
+        AccessLogLayout::write_plain(wr, &AccessLogLayout::field(rec, "remote_user")?)?;
+        write!(wr, " [{}] ", rec.datetime().format("%d/%b/%Y:%H:%M:%S %z"))?;
+        AccessLogLayout::write_quoted(wr, &AccessLogLayout::field(rec, "request")?)?;
+        wr.write_all(b" ")?;
+        AccessLogLayout::write_plain(wr, &AccessLogLayout::field(rec, "status")?)?;
+        wr.write_all(b" ")?;
+        AccessLogLayout::write_plain(wr, &AccessLogLayout::field(rec, "bytes")?)?;
+        wr.write_all(b" ")?;
+        AccessLogLayout::write_quoted(wr, &AccessLogLayout::field(rec, "referer")?)?;
+        wr.write_all(b" ")?;
+        AccessLogLayout::write_quoted(wr, &AccessLogLayout::field(rec, "user_agent")?)?;
+
+        Ok(())
+    }
+}
+
+impl Factory for AccessLogLayout {
+    type Item = Layout;
+
+    fn ty() -> &'static str {
+        "access_log"
+    }
+
+    fn from(_cfg: &Config, _registry: &Registry) -> Result<Box<Layout>, Box<error::Error>> {
+        Ok(box AccessLogLayout::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Layout, Meta, MetaLink, Record};
+
+    use super::AccessLogLayout;
+
+    #[test]
+    fn format() {
+        let remote_addr = "127.0.0.1";
+        let remote_user = "frank";
+        let request = "GET /apache_pb.gif HTTP/1.0";
+        let status = 200;
+        let bytes = 2326;
+        let referer = "http://www.example.com/start.html";
+        let user_agent = "Mozilla/4.08 [en] (Win98; I ;Nav)";
+        let meta = [
+            Meta::new("remote_addr", &remote_addr),
+            Meta::new("remote_user", &remote_user),
+            Meta::new("request", &request),
+            Meta::new("status", &status),
+            Meta::new("bytes", &bytes),
+            Meta::new("referer", &referer),
+            Meta::new("user_agent", &user_agent),
+        ];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(2, 0, "", &metalink);
+        rec.activate(format_args!("request processed"));
+
+        let expected = format!(concat!(r#"127.0.0.1 - frank [{}] "GET /apache_pb.gif HTTP/1.0" "#,
+            r#"200 2326 "http://www.example.com/start.html" "Mozilla/4.08 [en] (Win98; I ;Nav)""#),
+            rec.datetime().format("%d/%b/%Y:%H:%M:%S +0000"));
+
+        assert_eq!(expected, AccessLogLayout.format_string(&rec).unwrap());
+    }
+
+    #[test]
+    fn format_missing_fields() {
+        let request = r#"GET /"quoted"\ HTTP/1.1"#;
+        let status = 404;
+        let meta = [
+            Meta::new("request", &request),
+            Meta::new("status", &status),
+        ];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(2, 0, "", &metalink);
+        rec.activate(format_args!("request processed"));
+
+        let expected = format!(r#"- - - [{}] "GET /\"quoted\"\\ HTTP/1.1" 404 - "-" "-""#,
+            rec.datetime().format("%d/%b/%Y:%H:%M:%S +0000"));
+
+        assert_eq!(expected, AccessLogLayout.format_string(&rec).unwrap());
+    }
+}
@@ -0,0 +1,92 @@
+use std::error;
+use std::io::Write;
+
+use {Config, Record, Registry};
+use factory::Factory;
+
+use super::{Error, Layout};
+
+/// A layout that renders a record twice - as a human readable line followed by its structured
+/// form on the next line.
+///
+/// This is mostly useful for local development, where a readable line helps to quickly scan
+/// through logs, while a structured trailer, like JSON, can be copy-pasted for further
+/// investigation.
+pub struct CombinedLayout {
+    primary: Box<Layout>,
+    structured: Box<Layout>,
+}
+
+impl CombinedLayout {
+    pub fn new(primary: Box<Layout>, structured: Box<Layout>) -> CombinedLayout {
+        CombinedLayout {
+            primary: primary,
+            structured: structured,
+        }
+    }
+}
+
+impl Layout for CombinedLayout {
+    fn format(&self, rec: &Record, wr: &mut Write) -> Result<(), Error> {
+        self.primary.format(rec, wr)?;
+        wr.write_all(b"\n")?;
+        self.structured.format(rec, wr)
+    }
+}
+
+impl Factory for CombinedLayout {
+    type Item = Layout;
+
+    fn ty() -> &'static str {
+        "combined"
+    }
+
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Layout>, Box<error::Error>> {
+        let primary = registry.layout(cfg.find("primary")
+            .ok_or("field \"primary\" is required")?)?;
+        let structured = registry.layout(cfg.find("structured")
+            .ok_or("field \"structured\" is required")?)?;
+
+        Ok(box CombinedLayout::new(primary, structured))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use serde_json::{self, Value};
+
+    use {Layout, Meta, MetaLink, Record};
+    use layout::{JsonLayout, PatternLayout};
+
+    use super::CombinedLayout;
+
+    #[test]
+    fn format() {
+        let layout = CombinedLayout::new(
+            box PatternLayout::new("{severity}: {message}").unwrap(),
+            box JsonLayout::new());
+
+        let path = "/var/www";
+        let meta = [Meta::new("path", &path)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(2, 42, "app", &metalink);
+        rec.activate(format_args!("file does not exist"));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        let out = from_utf8(&buf[..]).unwrap();
+        let mut lines = out.lines();
+
+        assert_eq!(Some("2: file does not exist"), lines.next());
+
+        let value: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(Some("/var/www"), value.find("path").and_then(|v| v.as_string()));
+        assert_eq!(Some("file does not exist"),
+            value.find("message").and_then(|v| v.as_string()));
+
+        assert_eq!(None, lines.next());
+    }
+}
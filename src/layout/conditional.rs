@@ -0,0 +1,93 @@
+use std::io::Write;
+
+use filter::{Filter, FilterAction};
+use record::Record;
+
+use super::{Error, Layout};
+
+/// A layout that chooses one of two layouts for each record using the given predicate filter.
+///
+/// Records accepted by the predicate are formatted using the first layout, all others - denied
+/// or those the predicate is neutral to - using the second one. This allows, for example, to
+/// format warnings with all details available while keeping debug records terse.
+pub struct ConditionalLayout {
+    predicate: Box<Filter>,
+    when_true: Box<Layout>,
+    when_false: Box<Layout>,
+}
+
+impl ConditionalLayout {
+    pub fn new(predicate: Box<Filter>, when_true: Box<Layout>, when_false: Box<Layout>) ->
+        ConditionalLayout
+    {
+        ConditionalLayout {
+            predicate: predicate,
+            when_true: when_true,
+            when_false: when_false,
+        }
+    }
+}
+
+impl Layout for ConditionalLayout {
+    fn format(&self, rec: &Record, wr: &mut Write) -> Result<(), Error> {
+        match self.predicate.filter(rec) {
+            FilterAction::Accept => self.when_true.format(rec, wr),
+            FilterAction::Deny | FilterAction::Neutral => self.when_false.format(rec, wr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use log::LogLevel;
+
+    use {Layout, MetaLink, Record};
+    use filter::FilterAction;
+    use layout::PatternLayout;
+
+    use super::ConditionalLayout;
+
+    fn layout() -> ConditionalLayout {
+        let predicate = |rec: &Record| {
+            if rec.severity() >= 3 {
+                FilterAction::Accept
+            } else {
+                FilterAction::Deny
+            }
+        };
+
+        ConditionalLayout::new(box predicate,
+            box PatternLayout::new("{severity}: {message} [{module}:{line}]").unwrap(),
+            box PatternLayout::new("{message}").unwrap())
+    }
+
+    #[test]
+    fn format_verbose_when_accepted() {
+        let layout = layout();
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(LogLevel::Warn, 42, "app", &metalink);
+        rec.activate(format_args!("value"));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("Warn: value [app:42]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_terse_when_denied() {
+        let layout = layout();
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(LogLevel::Info, 42, "app", &metalink);
+        rec.activate(format_args!("value"));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("value", from_utf8(&buf[..]).unwrap());
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::error;
+use std::io::{self, ErrorKind, Write};
+
+use serde_json::{self, Value};
+
+use {Config, Format, Formatter, Record, Registry};
+use factory::Factory;
+
+use super::{Error, Layout};
+
+/// A layout, that formats records as JSON objects.
+///
+/// Each record is represented as a single object with the following fields: `message`,
+/// `severity`, `timestamp`, `module`, `line` and `thread`. All meta attributes are placed at the
+/// same level as formatted strings, overriding builtin fields with the same name.
+///
+/// By default the output is compact, i.e. it is written on a single line, which perfectly fits to
+/// line-based outputs, like files or terminal. Pretty mode is intended for debugging only, because
+/// it spans a record over multiple lines - line-based outputs will just write them as is, but
+/// line-oriented consumers (`tail | grep`, log shippers) will fail to treat them as a single
+/// record.
+///
+/// To reduce noise the layout can be configured to suppress empty values, i.e. string fields and
+/// meta attributes, which give zero-length output when formatted, are omitted.
+pub struct JsonLayout {
+    pretty: bool,
+    suppress_empty: bool,
+}
+
+impl JsonLayout {
+    /// Constructs a new compact JSON layout.
+    pub fn new() -> JsonLayout {
+        JsonLayout {
+            pretty: false,
+            suppress_empty: false,
+        }
+    }
+
+    /// Switches between pretty multi-line and compact single-line output.
+    pub fn pretty(mut self, pretty: bool) -> JsonLayout {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Enables or disables omitting fields with empty values.
+    pub fn suppress_empty(mut self, suppress: bool) -> JsonLayout {
+        self.suppress_empty = suppress;
+        self
+    }
+
+    fn insert(&self, map: &mut BTreeMap<String, Value>, name: &str, value: String) {
+        if !(self.suppress_empty && value.is_empty()) {
+            map.insert(name.into(), Value::String(value));
+        }
+    }
+
+    fn to_value(&self, rec: &Record) -> Result<Value, Error> {
+        let mut map = BTreeMap::new();
+
+        self.insert(&mut map, "message", rec.message().into());
+        map.insert("severity".into(), Value::I64(rec.severity() as i64));
+        map.insert("timestamp".into(),
+            Value::String(format!("{}", rec.datetime().format("%+"))));
+        self.insert(&mut map, "module", rec.module().into());
+        map.insert("line".into(), Value::U64(rec.line() as u64));
+        map.insert("thread".into(), Value::U64(rec.thread() as u64));
+
+        for meta in rec.iter() {
+            let mut buf = Vec::new();
+            meta.value.format(&mut Formatter::new(&mut buf, Default::default()))?;
+            let value = String::from_utf8(buf)
+                .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+            self.insert(&mut map, meta.name, value);
+        }
+
+        Ok(Value::Object(map))
+    }
+}
+
+impl Layout for JsonLayout {
+    fn format(&self, rec: &Record, mut wr: &mut Write) -> Result<(), Error> {
+        let value = self.to_value(rec)?;
+
+        let result = if self.pretty {
+            serde_json::to_writer_pretty(&mut wr, &value)
+        } else {
+            serde_json::to_writer(&mut wr, &value)
+        };
+
+        result.map_err(|err| Error::Io(io::Error::new(ErrorKind::Other, format!("{}", err))))
+    }
+}
+
+impl Factory for JsonLayout {
+    type Item = Layout;
+
+    fn ty() -> &'static str {
+        "json"
+    }
+
+    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Layout>, Box<error::Error>> {
+        let pretty = match cfg.find("pretty") {
+            Some(pretty) => pretty.as_boolean().ok_or("field \"pretty\" must be a boolean")?,
+            None => false,
+        };
+
+        let suppress_empty = match cfg.find("suppress_empty") {
+            Some(suppress) => {
+                suppress.as_boolean().ok_or("field \"suppress_empty\" must be a boolean")?
+            }
+            None => false,
+        };
+
+        Ok(box JsonLayout::new().pretty(pretty).suppress_empty(suppress_empty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use serde_json::{self, Value};
+
+    use {Meta, MetaLink, Record};
+    use layout::Layout;
+
+    use super::JsonLayout;
+
+    fn format(layout: &JsonLayout) -> String {
+        let val = 42;
+        let meta = [Meta::new("num", &val)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(2, 10, "app", &metalink);
+        rec.activate(format_args!("value"));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        from_utf8(&buf[..]).unwrap().into()
+    }
+
+    fn check(value: &Value) {
+        assert_eq!(Some("value"), value.find("message").and_then(|v| v.as_string()));
+        assert_eq!(Some(2), value.find("severity").and_then(|v| v.as_i64()));
+        assert_eq!(Some("app"), value.find("module").and_then(|v| v.as_string()));
+        assert_eq!(Some(10), value.find("line").and_then(|v| v.as_u64()));
+        assert_eq!(Some("42"), value.find("num").and_then(|v| v.as_string()));
+    }
+
+    #[test]
+    fn compact() {
+        let out = format(&JsonLayout::new());
+
+        assert!(!out.contains('\n'));
+        check(&serde_json::from_str(&out).unwrap());
+    }
+
+    #[test]
+    fn pretty() {
+        let out = format(&JsonLayout::new().pretty(true));
+
+        // Pretty output spans over multiple lines, so line-based consumers will break it apart.
+        assert!(out.lines().count() > 1);
+        check(&serde_json::from_str(&out).unwrap());
+    }
+
+    fn format_with_empty(layout: &JsonLayout) -> Value {
+        let empty = "";
+        let path = "/var/www";
+        let meta = [Meta::new("empty", &empty), Meta::new("path", &path)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(2, 10, "", &metalink);
+        rec.activate(format_args!("value"));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        serde_json::from_str(from_utf8(&buf[..]).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn empty_values_are_kept_by_default() {
+        let value = format_with_empty(&JsonLayout::new());
+
+        assert_eq!(Some(""), value.find("empty").and_then(|v| v.as_string()));
+        assert_eq!(Some(""), value.find("module").and_then(|v| v.as_string()));
+    }
+
+    #[test]
+    fn suppress_empty() {
+        let value = format_with_empty(&JsonLayout::new().suppress_empty(true));
+
+        assert!(value.find("empty").is_none());
+        assert!(value.find("module").is_none());
+        assert_eq!(Some("/var/www"), value.find("path").and_then(|v| v.as_string()));
+        assert_eq!(Some("value"), value.find("message").and_then(|v| v.as_string()));
+    }
+}
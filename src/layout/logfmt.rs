@@ -0,0 +1,136 @@
+use std::error;
+use std::io::Write;
+
+use {Config, Format, Formatter, Record, Registry};
+use factory::Factory;
+
+use super::{Error, Layout};
+
+/// A layout, that formats records as `logfmt` lines of space-separated `key=value` pairs.
+///
+/// Each line looks like `ts=2016-10-15T12:00:00+00:00 level=info msg="le message" module=app
+/// line=42`, followed by all meta attributes in the order they are iterated. The level is the
+/// severity name rendered by the severity type the record was created with, lower-cased, so plain
+/// integer severities are shown as digits.
+///
+/// Values are written as is unless they are empty or contain spaces, `=`, quotes or control
+/// characters, in that case they are wrapped in double quotes with quotes, backslashes and line
+/// breaks escaped.
+pub struct LogfmtLayout;
+
+impl LogfmtLayout {
+    pub fn new() -> LogfmtLayout {
+        LogfmtLayout
+    }
+
+    fn write_pair(wr: &mut Write, key: &str, value: &str) -> Result<(), Error> {
+        write!(wr, "{}=", key)?;
+
+        let quote = value.is_empty() || value.chars().any(|ch| {
+            ch == ' ' || ch == '=' || ch == '"' || ch.is_control()
+        });
+
+        if !quote {
+            wr.write_all(value.as_bytes())?;
+            return Ok(());
+        }
+
+        wr.write_all(b"\"")?;
+        for ch in value.chars() {
+            match ch {
+                '"' => wr.write_all(b"\\\"")?,
+                '\\' => wr.write_all(b"\\\\")?,
+                '\n' => wr.write_all(b"\\n")?,
+                '\r' => wr.write_all(b"\\r")?,
+                '\t' => wr.write_all(b"\\t")?,
+                ch => write!(wr, "{}", ch)?,
+            }
+        }
+        wr.write_all(b"\"")?;
+
+        Ok(())
+    }
+}
+
+impl Default for LogfmtLayout {
+    fn default() -> LogfmtLayout {
+        LogfmtLayout::new()
+    }
+}
+
+impl Layout for LogfmtLayout {
+    fn format(&self, rec: &Record, wr: &mut Write) -> Result<(), Error> {
+        write!(wr, "ts={} ", rec.datetime().format("%+"))?;
+        LogfmtLayout::write_pair(wr, "level", &rec.severity_name().to_lowercase())?;
+        wr.write_all(b" ")?;
+        LogfmtLayout::write_pair(wr, "msg", rec.message())?;
+        wr.write_all(b" ")?;
+        LogfmtLayout::write_pair(wr, "module", rec.module())?;
+        write!(wr, " line={}", rec.line())?;
+
+        for meta in rec.iter() {
+            let mut buf = Vec::new();
+            meta.value.format(&mut Formatter::new(&mut buf, Default::default()))?;
+
+            wr.write_all(b" ")?;
+            LogfmtLayout::write_pair(wr, meta.name, &String::from_utf8_lossy(&buf))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Factory for LogfmtLayout {
+    type Item = Layout;
+
+    fn ty() -> &'static str {
+        "logfmt"
+    }
+
+    fn from(_cfg: &Config, _registry: &Registry) -> Result<Box<Layout>, Box<error::Error>> {
+        Ok(box LogfmtLayout::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::LogLevel;
+
+    use {Layout, Meta, MetaLink, Record};
+
+    use super::LogfmtLayout;
+
+    #[test]
+    fn format() {
+        let code = 42;
+        let meta = [Meta::new("code", &code)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(LogLevel::Info, 10, "app", &metalink);
+        rec.activate(format_args!("le message"));
+
+        let expected = format!(r#"ts={} level=info msg="le message" module=app line=10 code=42"#,
+            rec.datetime().format("%+"));
+
+        assert_eq!(expected, LogfmtLayout.format_string(&rec).unwrap());
+    }
+
+    #[test]
+    fn format_quoted_values() {
+        let query = r#"a="b c""#;
+        let empty = "";
+        let path = r#"C:\temp"#;
+        let meta = [
+            Meta::new("query", &query),
+            Meta::new("empty", &empty),
+            Meta::new("path", &path),
+        ];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(3, 0, "", &metalink);
+        rec.activate(format_args!("multi\nline"));
+
+        let out = LogfmtLayout.format_string(&rec).unwrap();
+
+        assert!(out.contains(r#" level=3 msg="multi\nline" module="" line=0 "#));
+        assert!(out.ends_with(r#" query="a=\"b c\"" empty="" path=C:\temp"#));
+    }
+}
@@ -1,15 +1,119 @@
-use std::io::Write;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, ErrorKind, Write};
 
 use record::Record;
 
+mod access;
+mod combined;
+mod conditional;
+mod json;
+mod logfmt;
 pub mod pattern;
+mod relative;
 
+pub use self::access::AccessLogLayout;
+pub use self::combined::CombinedLayout;
+pub use self::conditional::ConditionalLayout;
+pub use self::json::JsonLayout;
+pub use self::logfmt::LogfmtLayout;
 pub use self::pattern::PatternLayout;
+pub use self::relative::RelativeTimeLayout;
+pub use meta::format::Alignment;
 
-pub type Error = ::std::io::Error;
+/// Layout formatting error.
+#[derive(Debug)]
+pub enum Error {
+    /// Meta information with the given name required for formatting is not found in the record.
+    MetaNotFound(String),
+    /// Underlying I/O error, usually while writing into the destination.
+    Io(io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Error::MetaNotFound(ref name) => write!(fmt, "meta \"{}\" not found", name),
+            Error::Io(ref err) => write!(fmt, "{}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::MetaNotFound(..) => "meta not found",
+            Error::Io(ref err) => error::Error::description(err),
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            Error::MetaNotFound(..) => None,
+            Error::Io(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        match err {
+            Error::MetaNotFound(..) => io::Error::new(ErrorKind::NotFound, err),
+            Error::Io(err) => err,
+        }
+    }
+}
 
 /// Layouts are responsible for formatting a log event into a form that meets the needs of whatever
 /// will be consuming the log event.
 pub trait Layout: Send + Sync {
     fn format(&self, rec: &Record, wr: &mut Write) -> Result<(), Error>;
+
+    /// Formats the given record into a string.
+    ///
+    /// All builtin layouts produce valid UTF-8, but custom layouts are free to emit arbitrary
+    /// bytes, in that case an `InvalidData` I/O error is returned.
+    fn format_string(&self, rec: &Record) -> Result<String, Error> {
+        let mut buf = Vec::new();
+        self.format(rec, &mut buf)?;
+
+        String::from_utf8(buf).map_err(|err| {
+            Error::Io(io::Error::new(ErrorKind::InvalidData, err))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Write};
+
+    use {MetaLink, Record};
+
+    use super::{Error, Layout};
+
+    struct BinaryLayout;
+
+    impl Layout for BinaryLayout {
+        fn format(&self, _rec: &Record, wr: &mut Write) -> Result<(), Error> {
+            wr.write_all(&[0xff, 0xfe])?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fail_format_string_invalid_utf8() {
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        match BinaryLayout.format_string(&rec) {
+            Err(Error::Io(ref err)) => assert_eq!(ErrorKind::InvalidData, err.kind()),
+            other => panic!("expected invalid data error, actual: {:?}", other),
+        }
+    }
 }
@@ -1,12 +1,18 @@
 use meta::format::Alignment;
 
 use super::{
+    default_align,
+    severity_align,
     FormatSpec,
     ProcessType,
     SeverityType,
+    SpanType,
     Timezone,
     Token,
     CLOSED_BRACE,
+    FLAG_ALTERNATE,
+    FLAG_SIGN_AWARE_ZERO_PAD,
+    FLAG_SIGN_PLUS,
     OPENED_BRACE
 };
 
@@ -19,41 +25,73 @@ text -> Token<'input>
     / "}}" { Token::Piece(CLOSED_BRACE) }
     / [^{}]+ { Token::Piece(match_str) }
 format -> Token<'input>
-    = "{" "message" "}" { Token::Message(None) }
-    / "{" "message:" fill:fill? align:align? width:width? precision:precision? "}" {
+    = "{" "message" ":"? "}" { Token::Message(None) }
+    / "{" "message:" fill:fill? align:align? flags:flags width:width? precision:precision? "}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: precision,
             width: width.unwrap_or(0),
+            ty: None,
         };
 
         Token::Message(Some(spec))
     }
-    / "{" "severity" "}"   { Token::Severity(None, SeverityType::String) }
+    / "{" "message:" fill:fill? align:align? flags:flags "$" width:name precision:precision? "}" {
+        let spec = FormatSpec {
+            fill: fill.unwrap_or(' '),
+            align: default_align(align, flags),
+            flags: flags,
+            precision: precision,
+            width: 0,
+            ty: None,
+        };
+
+        Token::MessageWidthRef(spec, width)
+    }
+    / "{" "severity" ":"? "}"   { Token::Severity(None, SeverityType::String) }
     / "{" "severity:" "s}" { Token::Severity(None, SeverityType::String) }
     / "{" "severity:" "d}" { Token::Severity(None, SeverityType::Num) }
-    / "{" "severity:" fill:fill? align:align? width:width? precision:precision? ty:sevty? "}" {
+    / "{" "severity:" "syslog}" { Token::Severity(None, SeverityType::Syslog) }
+    / "{" "severity:" "upper}" { Token::Severity(None, SeverityType::Upper) }
+    / "{" "severity:" "lower}" { Token::Severity(None, SeverityType::Lower) }
+    / "{" "severity:" fill:fill? align:align? flags:flags width:width? precision:precision? ty:intty "}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: severity_align(align, flags, SeverityType::Num),
+            flags: flags,
             precision: precision,
             width: width.unwrap_or(0),
+            ty: Some(ty),
         };
 
-        Token::Severity(Some(spec), ty.unwrap_or(SeverityType::String))
+        Token::Severity(Some(spec), SeverityType::Num)
     }
-    / "{" "timestamp" "}"   { Token::Timestamp(None, "%+".into(), Timezone::Utc) }
+    / "{" "severity:" fill:fill? align:align? flags:flags width:width? precision:precision? ty:sevty? "}" {
+        let ty = ty.unwrap_or(SeverityType::String);
+
+        let spec = FormatSpec {
+            fill: fill.unwrap_or(' '),
+            align: severity_align(align, flags, ty),
+            flags: flags,
+            precision: precision,
+            width: width.unwrap_or(0),
+            ty: None,
+        };
+
+        Token::Severity(Some(spec), ty)
+    }
+    / "{" "timestamp" ":"? "}"   { Token::Timestamp(None, "%+".into(), Timezone::Utc) }
     / "{" "timestamp:" "d}" { Token::TimestampNum(None) }
-    / "{" "timestamp:" fill:fill? align:align? width:width? "d}" {
+    / "{" "timestamp:" fill:fill? align:align? flags:flags width:width? "d}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: None,
             width: width.unwrap_or(0),
+            ty: None,
         };
 
         Token::TimestampNum(Some(spec))
@@ -61,75 +99,111 @@ format -> Token<'input>
     / "{" "timestamp:" pattern:strftime? tz:tz "}" {
         Token::Timestamp(None, pattern.unwrap_or("%+".into()), tz)
     }
-    / "{" "timestamp:" pattern:strftime? fill:fill? align:align? width:width? precision:precision? tz:tz "}" {
+    / "{" "timestamp:" pattern:strftime? fill:fill? align:align? flags:flags width:width? precision:precision? tz:tz "}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: precision,
             width: width.unwrap_or(0),
+            ty: None,
         };
 
         Token::Timestamp(Some(spec), pattern.unwrap_or("%+".into()), tz)
     }
-    / "{" "line" "}" { Token::Line(None) }
-    / "{" "line:" fill:fill? align:align? width:width? "}" {
+    / "{" "line" ":"? "}" { Token::Line(None) }
+    / "{" "line:" fill:fill? align:align? flags:flags width:width? ty:intty? "}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: None,
             width: width.unwrap_or(0),
+            ty: ty,
         };
 
         Token::Line(Some(spec))
     }
-    / "{" "module" "}" { Token::Module(None) }
-    / "{" "module:" fill:fill? align:align? width:width? precision:precision? "}" {
+    / "{" "module" ":"? "}" { Token::Module(None) }
+    / "{" "module:" fill:fill? align:align? flags:flags width:width? precision:precision? "}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: precision,
             width: width.unwrap_or(0),
+            ty: None,
         };
 
         Token::Module(Some(spec))
     }
-    / "{" "process" "}" {
+    / "{" "module:" segments:segments "}" { Token::ModuleTail(None, segments) }
+    / "{" "module:" fill:fill? align:align? flags:flags width:width? precision:precision? segments:segments "}" {
+        let spec = FormatSpec {
+            fill: fill.unwrap_or(' '),
+            align: default_align(align, flags),
+            flags: flags,
+            precision: precision,
+            width: width.unwrap_or(0),
+            ty: None,
+        };
+
+        Token::ModuleTail(Some(spec), segments)
+    }
+    / "{" "process" ":"? "}" {
         Token::Process(None, ProcessType::Id)
     }
-    / "{" "process:" fill:fill? align:align? width:width? "d}" {
+    / "{" "process:" fill:fill? align:align? flags:flags width:width? "d}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: None,
             width: width.unwrap_or(0),
+            ty: None,
         };
 
         Token::Process(Some(spec), ProcessType::Id)
     }
-    / "{" "process:" fill:fill? align:align? width:width? precision:precision? "}" {
+    / "{" "process:" fill:fill? align:align? flags:flags width:width? precision:precision? "}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: precision,
             width: width.unwrap_or(0),
+            ty: None,
         };
 
         Token::Process(Some(spec), ProcessType::Name)
     }
-    / "{" "..." "}" { Token::MetaList(None) }
-    / "{" name:name "}" { Token::Meta(name, None) }
-    / "{" name:name ":" fill:fill? align:align? width:width? precision:precision? "}" {
+    / "{" "span" ":"? "}"   { Token::Span(SpanType::Record) }
+    / "{" "span:" "p}" { Token::Span(SpanType::Process) }
+    / "{" "..." ":"? "}" { Token::MetaList(None) }
+    / "{" "...:" fill:fill? align:align? flags:flags width:width? precision:precision? ty:metaty? "}" {
         let spec = FormatSpec {
             fill: fill.unwrap_or(' '),
-            align: align.unwrap_or(Alignment::AlignLeft),
-            flags: 0,
+            align: default_align(align, flags),
+            flags: flags,
             precision: precision,
             width: width.unwrap_or(0),
+            ty: ty,
+        };
+
+        Token::MetaList(Some(spec))
+    }
+    / "{" "..." prefix:prefix? suffix:suffix? "}" {
+        Token::MetaListAffixed(prefix.unwrap_or(""), suffix.unwrap_or(""))
+    }
+    / "{" name:metaname ":"? "}" { Token::Meta(name, None) }
+    / "{" name:metaname ":" fill:fill? align:align? flags:flags width:width? precision:precision? ty:metaty? "}" {
+        let spec = FormatSpec {
+            fill: fill.unwrap_or(' '),
+            align: default_align(align, flags),
+            flags: flags,
+            precision: precision,
+            width: width.unwrap_or(0),
+            ty: ty,
         };
 
         Token::Meta(name, Some(spec))
@@ -140,12 +214,45 @@ align -> Alignment
     = "<" { Alignment::AlignLeft }
     / ">" { Alignment::AlignRight }
     / "^" { Alignment::AlignCenter }
+flags -> u32
+    = sign:"+"? alternate:"#"? zero:"0"? {
+        let mut flags = 0;
+        if sign.is_some() {
+            flags |= FLAG_SIGN_PLUS;
+        }
+        if alternate.is_some() {
+            flags |= FLAG_ALTERNATE;
+        }
+        if zero.is_some() {
+            flags |= FLAG_SIGN_AWARE_ZERO_PAD;
+        }
+        flags
+    }
 width -> usize
     = [0-9]+ { match_str.parse().unwrap() }
 precision -> usize
     = "." [0-9]+ { match_str[1..].parse().unwrap() }
+prefix -> &'input str
+    = "|prefix=" literal:literal { literal }
+suffix -> &'input str
+    = "|suffix=" literal:literal { literal }
+literal -> &'input str
+    = "'" chars:lchars "'" { chars }
+lchars -> &'input str
+    = [^']* { match_str }
+intty -> char
+    = [xXob] { match_str.chars().next().unwrap() }
+metaty -> char
+    = "q" { 'q' }
+    / [eEgG] { match_str.chars().next().unwrap() }
+    / ty:intty { ty }
+segments -> usize
+    = "-" [1-9][0-9]* { match_str[1..].parse().unwrap() }
 sevty -> SeverityType
     = "d" { SeverityType::Num }
+    / "syslog" { SeverityType::Syslog }
+    / "upper" { SeverityType::Upper }
+    / "lower" { SeverityType::Lower }
     / "s" { SeverityType::String }
 process_type -> ProcessType
     = "d" { ProcessType::Id }
@@ -160,4 +267,6 @@ tchar -> char
     / "}}" { CLOSED_BRACE.chars().next().unwrap() }
     / [^{}] { match_str.chars().next().unwrap() }
 name -> &'input str
-    = [a-zA-Z][a-zA-Z0-9]* { match_str }
+    = [a-zA-Z][a-zA-Z0-9_]* { match_str }
+metaname -> &'input str
+    = [a-zA-Z][a-zA-Z0-9_.-]* { match_str }
@@ -6,12 +6,22 @@ use meta::format::Alignment;
 const OPENED_BRACE: &'static str = "{";
 const CLOSED_BRACE: &'static str = "}";
 
+const FLAG_SIGN_PLUS: u32 = 1 << 0;
+const FLAG_ALTERNATE: u32 = 1 << 1;
+const FLAG_SIGN_AWARE_ZERO_PAD: u32 = 1 << 2;
+
 peg_file! grammar("grammar.peg.rs");
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum SeverityType {
     Num,
     String,
+    /// Syslog priority number, i.e. 0 for emergency up to 7 for debug.
+    Syslog,
+    /// String representation converted to upper case, i.e. `ERROR`.
+    Upper,
+    /// String representation converted to lower case, i.e. `error`.
+    Lower,
 }
 
 // TODO: Uncomment.
@@ -27,6 +37,15 @@ pub enum ProcessType {
     Name,
 }
 
+/// Lifetime of a random span id.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpanType {
+    /// A new id is generated for each record.
+    Record,
+    /// The same id is used for all records formatted by any layout during the process lifetime.
+    Process,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Timezone {
     Utc,
@@ -40,6 +59,7 @@ pub struct FormatSpec {
     pub flags: u32,
     pub precision: Option<usize>,
     pub width: usize,
+    pub ty: Option<char>,
 }
 
 impl Default for FormatSpec {
@@ -50,6 +70,7 @@ impl Default for FormatSpec {
             flags: 0,
             precision: None,
             width: 0,
+            ty: None,
         }
     }
 }
@@ -62,17 +83,42 @@ impl Into<meta::format::FormatSpec> for FormatSpec {
             flags: self.flags,
             precision: self.precision,
             width: self.width,
-            ty: None,
+            ty: self.ty,
         }
     }
 }
 
+/// Returns the alignment to be used if it's not explicitly specified in the spec.
+///
+/// Values are left aligned by default, unless the `0` flag is set, which like in `std::fmt`
+/// implies padding with zeros between the sign and the number itself.
+fn default_align(align: Option<Alignment>, flags: u32) -> Alignment {
+    match align {
+        Some(align) => align,
+        None if flags & FLAG_SIGN_AWARE_ZERO_PAD != 0 => Alignment::AlignRight,
+        None => Alignment::AlignLeft,
+    }
+}
+
+/// Returns the alignment to be used for severity if it's not explicitly specified in the spec.
+///
+/// Numeric severity (including syslog priorities) is right aligned like all integers in
+/// `std::fmt`, while its string representation is left aligned like all other strings.
+fn severity_align(align: Option<Alignment>, flags: u32, ty: SeverityType) -> Alignment {
+    match (align, ty) {
+        (None, SeverityType::Num) | (None, SeverityType::Syslog) => Alignment::AlignRight,
+        (align, ..) => default_align(align, flags),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token<'a> {
     /// Portion of the format string which represents the next part to emit.
     Piece(&'a str),
     /// Message with an optional spec.
     Message(Option<FormatSpec>),
+    /// Message with a spec, which width is taken from the meta attribute with the given name.
+    MessageWidthRef(FormatSpec, &'a str),
     /// Severity formatted as either numeric or string with an optional spec.
     Severity(Option<FormatSpec>, SeverityType),
     /// Timestamp representation with a pattern, timezone and optional spec.
@@ -83,27 +129,40 @@ pub enum Token<'a> {
     Line(Option<FormatSpec>),
     /// The module path where the logging event was created.
     Module(Option<FormatSpec>),
+    /// The last given number of `::`-separated segments of the module path.
+    ModuleTail(Option<FormatSpec>, usize),
     /// Thread id or its name depending on type specified.
     // Thread(Option<FormatSpec>, ThreadType),
     /// Process id (aka PID) or its name depending on type specified.
     Process(Option<FormatSpec>, ProcessType),
+    /// Random span id as a 16-digit hex string for correlation.
+    Span(SpanType),
     Meta(&'a str, Option<FormatSpec>),
+    /// All meta attributes as comma separated `name: value` pairs. The optional spec is applied to
+    /// each value separately, i.e. width means the width of every single value.
     MetaList(Option<FormatSpec>),
+    /// Meta list surrounded by the given prefix and suffix, which are written only if there is at
+    /// least one attribute.
+    MetaListAffixed(&'a str, &'a str),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenBuf {
     Piece(String),
     Message(Option<FormatSpec>),
+    MessageWidthRef(FormatSpec, String),
     Severity(Option<FormatSpec>, SeverityType),
     Timestamp(Option<FormatSpec>, String, Timezone),
     TimestampNum(Option<FormatSpec>),
     Line(Option<FormatSpec>),
     Module(Option<FormatSpec>),
+    ModuleTail(Option<FormatSpec>, usize),
     // TODO: Thread(Option<FormatSpec>, ThreadType),
     Process(Option<FormatSpec>, ProcessType),
+    Span(SpanType),
     Meta(String, Option<FormatSpec>),
     MetaList(Option<FormatSpec>),
+    MetaListAffixed(String, String),
 }
 
 impl<'a> From<Token<'a>> for TokenBuf {
@@ -111,14 +170,20 @@ impl<'a> From<Token<'a>> for TokenBuf {
         match val {
             Token::Piece(piece) => TokenBuf::Piece(piece.into()),
             Token::Message(spec) => TokenBuf::Message(spec),
+            Token::MessageWidthRef(spec, name) => TokenBuf::MessageWidthRef(spec, name.into()),
             Token::Severity(spec, ty) => TokenBuf::Severity(spec, ty),
             Token::Timestamp(spec, pattern, tz) => TokenBuf::Timestamp(spec, pattern, tz),
             Token::TimestampNum(spec) => TokenBuf::TimestampNum(spec),
             Token::Line(spec) => TokenBuf::Line(spec),
             Token::Module(spec) => TokenBuf::Module(spec),
+            Token::ModuleTail(spec, segments) => TokenBuf::ModuleTail(spec, segments),
             Token::Process(spec, ty) => TokenBuf::Process(spec, ty),
+            Token::Span(ty) => TokenBuf::Span(ty),
             Token::Meta(name, spec) => TokenBuf::Meta(name.into(), spec),
             Token::MetaList(spec) => TokenBuf::MetaList(spec),
+            Token::MetaListAffixed(prefix, suffix) => {
+                TokenBuf::MetaListAffixed(prefix.into(), suffix.into())
+            }
         }
     }
 }
@@ -163,10 +228,54 @@ mod tests {
             flags: 0,
             precision: Some(8),
             width: 10,
+            ty: None,
         };
         assert_eq!(vec![Token::Message(Some(spec))], tokens);
     }
 
+    #[test]
+    fn message_spec_width_ref() {
+        let tokens = parse("{message:<$width}").unwrap();
+
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignLeft,
+            flags: 0,
+            precision: None,
+            width: 0,
+            ty: None,
+        };
+        assert_eq!(vec![Token::MessageWidthRef(spec, "width")], tokens);
+    }
+
+    #[test]
+    fn message_spec_width_ref_with_fill_and_precision() {
+        let tokens = parse("{message:.>$w.3}").unwrap();
+
+        let spec = FormatSpec {
+            fill: '.',
+            align: Alignment::AlignRight,
+            flags: 0,
+            precision: Some(3),
+            width: 0,
+            ty: None,
+        };
+        assert_eq!(vec![Token::MessageWidthRef(spec, "w")], tokens);
+    }
+
+    #[test]
+    fn message_spec_width_ref_with_precision_and_dotted_meta() {
+        let tokens = parse("{message:$w.3}{http.status}").unwrap();
+
+        let spec = FormatSpec {
+            align: Alignment::AlignLeft,
+            precision: Some(3),
+            .. FormatSpec::default()
+        };
+        assert_eq!(vec![Token::MessageWidthRef(spec, "w"), Token::Meta("http.status", None)],
+            tokens);
+    }
+
     #[test]
     fn severity() {
         let tokens = parse("{severity}").unwrap();
@@ -188,6 +297,55 @@ mod tests {
         assert_eq!(vec![Token::Severity(None, SeverityType::Num)], tokens);
     }
 
+    #[test]
+    fn severity_syslog() {
+        let tokens = parse("{severity:syslog}").unwrap();
+
+        assert_eq!(vec![Token::Severity(None, SeverityType::Syslog)], tokens);
+    }
+
+    #[test]
+    fn severity_ext_syslog() {
+        let tokens = parse("{severity:>3syslog}").unwrap();
+
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignRight,
+            flags: 0,
+            precision: None,
+            width: 3,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Severity(Some(spec), SeverityType::Syslog)], tokens);
+    }
+
+    #[test]
+    fn severity_upper() {
+        assert_eq!(vec![Token::Severity(None, SeverityType::Upper)],
+            parse("{severity:upper}").unwrap());
+    }
+
+    #[test]
+    fn severity_lower() {
+        assert_eq!(vec![Token::Severity(None, SeverityType::Lower)],
+            parse("{severity:lower}").unwrap());
+    }
+
+    #[test]
+    fn severity_ext_upper() {
+        let tokens = parse("{severity:5upper}").unwrap();
+
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignLeft,
+            flags: 0,
+            precision: None,
+            width: 5,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Severity(Some(spec), SeverityType::Upper)], tokens);
+    }
+
     #[test]
     fn severity_ext() {
         let tokens = parse("{severity:<10}").unwrap();
@@ -198,8 +356,40 @@ mod tests {
             flags: 0,
             precision: None,
             width: 10,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Severity(Some(spec), SeverityType::String)], tokens);
+    }
+
+    #[test]
+    fn severity_ext_num_aligned_right_by_default() {
+        let tokens = parse("{severity:5d}").unwrap();
+
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignRight,
+            flags: 0,
+            precision: None,
+            width: 5,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Severity(Some(spec), SeverityType::Num)], tokens);
+    }
+
+    #[test]
+    fn severity_ext_string_aligned_left_by_default() {
+        let tokens = parse("{severity:5s}").unwrap();
+
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignLeft,
+            flags: 0,
+            precision: None,
+            width: 5,
+            ty: None,
         };
         assert_eq!(vec![Token::Severity(Some(spec), SeverityType::String)], tokens);
+        assert_eq!(tokens, parse("{severity:5}").unwrap());
     }
 
     #[test]
@@ -212,6 +402,7 @@ mod tests {
             flags: 0,
             precision: None,
             width: 16,
+            ty: None,
         };
         assert_eq!(vec![Token::Severity(Some(spec), SeverityType::String)], tokens);
     }
@@ -226,6 +417,7 @@ mod tests {
             flags: 0,
             precision: None,
             width: 16,
+            ty: None,
         };
         assert_eq!(vec![Token::Severity(Some(spec), SeverityType::Num)], tokens);
     }
@@ -240,6 +432,7 @@ mod tests {
             flags: 0,
             precision: None,
             width: 16,
+            ty: None,
         };
         assert_eq!(vec![Token::Severity(Some(spec), SeverityType::String)], tokens);
     }
@@ -254,6 +447,7 @@ mod tests {
             flags: 0,
             precision: Some(1),
             width: 0,
+            ty: None,
         };
         assert_eq!(vec![Token::Severity(Some(spec), SeverityType::String)], tokens);
     }
@@ -296,6 +490,7 @@ mod tests {
             flags: 0,
             precision: None,
             width: 20,
+            ty: None,
         };
         assert_eq!(vec![Token::TimestampNum(Some(spec))], tokens);
     }
@@ -310,6 +505,7 @@ mod tests {
             flags: 0,
             precision: None,
             width: 0,
+            ty: None,
         };
         assert_eq!(vec![Token::TimestampNum(Some(spec))], tokens);
     }
@@ -348,6 +544,22 @@ mod tests {
         assert_eq!(expected, tokens);
     }
 
+    #[test]
+    fn timestamp_with_iso_week_pattern() {
+        let tokens = parse("{timestamp:{%G-W%V}s}").unwrap();
+
+        assert_eq!(vec![Token::Timestamp(None, "%G-W%V".into(), Timezone::Utc)], tokens);
+    }
+
+    #[test]
+    fn timestamp_with_week_and_ordinal_directives() {
+        for directive in &["%G", "%g", "%V", "%j", "%U", "%W", "%u", "%w"] {
+            let tokens = parse(&format!("{{timestamp:{{{}}}s}}", directive)).unwrap();
+
+            assert_eq!(vec![Token::Timestamp(None, directive.to_string(), Timezone::Utc)], tokens);
+        }
+    }
+
     #[test]
     fn timestamp_ext_with_pattern_and_fill_utc() {
         let tokens = parse("{timestamp:{%Y-%m-%d}.<s}").unwrap();
@@ -358,6 +570,7 @@ mod tests {
             flags: 0,
             precision: None,
             width: 0,
+            ty: None,
         };
         assert_eq!(vec![Token::Timestamp(Some(spec), "%Y-%m-%d".into(), Timezone::Utc)], tokens);
     }
@@ -372,6 +585,7 @@ mod tests {
             flags: 0,
             precision: None,
             width: 50,
+            ty: None,
         };
         let exp = vec![
             Token::Timestamp(Some(spec), "%Y-%m-%dT%H:%M:%S.%.6f".into(), Timezone::Local),
@@ -396,6 +610,47 @@ mod tests {
             flags: 0,
             precision: None,
             width: 20,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Line(Some(spec))], tokens);
+    }
+
+    #[test]
+    fn line_spec_hex() {
+        let spec = FormatSpec {
+            align: Alignment::AlignLeft,
+            ty: Some('x'),
+            .. FormatSpec::default()
+        };
+        assert_eq!(vec![Token::Line(Some(spec))], parse("{line:x}").unwrap());
+    }
+
+    #[test]
+    fn line_spec_zero_pad() {
+        let tokens = parse("{line:04}").unwrap();
+
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignRight,
+            flags: 0b100,
+            precision: None,
+            width: 4,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Line(Some(spec))], tokens);
+    }
+
+    #[test]
+    fn line_spec_width_with_zero() {
+        let tokens = parse("{line:10}").unwrap();
+
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignLeft,
+            flags: 0,
+            precision: None,
+            width: 10,
+            ty: None,
         };
         assert_eq!(vec![Token::Line(Some(spec))], tokens);
     }
@@ -417,10 +672,38 @@ mod tests {
             flags: 0,
             precision: Some(16),
             width: 20,
+            ty: None,
         };
         assert_eq!(vec![Token::Module(Some(spec))], tokens);
     }
 
+    #[test]
+    fn module_tail() {
+        let tokens = parse("{module:-2}").unwrap();
+
+        assert_eq!(vec![Token::ModuleTail(None, 2)], tokens);
+    }
+
+    #[test]
+    fn module_tail_spec() {
+        let tokens = parse("{module:/^20.16-1}").unwrap();
+
+        let spec = FormatSpec {
+            fill: '/',
+            align: Alignment::AlignCenter,
+            flags: 0,
+            precision: Some(16),
+            width: 20,
+            ty: None,
+        };
+        assert_eq!(vec![Token::ModuleTail(Some(spec), 1)], tokens);
+    }
+
+    #[test]
+    fn fail_module_tail_zero() {
+        assert!(parse("{module:-0}").is_err());
+    }
+
     #[test]
     fn process() {
         let tokens = parse("{process}").unwrap();
@@ -438,10 +721,21 @@ mod tests {
             flags: 0,
             precision: None,
             width: 8,
+            ty: None,
         };
         assert_eq!(vec![Token::Process(Some(spec), ProcessType::Id)], tokens);
     }
 
+    #[test]
+    fn span() {
+        assert_eq!(vec![Token::Span(SpanType::Record)], parse("{span}").unwrap());
+    }
+
+    #[test]
+    fn span_process() {
+        assert_eq!(vec![Token::Span(SpanType::Process)], parse("{span:p}").unwrap());
+    }
+
     #[test]
     fn meta() {
         let tokens = parse("{hello}").unwrap();
@@ -450,6 +744,42 @@ mod tests {
         assert_eq!(expected, tokens);
     }
 
+    #[test]
+    fn meta_with_underscore() {
+        assert_eq!(vec![Token::Meta("request_id", None)], parse("{request_id}").unwrap());
+    }
+
+    #[test]
+    fn meta_with_dot() {
+        assert_eq!(vec![Token::Meta("http.status", None)], parse("{http.status}").unwrap());
+    }
+
+    #[test]
+    fn meta_with_dash() {
+        let expected = vec![Token::Meta("x-forwarded-for", None)];
+        assert_eq!(expected, parse("{x-forwarded-for}").unwrap());
+    }
+
+    #[test]
+    fn meta_with_dot_and_spec() {
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignRight,
+            flags: 0,
+            precision: None,
+            width: 3,
+            ty: None,
+        };
+
+        assert_eq!(vec![Token::Meta("http.status", Some(spec))],
+            parse("{http.status:>3}").unwrap());
+    }
+
+    #[test]
+    fn meta_name_must_start_with_letter() {
+        assert!(parse("{_id}").is_err());
+    }
+
     #[test]
     fn meta_spec() {
         let spec = FormatSpec {
@@ -458,6 +788,7 @@ mod tests {
             flags: 0,
             precision: Some(2),
             width: 6,
+            ty: None,
         };
         println!("{pi:/^6.2}", pi=3.1415);
         assert_eq!(vec![Token::Meta("pi", Some(spec))], parse("{pi:/^6.2}").unwrap());
@@ -467,4 +798,251 @@ mod tests {
     fn metalist() {
         assert_eq!(vec![Token::MetaList(None)], parse("{...}").unwrap());
     }
+
+    #[test]
+    fn metalist_spec() {
+        let spec = FormatSpec {
+            align: Alignment::AlignRight,
+            width: 40,
+            .. FormatSpec::default()
+        };
+        assert_eq!(vec![Token::MetaList(Some(spec))], parse("{...:>40}").unwrap());
+    }
+
+    #[test]
+    fn metalist_affixed() {
+        assert_eq!(vec![Token::MetaListAffixed(" {", "}")],
+            parse("{...|prefix=' {'|suffix='}'}").unwrap());
+    }
+
+    #[test]
+    fn metalist_prefix_only() {
+        assert_eq!(vec![Token::Piece("message"), Token::MetaListAffixed(" - ", "")],
+            parse("message{...|prefix=' - '}").unwrap());
+    }
+
+    #[test]
+    fn metalist_suffix_only() {
+        assert_eq!(vec![Token::MetaListAffixed("", ";")], parse("{...|suffix=';'}").unwrap());
+    }
+
+    #[test]
+    fn fail_metalist_unquoted_prefix() {
+        assert!(parse("{...|prefix= - }").is_err());
+    }
+
+    #[test]
+    fn meta_alternate_zero_padded_hex() {
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignRight,
+            flags: 0b110,
+            precision: None,
+            width: 6,
+            ty: Some('x'),
+        };
+        assert_eq!(vec![Token::Meta("code", Some(spec))], parse("{code:#06x}").unwrap());
+    }
+
+    #[test]
+    fn meta_int_types() {
+        for ty in &['x', 'X', 'o', 'b'] {
+            let spec = FormatSpec {
+                align: Alignment::AlignLeft,
+                ty: Some(*ty),
+                .. FormatSpec::default()
+            };
+            assert_eq!(vec![Token::Meta("code", Some(spec))],
+                parse(&format!("{{code:{}}}", ty)).unwrap());
+        }
+    }
+
+    #[test]
+    fn meta_float_types() {
+        for ty in &['e', 'E', 'g', 'G'] {
+            let spec = FormatSpec {
+                align: Alignment::AlignLeft,
+                ty: Some(*ty),
+                .. FormatSpec::default()
+            };
+            assert_eq!(vec![Token::Meta("x", Some(spec))],
+                parse(&format!("{{x:{}}}", ty)).unwrap());
+        }
+    }
+
+    #[test]
+    fn severity_hex() {
+        let spec = FormatSpec {
+            align: Alignment::AlignRight,
+            flags: 0b010,
+            ty: Some('x'),
+            .. FormatSpec::default()
+        };
+        assert_eq!(vec![Token::Severity(Some(spec), SeverityType::Num)],
+            parse("{severity:#x}").unwrap());
+    }
+
+    #[test]
+    fn severity_type_selectors_are_kept() {
+        assert_eq!(vec![Token::Severity(None, SeverityType::Num)], parse("{severity:d}").unwrap());
+        assert_eq!(vec![Token::Severity(None, SeverityType::String)],
+            parse("{severity:s}").unwrap());
+    }
+
+    #[test]
+    fn meta_quoted() {
+        let spec = FormatSpec {
+            align: Alignment::AlignLeft,
+            ty: Some('q'),
+            .. FormatSpec::default()
+        };
+        assert_eq!(vec![Token::Meta("name", Some(spec))], parse("{name:q}").unwrap());
+    }
+
+    #[test]
+    fn meta_quoted_with_spec() {
+        let spec = FormatSpec {
+            align: Alignment::AlignRight,
+            width: 10,
+            ty: Some('q'),
+            .. FormatSpec::default()
+        };
+        assert_eq!(vec![Token::Meta("name", Some(spec))], parse("{name:>10q}").unwrap());
+    }
+
+    #[test]
+    fn meta_spec_sign() {
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignLeft,
+            flags: 0b001,
+            precision: None,
+            width: 0,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Meta("n", Some(spec))], parse("{n:+}").unwrap());
+    }
+
+    #[test]
+    fn meta_spec_alternate() {
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignLeft,
+            flags: 0b010,
+            precision: None,
+            width: 0,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Meta("n", Some(spec))], parse("{n:#}").unwrap());
+    }
+
+    #[test]
+    fn meta_spec_alternate_hex() {
+        let spec = FormatSpec {
+            align: Alignment::AlignLeft,
+            flags: 0b010,
+            ty: Some('x'),
+            .. FormatSpec::default()
+        };
+        assert_eq!(vec![Token::Meta("n", Some(spec))], parse("{n:#x}").unwrap());
+    }
+
+    #[test]
+    fn meta_spec_zero_pad() {
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignRight,
+            flags: 0b100,
+            precision: None,
+            width: 8,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Meta("n", Some(spec))], parse("{n:08}").unwrap());
+    }
+
+    #[test]
+    fn meta_spec_all_flags() {
+        let spec = FormatSpec {
+            fill: '/',
+            align: Alignment::AlignCenter,
+            flags: 0b111,
+            precision: None,
+            width: 10,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Meta("n", Some(spec))], parse("{n:/^+#010}").unwrap());
+    }
+
+    #[test]
+    fn meta_spec_zero_pad_with_explicit_fill_and_align() {
+        // Both the fill and the alignment are kept here, but ignored while formatting in favor of
+        // right aligned zeros.
+        let spec = FormatSpec {
+            fill: '*',
+            align: Alignment::AlignLeft,
+            flags: 0b100,
+            precision: None,
+            width: 8,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Meta("n", Some(spec))], parse("{n:*<08}").unwrap());
+    }
+
+    #[test]
+    fn severity_spec_all_flags() {
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignRight,
+            flags: 0b111,
+            precision: None,
+            width: 6,
+            ty: Some('x'),
+        };
+        assert_eq!(vec![Token::Severity(Some(spec), SeverityType::Num)],
+            parse("{severity:+#06x}").unwrap());
+    }
+
+    #[test]
+    fn meta_spec_width_without_zero_pad() {
+        let spec = FormatSpec {
+            fill: ' ',
+            align: Alignment::AlignLeft,
+            flags: 0,
+            precision: None,
+            width: 10,
+            ty: None,
+        };
+        assert_eq!(vec![Token::Meta("n", Some(spec))], parse("{n:10}").unwrap());
+    }
+
+    #[test]
+    fn empty_spec_is_default() {
+        assert_eq!(vec![Token::Message(None)], parse("{message:}").unwrap());
+        assert_eq!(vec![Token::Severity(None, SeverityType::String)],
+            parse("{severity:}").unwrap());
+        assert_eq!(vec![Token::Line(None)], parse("{line:}").unwrap());
+    }
+
+    #[test]
+    fn empty_spec_is_default_for_all_placeholders() {
+        assert_eq!(parse("{timestamp}").unwrap(), parse("{timestamp:}").unwrap());
+        assert_eq!(parse("{module}").unwrap(), parse("{module:}").unwrap());
+        assert_eq!(parse("{process}").unwrap(), parse("{process:}").unwrap());
+        assert_eq!(parse("{span}").unwrap(), parse("{span:}").unwrap());
+        assert_eq!(parse("{...}").unwrap(), parse("{...:}").unwrap());
+        assert_eq!(vec![Token::Meta("id", None)], parse("{id:}").unwrap());
+    }
+
+    #[test]
+    fn empty_spec_among_pieces() {
+        let tokens = parse("[{severity:}] {message:}").unwrap();
+
+        let expected = vec![
+            Token::Piece("["),
+            Token::Severity(None, SeverityType::String),
+            Token::Piece("] "),
+            Token::Message(None),
+        ];
+        assert_eq!(expected, tokens);
+    }
 }
@@ -1,25 +1,51 @@
+use std::collections::HashSet;
 use std::error;
-use std::io::{ErrorKind, Write};
+use std::io::{self, ErrorKind, Write};
+use std::str;
+use std::sync::atomic::{AtomicU64, ATOMIC_U64_INIT, Ordering};
 
 use chrono::Timelike;
 use chrono::offset::local::Local;
+use serde_json::Value;
 
-use {Format, Formatter, Record, Registry};
+use {Format, Formatter, Meta, Record, Registry};
+use severity::{severity_color, syslog_priority};
 use factory::Factory;
+use random;
 use registry::Config;
 
 use super::{Error, Layout};
 
 mod grammar;
 
-use self::grammar::{parse, FormatSpec, SeverityType, Timezone, TokenBuf};
-pub use self::grammar::ParseError;
+use self::grammar::{parse, Token};
+pub use self::grammar::{FormatSpec, ParseError, ProcessType, SeverityType, SpanType, Timezone,
+                        TokenBuf};
 
+/// Severity mapping, that renders severities in all of their representations.
+///
+/// All severity tokens, both numeric and string ones, are formatted through the mapping, which
+/// sees the entire record. This allows context-aware implementations, for example naming levels
+/// differently depending on some meta attribute.
 pub trait SevMap: Send + Sync {
     fn map(&self, rec: &Record, spec: FormatSpec, ty: SeverityType, wr: &mut Write) ->
         Result<(), ::std::io::Error>;
 }
 
+/// Default severity mapping, which delegates to the record's `Severity` implementation.
+///
+/// Upper and lower case representations (`{severity:upper}` and `{severity:lower}`) are obtained
+/// by converting the string representation, so a single `Severity` implementation fits consumers
+/// with different case expectations.
+///
+/// Syslog priorities are obtained using `syslog_priority` function. To use another mapping,
+/// provide a custom `SevMap`, that handles `SeverityType::Syslog` differently.
+///
+/// Note, that the string representation (`{severity}` or `{severity:s}`) is provided by the
+/// severity type the record was created with, not by the layout. For example records created with
+/// `log::LogLevel` are formatted as `Error`, `Warn` and so on, while a plain `i32` has no names at
+/// all, so its string representation falls back to digits. Either use a naming `Severity` type or
+/// provide a custom `SevMap` to get named levels for numeric severities.
 pub struct DefaultSevMap;
 
 impl SevMap for DefaultSevMap {
@@ -33,136 +59,451 @@ impl SevMap for DefaultSevMap {
                 sev.format(&mut Formatter::new(wr, spec.into()))
             }
             SeverityType::String => {
-                rec.severity_format()(sev, &mut Formatter::new(wr, spec.into()))
+                rec.severity_name().format(&mut Formatter::new(wr, spec.into()))
+            }
+            SeverityType::Syslog => {
+                syslog_priority(sev).format(&mut Formatter::new(wr, spec.into()))
+            }
+            SeverityType::Upper => {
+                rec.severity_name().to_uppercase().format(&mut Formatter::new(wr, spec.into()))
+            }
+            SeverityType::Lower => {
+                rec.severity_name().to_lowercase().format(&mut Formatter::new(wr, spec.into()))
+            }
+        }
+    }
+}
+
+/// Severity mapping, that wraps string severities into ANSI terminal color codes.
+///
+/// Colors are obtained using `severity_color` function by default. Numeric and syslog
+/// representations are left as is, because they are usually consumed by machines rather than by
+/// humans.
+///
+/// Select it in config using the `"color": true` pattern layout option.
+pub struct ColorSevMap {
+    color: fn(i32) -> u8,
+}
+
+impl ColorSevMap {
+    pub fn new() -> ColorSevMap {
+        ColorSevMap {
+            color: severity_color,
+        }
+    }
+
+    /// Replaces the severity color mapping with the given one, which must return a 256-color ANSI
+    /// terminal palette index.
+    pub fn color(mut self, color: fn(i32) -> u8) -> ColorSevMap {
+        self.color = color;
+        self
+    }
+}
+
+impl Default for ColorSevMap {
+    fn default() -> ColorSevMap {
+        ColorSevMap::new()
+    }
+}
+
+impl SevMap for ColorSevMap {
+    fn map(&self, rec: &Record, spec: FormatSpec, ty: SeverityType, wr: &mut Write) ->
+        Result<(), ::std::io::Error>
+    {
+        match ty {
+            SeverityType::String | SeverityType::Upper | SeverityType::Lower => {
+                write!(wr, "\x1B[38;5;{}m", (self.color)(rec.severity()))?;
+                DefaultSevMap.map(rec, spec, ty, wr)?;
+                write!(wr, "\x1B[0m")
+            }
+            SeverityType::Num | SeverityType::Syslog => DefaultSevMap.map(rec, spec, ty, wr),
+        }
+    }
+}
+
+/// Returns the span id for `{span:p}` tokens, generated once per process on first use.
+///
+/// The id is shared between all layouts, so records from different handles can be correlated.
+fn process_span() -> u64 {
+    // Zero means not yet generated, because the generator never returns it.
+    static SPAN: AtomicU64 = ATOMIC_U64_INIT;
+
+    match SPAN.load(Ordering::Relaxed) {
+        0 => {
+            let span = random::next();
+            match SPAN.compare_and_swap(0, span, Ordering::Relaxed) {
+                0 => span,
+                prev => prev,
             }
         }
+        span => span,
     }
 }
 
+/// Meta attribute names for trace and span ids following the OpenTelemetry convention.
+pub const TRACE_FIELDS: [&'static str; 2] = ["trace_id", "span_id"];
+
+/// A layout, that formats records using the given pattern.
+///
+/// # Alignment
+///
+/// If the alignment is not explicitly specified in a token spec, the following defaults apply:
+///
+/// - Numeric severity (`{severity:5d}`) is right aligned, like all integers in `std::fmt`.
+/// - String severity (`{severity:5}`, `{severity:5s}`, `{severity:5upper}`) is left aligned.
+/// - All other tokens, including the line number and meta attributes, are left aligned, unless
+///   the `0` flag is specified, which implies right alignment with zero padding.
 pub struct PatternLayout<F: SevMap=DefaultSevMap> {
     tokens: Vec<TokenBuf>,
     sevmap: F,
+    /// Meta attribute names pinned right after each timestamp.
+    trace: Vec<String>,
+    /// Optional pattern applied to each attribute in `{...}` expansion.
+    item: Option<Vec<TokenBuf>>,
+    /// Optional message substituted for inactive records.
+    inactive: Option<String>,
 }
 
 impl PatternLayout<DefaultSevMap> {
     pub fn new(pattern: &str) -> Result<PatternLayout<DefaultSevMap>, ParseError> {
         PatternLayout::with(pattern, DefaultSevMap)
     }
+
+    /// Constructs a layout from the given field specs separated by the given separator.
+    ///
+    /// Each field is a token without braces, for example `severity:d` or `message`. Braces in the
+    /// separator are escaped, so it's always treated as a literal text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::layout::PatternLayout;
+    ///
+    /// // The same as `PatternLayout::new("{timestamp} {severity} {message}")`.
+    /// let layout = PatternLayout::join(&["timestamp", "severity", "message"], " ").unwrap();
+    /// ```
+    pub fn join(fields: &[&str], sep: &str) -> Result<PatternLayout<DefaultSevMap>, ParseError> {
+        let sep = sep.replace("{", "{{").replace("}", "}}");
+
+        let pattern = fields.iter()
+            .map(|field| format!("{{{}}}", field))
+            .collect::<Vec<_>>()
+            .join(&sep);
+
+        PatternLayout::new(&pattern)
+    }
 }
 
 impl<F: SevMap> PatternLayout<F> {
     fn with(pattern: &str, sevmap: F) -> Result<PatternLayout<F>, ParseError> {
-        let layout = PatternLayout {
-            tokens: parse(pattern)?.into_iter().map(From::from).collect(),
+        let tokens = parse(pattern)?.into_iter().map(From::from).collect();
+
+        Ok(PatternLayout::from_tokens(tokens, sevmap))
+    }
+
+    /// Constructs a layout from already parsed tokens, bypassing the pattern parser.
+    ///
+    /// This is useful for tools, that generate or cache patterns programmatically.
+    pub fn from_tokens(tokens: Vec<TokenBuf>, sevmap: F) -> PatternLayout<F> {
+        PatternLayout {
+            tokens: tokens,
             sevmap: sevmap,
-        };
+            trace: Vec::new(),
+            item: None,
+            inactive: None,
+        }
+    }
+
+    /// Sets the pattern applied to each meta attribute when expanding `{...}`.
+    ///
+    /// Besides of all usual tokens, the pattern may contain `{name}` and `{value}` placeholders
+    /// referring to the attribute being formatted, for example `\n\t{name} = {value}` renders
+    /// each attribute on its own indented line. No separator is written between attributes, so it
+    /// should be the part of the pattern itself. The pattern takes precedence over the spec given
+    /// in `{...:spec}`, which is ignored then.
+    ///
+    /// The pattern itself must not contain `{...}` tokens in any form, because expanding them
+    /// would never end.
+    pub fn meta_pattern(mut self, pattern: &str) -> Result<PatternLayout<F>, ParseError> {
+        let tokens = parse(pattern)?;
+
+        let nested = tokens.iter().any(|token| match *token {
+            Token::MetaList(..) | Token::MetaListAffixed(..) => true,
+            _ => false,
+        });
+
+        if nested {
+            return Err(nested_metalist_error(pattern));
+        }
+
+        self.item = Some(tokens.into_iter().map(From::from).collect());
+        Ok(self)
+    }
 
-        Ok(layout)
+    /// Pins the given meta attributes right after each timestamp in the pattern.
+    ///
+    /// Services usually carry trace and span ids as meta attributes, and it's convenient to find
+    /// them at the same position in every line. Each attribute present in a record is written as
+    /// ` name=value`, absent ones are omitted. See `TRACE_FIELDS` for the OpenTelemetry naming
+    /// convention.
+    pub fn trace_fields<I>(mut self, fields: I) -> PatternLayout<F>
+        where I: IntoIterator,
+              I::Item: Into<String>
+    {
+        self.trace = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the placeholder written instead of the message of inactive records.
+    ///
+    /// Records formatted without passing through a logger are never activated, so their messages
+    /// are empty, which is confusing while debugging custom pipelines. With a placeholder, like
+    /// `<no message>`, such records are easy to spot. See `Record::is_active`.
+    pub fn inactive_message(mut self, placeholder: &str) -> PatternLayout<F> {
+        self.inactive = Some(placeholder.into());
+        self
+    }
+
+    fn message<'a>(&'a self, rec: &'a Record) -> &'a str {
+        match self.inactive {
+            Some(ref placeholder) if !rec.is_active() => placeholder,
+            Some(..) | None => rec.message(),
+        }
+    }
+
+    fn format_trace(&self, rec: &Record, wr: &mut Write) -> Result<(), Error> {
+        for name in &self.trace {
+            if let Some(meta) = rec.iter().find(|meta| meta.name == name) {
+                write!(wr, " {}=", name)?;
+                meta.value.format(&mut Formatter::new(wr, Default::default()))?;
+            }
+        }
+
+        Ok(())
     }
 }
 
-impl<F: SevMap> Layout for PatternLayout<F> {
-    fn format(&self, rec: &Record, mut wr: &mut Write) -> Result<(), Error> {
-        for token in &self.tokens {
-            match *token {
-                TokenBuf::Piece(ref piece) => {
-                    wr.write_all(piece.as_bytes())?
-                }
-                TokenBuf::Message(None) => {
-                    wr.write_all(rec.message().as_bytes())?
-                }
-                TokenBuf::Message(Some(spec)) => {
-                    rec.message().format(&mut Formatter::new(wr, spec.into()))?
-                }
-                TokenBuf::Severity(None, SeverityType::Num) => {
-                    rec.severity().format(&mut Formatter::new(wr, Default::default()))?
-                }
-                TokenBuf::Severity(None, SeverityType::String) => {
-                    self.sevmap.map(rec, Default::default(), SeverityType::String, wr)?
-                }
-                TokenBuf::Severity(Some(spec), SeverityType::Num) => {
-                    rec.severity().format(&mut Formatter::new(wr, spec.into()))?
-                }
-                TokenBuf::Severity(Some(spec), SeverityType::String) => {
-                    self.sevmap.map(rec, spec, SeverityType::String, wr)?
-                }
-                TokenBuf::Timestamp(None, ref pattern, Timezone::Utc) => {
-                    write!(wr, "{}", rec.datetime().format(&pattern))?
-                }
-                TokenBuf::Timestamp(None, ref pattern, Timezone::Local) => {
-                    write!(wr, "{}", rec.datetime().with_timezone(&Local).format(&pattern))?
-                }
-                TokenBuf::Timestamp(Some(spec), ref pattern, timezone) => {
-                    let tokens = match timezone {
-                        Timezone::Utc => rec.datetime().format(&pattern),
-                        Timezone::Local => rec.datetime().with_timezone(&Local).format(&pattern),
-                    };
-
-                    format!("{}", tokens)
-                        .format(&mut Formatter::new(wr, spec.into()))?
-                }
-                TokenBuf::TimestampNum(None) => {
-                    let datetime = rec.datetime();
-                    let timestamp = datetime.timestamp();
-                    let total = timestamp * 1000000 + datetime.nanosecond() as i64 / 1000;
+/// Returns the last given number of `::`-separated segments of the module path, or the whole
+/// path if it has no more segments than that.
+///
+/// Zero segments, which can only be given via tokens constructed manually, also result in the
+/// whole path.
+fn module_tail(module: &str, segments: usize) -> &str {
+    let pos = segments.checked_sub(1)
+        .and_then(|nth| module.rmatch_indices("::").nth(nth));
+
+    match pos {
+        Some((pos, ..)) => &module[pos + 2..],
+        None => module,
+    }
+}
 
-                    total.format(&mut Formatter::new(wr, Default::default()))?
-                }
-                TokenBuf::TimestampNum(Some(spec)) => {
-                    let datetime = rec.datetime();
-                    let timestamp = datetime.timestamp();
-                    let total = timestamp * 1000000 + datetime.nanosecond() as i64 / 1000;
+/// Resolves a width from the meta attribute with the given name.
+fn width(rec: &Record, name: &str) -> Result<usize, Error> {
+    let meta = rec.iter().find(|meta| meta.name == name)
+        .ok_or_else(|| Error::MetaNotFound(name.into()))?;
+
+    let mut buf = Vec::new();
+    meta.value.format(&mut Formatter::new(&mut buf, Default::default()))?;
+
+    str::from_utf8(&buf).ok()
+        .and_then(|width| width.parse().ok())
+        .ok_or_else(|| {
+            let err = io::Error::new(ErrorKind::InvalidData,
+                format!("meta \"{}\" is not a valid width", name));
+            Error::Io(err)
+        })
+}
 
-                    total.format(&mut Formatter::new(wr, spec.into()))?
-                }
-                TokenBuf::Line(None) => {
-                    rec.line().format(&mut Formatter::new(wr, Default::default()))?
+impl<F: SevMap> PatternLayout<F> {
+    fn format_token(&self, rec: &Record, token: &TokenBuf, mut wr: &mut Write) ->
+        Result<(), Error>
+    {
+        match *token {
+            TokenBuf::Piece(ref piece) => {
+                wr.write_all(piece.as_bytes())?
+            }
+            TokenBuf::Message(None) => {
+                wr.write_all(self.message(rec).as_bytes())?
+            }
+            TokenBuf::Message(Some(spec)) => {
+                self.message(rec).format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::MessageWidthRef(mut spec, ref name) => {
+                spec.width = width(rec, name)?;
+                self.message(rec).format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::Severity(spec, ty) => {
+                let spec = spec.unwrap_or(Default::default());
+                self.sevmap.map(rec, spec, ty, wr)?
+            }
+            TokenBuf::Timestamp(None, ref pattern, Timezone::Utc) => {
+                write!(wr, "{}", rec.datetime().format(&pattern))?
+            }
+            TokenBuf::Timestamp(None, ref pattern, Timezone::Local) => {
+                write!(wr, "{}", rec.datetime().with_timezone(&Local).format(&pattern))?
+            }
+            TokenBuf::Timestamp(Some(spec), ref pattern, timezone) => {
+                let tokens = match timezone {
+                    Timezone::Utc => rec.datetime().format(&pattern),
+                    Timezone::Local => rec.datetime().with_timezone(&Local).format(&pattern),
+                };
+
+                format!("{}", tokens)
+                    .format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::TimestampNum(None) => {
+                let datetime = rec.datetime();
+                let timestamp = datetime.timestamp();
+                let total = timestamp * 1000000 + datetime.nanosecond() as i64 / 1000;
+
+                total.format(&mut Formatter::new(wr, Default::default()))?
+            }
+            TokenBuf::TimestampNum(Some(spec)) => {
+                let datetime = rec.datetime();
+                let timestamp = datetime.timestamp();
+                let total = timestamp * 1000000 + datetime.nanosecond() as i64 / 1000;
+
+                total.format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::Line(None) => {
+                rec.line().format(&mut Formatter::new(wr, Default::default()))?
+            }
+            TokenBuf::Line(Some(spec)) => {
+                rec.line().format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::Module(None) => {
+                wr.write_all(rec.module().as_bytes())?
+            }
+            TokenBuf::Module(Some(spec)) => {
+                rec.module().format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::ModuleTail(None, segments) => {
+                wr.write_all(module_tail(rec.module(), segments).as_bytes())?
+            }
+            TokenBuf::ModuleTail(Some(spec), segments) => {
+                module_tail(rec.module(), segments).format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::Process(None, _ty) => {
+                unimplemented!();
+            }
+            TokenBuf::Process(Some(_spec), _ty) => {
+                unimplemented!();
+            }
+            TokenBuf::Span(SpanType::Record) => {
+                write!(wr, "{:016x}", random::next())?
+            }
+            TokenBuf::Span(SpanType::Process) => {
+                write!(wr, "{:016x}", process_span())?
+            }
+            TokenBuf::Meta(ref name, None) => {
+                let meta = rec.iter().find(|meta| meta.name == name)
+                    .ok_or_else(|| Error::MetaNotFound(name.clone()))?;
+
+                meta.value.format(&mut Formatter::new(wr, Default::default()))?;
+            }
+            TokenBuf::Meta(ref name, Some(spec)) => {
+                let meta = rec.iter().find(|meta| meta.name == name)
+                    .ok_or_else(|| Error::MetaNotFound(name.clone()))?;
+
+                meta.value.format(&mut Formatter::new(wr, spec.into()))?;
+            }
+            TokenBuf::MetaList(..) if self.item.is_some() => {
+                for meta in rec.iter() {
+                    self.format_item(rec, meta, wr)?;
                 }
-                TokenBuf::Line(Some(spec)) => {
-                    rec.line().format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::MetaList(spec) => {
+                // The spec is applied to each value separately, names are written as is.
+                let spec = spec.map_or(Default::default(), Into::into);
+
+                let mut iter = rec.iter();
+                if let Some(meta) = iter.next() {
+                    wr.write_all(meta.name.as_bytes())?;
+                    write!(wr, ": ")?;
+                    meta.value.format(&mut Formatter::new(wr, spec))?;
                 }
-                TokenBuf::Module(None) => {
-                    wr.write_all(rec.module().as_bytes())?
+
+                for meta in iter {
+                    write!(wr, ", ")?;
+                    wr.write_all(meta.name.as_bytes())?;
+                    write!(wr, ": ")?;
+                    meta.value.format(&mut Formatter::new(wr, spec))?;
                 }
-                TokenBuf::Module(Some(spec)) => {
-                    rec.module().format(&mut Formatter::new(wr, spec.into()))?
+            }
+            TokenBuf::MetaListAffixed(ref prefix, ref suffix) => {
+                if rec.iter().next().is_some() {
+                    wr.write_all(prefix.as_bytes())?;
+                    self.format_token(rec, &TokenBuf::MetaList(None), wr)?;
+                    wr.write_all(suffix.as_bytes())?;
                 }
-                TokenBuf::Process(None, _ty) => {
-                    unimplemented!();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn format_item(&self, rec: &Record, meta: &Meta, wr: &mut Write) -> Result<(), Error> {
+        let tokens = match self.item {
+            Some(ref tokens) => tokens,
+            None => return Ok(()),
+        };
+
+        for token in tokens {
+            match *token {
+                TokenBuf::Meta(ref name, spec) if name == "name" => {
+                    let spec = spec.map_or(Default::default(), Into::into);
+                    meta.name.format(&mut Formatter::new(wr, spec))?;
                 }
-                TokenBuf::Process(Some(_spec), _ty) => {
-                    unimplemented!();
+                TokenBuf::Meta(ref name, spec) if name == "value" => {
+                    let spec = spec.map_or(Default::default(), Into::into);
+                    meta.value.format(&mut Formatter::new(wr, spec))?;
                 }
-                TokenBuf::Meta(ref name, None) => {
-                    let meta = rec.iter().find(|meta| meta.name == name)
-                        .ok_or(Error::new(ErrorKind::Other, "meta not found"))?;
+                ref token => self.format_token(rec, token, wr)?,
+            }
+        }
 
-                    meta.value.format(&mut Formatter::new(wr, Default::default()))?;
-                }
-                TokenBuf::Meta(ref name, Some(spec)) => {
-                    let meta = rec.iter().find(|meta| meta.name == name)
-                        .ok_or(Error::new(ErrorKind::Other, "meta not found"))?;
+        Ok(())
+    }
+}
 
-                    meta.value.format(&mut Formatter::new(wr, spec.into()))?;
-                }
-                TokenBuf::MetaList(None) => {
-                    let mut iter = rec.iter();
-                    if let Some(meta) = iter.next() {
-                        wr.write_all(meta.name.as_bytes())?;
-                        write!(wr, ": ")?;
-                        meta.value.format(&mut Formatter::new(wr, Default::default()))?;
-                    }
+/// Constructs a parse error pointing to the first `{...}` token of the given meta pattern.
+fn nested_metalist_error(pattern: &str) -> ParseError {
+    let mut offset = 0;
+    while offset < pattern.len() {
+        let tail = &pattern[offset..];
+        if tail.starts_with("{{") {
+            offset += 2;
+        } else if tail.starts_with("{...") {
+            break;
+        } else {
+            offset += tail.chars().next().unwrap().len_utf8();
+        }
+    }
 
-                    for meta in iter {
-                        write!(wr, ", ")?;
-                        wr.write_all(meta.name.as_bytes())?;
-                        write!(wr, ": ")?;
-                        meta.value.format(&mut Formatter::new(wr, Default::default()))?;
-                    }
-                }
-                TokenBuf::MetaList(Some(_spec)) => {
-                    unimplemented!();
+    let head = &pattern[..offset];
+
+    let mut expected = HashSet::new();
+    expected.insert("any token except of \"{...}\"");
+
+    ParseError {
+        line: head.matches('\n').count() + 1,
+        column: head.rsplit('\n').next().unwrap().chars().count() + 1,
+        offset: offset,
+        expected: expected,
+    }
+}
+
+impl<F: SevMap> Layout for PatternLayout<F> {
+    fn format(&self, rec: &Record, mut wr: &mut Write) -> Result<(), Error> {
+        for token in &self.tokens {
+            self.format_token(rec, token, wr)?;
+
+            match *token {
+                TokenBuf::Timestamp(..) | TokenBuf::TimestampNum(..) => {
+                    self.format_trace(rec, wr)?
                 }
+                _ => {}
             }
         }
 
@@ -182,28 +523,69 @@ impl<F: SevMap> Factory for PatternLayout<F> {
             .ok_or(r#"field "pattern" is required"#)?
             .as_string()
             .ok_or(r#"field "pattern" must be a string"#)?;
-        let res = box PatternLayout::new(pattern)?;
 
-        Ok(res)
+        let color = match cfg.find("color") {
+            Some(color) => color.as_boolean().ok_or("field \"color\" must be a boolean")?,
+            None => false,
+        };
+
+        if color {
+            configure(PatternLayout::with(pattern, ColorSevMap::new())?, cfg)
+        } else {
+            configure(PatternLayout::with(pattern, DefaultSevMap)?, cfg)
+        }
+    }
+}
+
+/// Applies optional settings, which are common for all severity mappings.
+fn configure<F: SevMap + 'static>(mut res: PatternLayout<F>, cfg: &Config) ->
+    Result<Box<Layout>, Box<error::Error>>
+{
+    if let Some(pattern) = cfg.find("meta_pattern") {
+        let pattern = pattern.as_string()
+            .ok_or("field \"meta_pattern\" must be a string")?;
+        res = res.meta_pattern(pattern)?;
     }
+
+    match cfg.find("trace_fields") {
+        Some(&Value::Bool(true)) => {
+            res = res.trace_fields(TRACE_FIELDS.iter().cloned());
+        }
+        Some(&Value::Bool(false)) | None => {}
+        Some(&Value::Array(ref fields)) => {
+            let fields = fields.iter()
+                .map(|field| field.as_string())
+                .collect::<Option<Vec<_>>>()
+                .ok_or("field \"trace_fields\" must contain only strings")?;
+            res = res.trace_fields(fields);
+        }
+        Some(..) => {
+            return Err("field \"trace_fields\" must be either a boolean or an array".into());
+        }
+    }
+
+    Ok(box res)
 }
 
 #[cfg(test)]
 mod tests {
     use std::io::Write;
     use std::str::from_utf8;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use chrono::Timelike;
     use chrono::offset::local::Local;
+    use log::LogLevel;
+    use serde_json;
 
     #[cfg(feature="benchmark")]
     use test::Bencher;
 
-    use {Meta, MetaLink, Record};
-    use layout::Layout;
-    use layout::pattern::{PatternLayout, SevMap};
-    use layout::pattern::grammar::{FormatSpec, SeverityType};
-    use meta::format::Alignment;
+    use {Format, Formatter, Meta, MetaLink, Record, Registry};
+    use factory::Factory;
+    use layout::{Alignment, Error, Layout};
+    use layout::pattern::{ColorSevMap, DefaultSevMap, PatternLayout, SevMap, TRACE_FIELDS};
+    use layout::pattern::grammar::{FormatSpec, SeverityType, TokenBuf};
 
     // TODO: Seems quite required for other testing modules. Maybe move into `record` module?
     macro_rules! record {
@@ -263,6 +645,17 @@ mod tests {
         assert_eq!("message: value", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn message_into_string() {
+        let layout = PatternLayout::new("message: {message}").unwrap();
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+
+        assert_eq!("message: value", layout.format_string(&rec).unwrap());
+    }
+
     #[cfg(feature="benchmark")]
     #[bench]
     fn bench_message(b: &mut Bencher) {
@@ -282,6 +675,25 @@ mod tests {
         run(&rec, b);
     }
 
+    #[test]
+    fn message_placeholder_for_inactive_record() {
+        let layout = PatternLayout::new("[{message}]").unwrap()
+            .inactive_message("<no message>");
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+        assert_eq!("[<no message>]", from_utf8(&buf[..]).unwrap());
+
+        rec.activate(format_args!(""));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+        assert_eq!("[]", from_utf8(&buf[..]).unwrap());
+    }
+
     #[test]
     fn message_with_spec() {
         let layout = PatternLayout::new("[{message:<10}]").unwrap();
@@ -334,6 +746,32 @@ mod tests {
         assert_eq!("/1005/", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn message_with_precision_multibyte() {
+        let layout = PatternLayout::new("[{message:.3}]").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("débâcle, 日本語"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[déb]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn message_with_precision_multibyte_cjk() {
+        let layout = PatternLayout::new("[{message:>5.3}]").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("日本語です"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[  日本語]", from_utf8(&buf[..]).unwrap());
+    }
+
     #[cfg(feature="benchmark")]
     #[bench]
     fn bench_message_with_spec(b: &mut Bencher) {
@@ -354,6 +792,174 @@ mod tests {
         run(&rec, b);
     }
 
+    #[test]
+    fn message_with_width_from_meta() {
+        let layout = PatternLayout::new("[{message:<$width}]").unwrap();
+
+        let mut buf = Vec::new();
+        let width = 10;
+        let meta = [Meta::new("width", &width)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[value     ]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn fail_message_with_width_from_missing_meta() {
+        let layout = PatternLayout::new("[{message:<$width}]").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        match layout.format(&rec, &mut buf) {
+            Err(Error::MetaNotFound(name)) => assert_eq!("width", name),
+            other => panic!("expected MetaNotFound, actual: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fail_message_with_width_from_non_integer_meta() {
+        let layout = PatternLayout::new("[{message:<$width}]").unwrap();
+
+        let mut buf = Vec::new();
+        let width = "wide";
+        let meta = [Meta::new("width", &width)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        assert!(layout.format(&rec, &mut buf).is_err());
+    }
+
+    #[test]
+    fn trace_fields() {
+        let layout = PatternLayout::new("{timestamp:{%Y}s} {message}").unwrap()
+            .trace_fields(TRACE_FIELDS.iter().cloned());
+
+        let mut buf = Vec::new();
+        let trace = "4bf92f3577b34da6";
+        let span = "00f067aa0ba902b7";
+        let meta = [Meta::new("span_id", &span), Meta::new("trace_id", &trace)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("1970 trace_id=4bf92f3577b34da6 span_id=00f067aa0ba902b7 ",
+            from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn trace_fields_omitted_when_absent() {
+        let layout = PatternLayout::new("{timestamp:{%Y}s} {message}").unwrap()
+            .trace_fields(TRACE_FIELDS.iter().cloned());
+
+        let mut buf = Vec::new();
+        let trace = "4bf92f3577b34da6";
+        let meta = [Meta::new("trace_id", &trace)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        let out = from_utf8(&buf[..]).unwrap();
+        assert!(out.ends_with(" trace_id=4bf92f3577b34da6 value"));
+        assert!(!out.contains("span_id"));
+    }
+
+    #[test]
+    fn from_tokens() {
+        let spec = FormatSpec {
+            fill: '.',
+            align: Alignment::AlignRight,
+            width: 8,
+            .. FormatSpec::default()
+        };
+
+        let tokens = vec![
+            TokenBuf::Severity(None, SeverityType::Num),
+            TokenBuf::Piece(": ".into()),
+            TokenBuf::Message(Some(spec)),
+            TokenBuf::Piece(" [".into()),
+            TokenBuf::Meta("path".into(), None),
+            TokenBuf::Piece("]".into()),
+        ];
+
+        let layout = PatternLayout::from_tokens(tokens, DefaultSevMap);
+
+        let mut buf = Vec::new();
+        let path = "/var/www";
+        let meta = [Meta::new("path", &path)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(4, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("4: ...value [/var/www]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn meta_atomic_counter() {
+        let layout = PatternLayout::new("requests: {requests}").unwrap();
+
+        let counter = AtomicUsize::new(0);
+        counter.fetch_add(3, Ordering::Relaxed);
+
+        let mut buf = Vec::new();
+        let meta = [Meta::new("requests", &counter)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("requests: 3", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn metalist_with_item_pattern() {
+        let layout = PatternLayout::new("{message}{...}").unwrap()
+            .meta_pattern("\n\t{name} = {value}").unwrap();
+
+        let mut buf = Vec::new();
+        let path = "/var/www";
+        let code = 404;
+        let meta = [Meta::new("path", &path), Meta::new("code", &code)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("file does not exist"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("file does not exist\n\tpath = /var/www\n\tcode = 404",
+            from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn metalist_with_item_pattern_spec() {
+        let layout = PatternLayout::new("{...}").unwrap()
+            .meta_pattern("[{name:>6}: {value:.<4}]").unwrap();
+
+        let mut buf = Vec::new();
+        let code = 42;
+        let meta = [Meta::new("code", &code)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[  code: 42..]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn fail_metalist_inside_item_pattern() {
+        let err = PatternLayout::new("{...}").unwrap()
+            .meta_pattern("{name}={value}{...}").err().unwrap();
+        assert_eq!(14, err.offset);
+
+        let err = PatternLayout::new("{...}").unwrap()
+            .meta_pattern("{{{name}}}\n{...|prefix='['|suffix=']'}").err().unwrap();
+        assert_eq!((2, 1), (err.line, err.column));
+    }
+
     #[test]
     fn severity() {
         // NOTE: No severity mapping provided, layout falls back to the numeric case.
@@ -367,6 +973,175 @@ mod tests {
         assert_eq!("[0]", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn severity_string_from_i32_falls_back_to_num() {
+        // A plain integer severity has no names, so `{severity:s}` gives digits.
+        let layout = PatternLayout::new("[{severity:s}]").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(4, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[4]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_string_from_loglevel() {
+        let layout = PatternLayout::new("[{severity:s}] [{severity:d}]").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(LogLevel::Error, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[Error] [4]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn join() {
+        let layout = PatternLayout::join(&["timestamp:{%H:%M}s", "severity", "message"], " ")
+            .unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(LogLevel::Info, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        let expected = format!("{} Info value", rec.datetime().format("%H:%M"));
+        assert_eq!(expected, from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn join_escapes_separator() {
+        let layout = PatternLayout::join(&["severity:d", "message"], "} {").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(2, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("2} {value", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn join_empty() {
+        let layout = PatternLayout::join(&[], " ").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        layout.format(&Record::new(0, 0, "", &metalink), &mut buf).unwrap();
+
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn severity_string_case() {
+        let layout = PatternLayout::new("{severity:s} {severity:upper} {severity:lower}").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(LogLevel::Error, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("Error ERROR error", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_string_case_with_spec() {
+        let layout = PatternLayout::new("[{severity:<6upper}] [{severity:>6.3lower}]").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(LogLevel::Warn, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[WARN  ] [   war]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_string_colored() {
+        let layout = PatternLayout::with("[{severity}] [{severity:d}]", ColorSevMap::new())
+            .unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(LogLevel::Error, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[\x1B[38;5;9mError\x1B[0m] [4]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_string_colored_with_custom_color() {
+        fn color(sev: i32) -> u8 {
+            160 + sev as u8
+        }
+
+        let layout = PatternLayout::with("{severity}", ColorSevMap::new().color(color)).unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(7, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("\x1B[38;5;167m7\x1B[0m", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_colored_from_config() {
+        let cfg = serde_json::from_str(r#"{
+            "type": "pattern",
+            "pattern": "{severity}",
+            "color": true
+        }"#).unwrap();
+        let layout = <PatternLayout as Factory>::from(&cfg, &Registry::new()).unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(LogLevel::Warn, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("\x1B[38;5;3mWarn\x1B[0m", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_syslog() {
+        let layout = PatternLayout::new("<{severity:syslog}>").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(LogLevel::Warn, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("<4>", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_syslog_with_mapping() {
+        struct Mapping;
+
+        impl SevMap for Mapping {
+            fn map(&self, rec: &Record, _spec: FormatSpec, ty: SeverityType, wr: &mut Write) ->
+                Result<(), ::std::io::Error>
+            {
+                assert_eq!(SeverityType::Syslog, ty);
+                write!(wr, "{}", 7 - rec.severity())
+            }
+        }
+
+        let layout = PatternLayout::with("<{severity:syslog}>", Mapping).unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(5, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("<2>", from_utf8(&buf[..]).unwrap());
+    }
+
     #[test]
     fn severity_num() {
         let layout = PatternLayout::new("[{severity:d}]").unwrap();
@@ -397,42 +1172,97 @@ mod tests {
             }
         }
 
-        let layout = PatternLayout::with("[{severity}]", Mapping).unwrap();
+        let layout = PatternLayout::with("[{severity}]", Mapping).unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(2, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[DEBUG]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_num_with_mapping() {
+        struct Mapping;
+
+        impl SevMap for Mapping {
+            fn map(&self, rec: &Record, spec: FormatSpec, ty: SeverityType, wr: &mut Write) ->
+                Result<(), ::std::io::Error>
+            {
+                let sev = rec.severity();
+                assert_eq!(2, sev);
+                assert_eq!(' ', spec.fill);
+                assert_eq!(Alignment::AlignUnknown, spec.align);
+                assert_eq!(0, spec.width);
+                assert_eq!(SeverityType::Num, ty);
+                wr.write_all("DEBUG".as_bytes())
+            }
+        }
+
+        let layout = PatternLayout::with("[{severity:d}]", Mapping).unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(2, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[DEBUG]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_with_context_aware_mapping() {
+        struct Mapping;
+
+        impl SevMap for Mapping {
+            fn map(&self, rec: &Record, spec: FormatSpec, ty: SeverityType, wr: &mut Write) ->
+                Result<(), ::std::io::Error>
+            {
+                let mut channel = Vec::new();
+                if let Some(meta) = rec.iter().find(|meta| meta.name == "channel") {
+                    meta.value.format(&mut Formatter::new(&mut channel, Default::default()))?;
+                }
+
+                match (ty, &channel[..] == b"audit") {
+                    (SeverityType::String, true) => {
+                        "AUDIT".format(&mut Formatter::new(wr, spec.into()))
+                    }
+                    (..) => DefaultSevMap.map(rec, spec, ty, wr),
+                }
+            }
+        }
+
+        let layout = PatternLayout::with("[{severity:<6}] [{severity:d}]", Mapping).unwrap();
+
+        let mut buf = Vec::new();
+        let channel = "audit";
+        let meta = [Meta::new("channel", &channel)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(LogLevel::Warn, 0, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[AUDIT ] [3]", from_utf8(&buf[..]).unwrap());
 
         let mut buf = Vec::new();
-        let metalink = MetaLink::new(&[]);
-        let rec = Record::new(2, 0, "", &metalink);
+        let channel = "system";
+        let meta = [Meta::new("channel", &channel)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(LogLevel::Warn, 0, "", &metalink);
         layout.format(&rec, &mut buf).unwrap();
 
-        assert_eq!("[DEBUG]", from_utf8(&buf[..]).unwrap());
+        assert_eq!("[Warn  ] [3]", from_utf8(&buf[..]).unwrap());
     }
 
     #[test]
-    fn severity_num_with_mapping() {
-        struct Mapping;
-
-        impl SevMap for Mapping {
-            fn map(&self, rec: &Record, spec: FormatSpec, ty: SeverityType, wr: &mut Write) ->
-                Result<(), ::std::io::Error>
-            {
-                let sev = rec.severity();
-                assert_eq!(2, sev);
-                assert_eq!(' ', spec.fill);
-                assert_eq!(Alignment::AlignLeft, spec.align);
-                assert_eq!(0, spec.width);
-                assert_eq!(SeverityType::Num, ty);
-                wr.write_all("DEBUG".as_bytes())
-            }
-        }
-
-        let layout = PatternLayout::with("[{severity:d}]", Mapping).unwrap();
+    fn severity_num_aligned_right_by_default() {
+        let layout = PatternLayout::new("[{severity:3d}] [{severity:3}]").unwrap();
 
         let mut buf = Vec::new();
         let metalink = MetaLink::new(&[]);
-        let rec = Record::new(2, 0, "", &metalink);
+        let rec = Record::new(4, 0, "", &metalink);
         layout.format(&rec, &mut buf).unwrap();
 
-        assert_eq!("[2]", from_utf8(&buf[..]).unwrap());
+        assert_eq!("[  4] [4  ]", from_utf8(&buf[..]).unwrap());
     }
 
     #[test]
@@ -511,6 +1341,22 @@ mod tests {
         assert!(layout.format(&rec, &mut &mut buf[..]).is_err());
     }
 
+    #[test]
+    fn fail_format_small_buffer_wraps_io_error() {
+        let layout = PatternLayout::new("[{message}]").unwrap();
+
+        let mut buf = [0u8];
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+
+        match layout.format(&rec, &mut &mut buf[..]) {
+            Err(Error::Io(..)) => {}
+            other => panic!("expected I/O error, actual: {:?}", other),
+        }
+    }
+
     #[test]
     fn timestamp() {
         let metalink = MetaLink::new(&[]);
@@ -526,6 +1372,41 @@ mod tests {
         assert_eq!(format!("{}", rec.datetime().format("%+")), from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn timestamp_iso_week() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!(""));
+
+        let layout = PatternLayout::new("{timestamp:{%G-W%V}s}").unwrap();
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        let actual = from_utf8(&buf[..]).unwrap();
+        assert_eq!(format!("{}", rec.datetime().format("%G-W%V")), actual);
+        assert_eq!(8, actual.len());
+        assert_eq!("-W", &actual[4..6]);
+    }
+
+    #[test]
+    fn timestamp_week_and_ordinal_directives() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!(""));
+
+        for directive in &["%G", "%g", "%V", "%j", "%U", "%W", "%u", "%w"] {
+            let layout = PatternLayout::new(&format!("{{timestamp:{{{}}}s}}", directive)).unwrap();
+
+            let mut buf = Vec::new();
+            layout.format(&rec, &mut buf).unwrap();
+
+            let actual = from_utf8(&buf[..]).unwrap();
+            assert_eq!(format!("{}", rec.datetime().format(directive)), actual);
+            assert!(!actual.contains('%'), "directive {} is not rendered: {}", directive, actual);
+        }
+    }
+
     #[test]
     fn timestamp_local() {
         let metalink = MetaLink::new(&[]);
@@ -663,6 +1544,177 @@ mod tests {
         run(&rec);
     }
 
+    #[test]
+    fn meta_f64_general() {
+        let layout = PatternLayout::new("{small:g} {large:g} {pi:.3g} {huge:G}").unwrap();
+
+        let small = 0.00001234;
+        let large = 100500.0;
+        let pi = 3.1415;
+        let huge = 123456789.0;
+        let meta = [
+            Meta::new("small", &small),
+            Meta::new("large", &large),
+            Meta::new("pi", &pi),
+            Meta::new("huge", &huge),
+        ];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("1.234e-5 100500 3.14 1.23457E8", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn meta_with_sign() {
+        let layout = PatternLayout::new("{n:+}").unwrap();
+
+        let val = 42;
+        let meta = [Meta::new("n", &val)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("+42", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn meta_with_alternate_hex() {
+        let layout = PatternLayout::new("{n:#x}").unwrap();
+
+        let val = 42;
+        let meta = [Meta::new("n", &val)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!(format!("{:#x}", 42), from_utf8(&buf[..]).unwrap());
+        assert_eq!("0x2a", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn meta_alternate_zero_padded_hex() {
+        let layout = PatternLayout::new("{code:#08x}").unwrap();
+
+        let val = 42;
+        let meta = [Meta::new("code", &val)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("0x00002a", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn line_and_severity_hex() {
+        let layout = PatternLayout::new("{line:X} {severity:#b}").unwrap();
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(5, 255, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("FF 0b101", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn severity_with_all_flags() {
+        let layout = PatternLayout::new("{severity:+#06x}").unwrap();
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(10, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!(format!("{:+#06x}", 10), from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn meta_quoted() {
+        let layout = PatternLayout::new("{name:q}").unwrap();
+
+        let val = r#"he said "hi""#;
+        let meta = [Meta::new("name", &val)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!(r#""he said \"hi\"""#, from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn meta_with_zero_pad() {
+        let layout = PatternLayout::new("{n:08}").unwrap();
+
+        let val = -42;
+        let meta = [Meta::new("n", &val)];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!(format!("{:08}", -42), from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn span() {
+        let layout = PatternLayout::new("{span}").unwrap();
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!(16, buf.len());
+        assert!(buf.iter().all(|&c| (c as char).is_digit(16)));
+    }
+
+    #[test]
+    fn span_process_is_stable() {
+        let layout = PatternLayout::new("{span:p}").unwrap();
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf1 = Vec::new();
+        layout.format(&rec, &mut buf1).unwrap();
+        let mut buf2 = Vec::new();
+        layout.format(&rec, &mut buf2).unwrap();
+
+        assert_eq!(16, buf1.len());
+        assert_eq!(buf1, buf2);
+    }
+
+    #[test]
+    fn span_process_is_shared_between_layouts() {
+        let layout1 = PatternLayout::new("{span:p}").unwrap();
+        let layout2 = PatternLayout::new("{span:p}").unwrap();
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf1 = Vec::new();
+        layout1.format(&rec, &mut buf1).unwrap();
+        let mut buf2 = Vec::new();
+        layout2.format(&rec, &mut buf2).unwrap();
+
+        assert_eq!(buf1, buf2);
+    }
+
     #[test]
     fn fail_meta_not_found() {
         let layout = PatternLayout::new("{flag}").unwrap();
@@ -672,7 +1724,10 @@ mod tests {
         let rec = Record::new(0, 0, "", &metalink);
 
         let mut buf = Vec::new();
-        assert!(layout.format(&rec, &mut buf).is_err());
+        match layout.format(&rec, &mut buf) {
+            Err(Error::MetaNotFound(ref name)) => assert_eq!("flag", name),
+            other => panic!("expected meta not found error, actual: {:?}", other),
+        }
     }
 
     #[test]
@@ -694,6 +1749,59 @@ mod tests {
         assert_eq!("num: 42, name: Vasya", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn metalist_with_spec() {
+        let layout = PatternLayout::new("[{...:<8}]").unwrap();
+
+        let v1 = 42;
+        let v2 = "Vasya";
+        let meta = [
+            Meta::new("num", &v1),
+            Meta::new("name", &v2),
+        ];
+        let metalink = MetaLink::new(&meta);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("[num: 42      , name: Vasya   ]", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn metalist_affixed_omitted_when_empty() {
+        let layout = PatternLayout::new("{message}{...|prefix=' - {'|suffix='}'}").unwrap();
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("le message"));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("le message", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn metalist_affixed() {
+        let layout = PatternLayout::new("{message}{...|prefix=' - {'|suffix='}'}").unwrap();
+
+        let v1 = 42;
+        let v2 = "Vasya";
+        let meta = [
+            Meta::new("num", &v1),
+            Meta::new("name", &v2),
+        ];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("le message"));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("le message - {num: 42, name: Vasya}", from_utf8(&buf[..]).unwrap());
+    }
+
     #[test]
     fn module() {
         let layout = PatternLayout::new("{module}").unwrap();
@@ -718,6 +1826,50 @@ mod tests {
         assert_eq!("/blacklog::la/", from_utf8(&buf[..]).unwrap());
     }
 
+    fn format_module(pattern: &str) -> String {
+        let layout = PatternLayout::new(pattern).unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, module_path!(), &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn module_last_segment() {
+        assert_eq!("tests", format_module("{module:-1}"));
+    }
+
+    #[test]
+    fn module_last_two_segments() {
+        assert_eq!("pattern::tests", format_module("{module:-2}"));
+    }
+
+    #[test]
+    fn module_more_segments_than_available() {
+        assert_eq!("blacklog::layout::pattern::tests", format_module("{module:-4}"));
+        assert_eq!("blacklog::layout::pattern::tests", format_module("{module:-10}"));
+    }
+
+    #[test]
+    fn module_segments_with_spec() {
+        assert_eq!("pattern::tests..", format_module("{module:.<16-2}"));
+    }
+
+    #[test]
+    fn module_zero_segments_from_tokens() {
+        let layout = PatternLayout::from_tokens(vec![TokenBuf::ModuleTail(None, 0)], DefaultSevMap);
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, module_path!(), &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("blacklog::layout::pattern::tests", from_utf8(&buf[..]).unwrap());
+    }
+
     #[test]
     fn line() {
         let layout = PatternLayout::new("{line}").unwrap();
@@ -741,4 +1893,30 @@ mod tests {
 
         assert_eq!("/555/", from_utf8(&buf[..]).unwrap());
     }
+
+    #[test]
+    fn line_with_zero_pad() {
+        let layout = PatternLayout::new("{line:05}").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 42, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        assert_eq!("00042", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn line_with_zero_pad_and_explicit_align() {
+        let layout = PatternLayout::new("{line:<05}").unwrap();
+
+        let mut buf = Vec::new();
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 42, "", &metalink);
+        layout.format(&rec, &mut buf).unwrap();
+
+        // Like in `std::fmt` zero padding ignores the explicit alignment.
+        assert_eq!(format!("{:<05}", 42), from_utf8(&buf[..]).unwrap());
+        assert_eq!("00042", from_utf8(&buf[..]).unwrap());
+    }
 }
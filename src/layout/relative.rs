@@ -0,0 +1,115 @@
+use std::error;
+use std::io::Write;
+
+use chrono::{DateTime, Duration, UTC};
+
+use {Config, Record, Registry};
+use factory::Factory;
+
+use super::{Error, Layout};
+
+/// A layout, that prefixes records with a coarse human-readable time elapsed since they were
+/// logged, like `just now`, `3s ago` or `5m ago`, followed by the output of the wrapped layout.
+///
+/// Absolute timestamps are noisy for interactive development consoles, while relative ones are
+/// easy to scan. Elapsed time is rounded down to the largest whole unit: seconds, minutes, hours
+/// or days. Anything less than a second old, including records from the future because of clock
+/// adjustments, is shown as `just now`.
+///
+/// # Note
+///
+/// The elapsed time is calculated against the current time at the moment of formatting, so it's
+/// intended for live viewing only. Formatting the same record again later, for example when it's
+/// buffered, gives a different output.
+pub struct RelativeTimeLayout {
+    layout: Box<Layout>,
+}
+
+impl RelativeTimeLayout {
+    pub fn new(layout: Box<Layout>) -> RelativeTimeLayout {
+        RelativeTimeLayout {
+            layout: layout,
+        }
+    }
+
+    fn format_at(&self, rec: &Record, now: DateTime<UTC>, wr: &mut Write) -> Result<(), Error> {
+        wr.write_all(describe(now - rec.datetime()).as_bytes())?;
+        wr.write_all(b" ")?;
+        self.layout.format(rec, wr)
+    }
+}
+
+/// Describes the given elapsed time using the largest whole unit.
+fn describe(elapsed: Duration) -> String {
+    if elapsed < Duration::seconds(1) {
+        "just now".into()
+    } else if elapsed < Duration::minutes(1) {
+        format!("{}s ago", elapsed.num_seconds())
+    } else if elapsed < Duration::hours(1) {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if elapsed < Duration::days(1) {
+        format!("{}h ago", elapsed.num_hours())
+    } else {
+        format!("{}d ago", elapsed.num_days())
+    }
+}
+
+impl Layout for RelativeTimeLayout {
+    fn format(&self, rec: &Record, wr: &mut Write) -> Result<(), Error> {
+        self.format_at(rec, UTC::now(), wr)
+    }
+}
+
+impl Factory for RelativeTimeLayout {
+    type Item = Layout;
+
+    fn ty() -> &'static str {
+        "relative_time"
+    }
+
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Layout>, Box<error::Error>> {
+        let layout = registry.layout(cfg.find("layout")
+            .ok_or("field \"layout\" is required")?)?;
+
+        Ok(box RelativeTimeLayout::new(layout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use chrono::Duration;
+
+    use {MetaLink, Record};
+    use layout::PatternLayout;
+
+    use super::{describe, RelativeTimeLayout};
+
+    #[test]
+    fn format_seconds_ago() {
+        let layout = RelativeTimeLayout::new(box PatternLayout::new("{message}").unwrap());
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("le message"));
+
+        let mut buf = Vec::new();
+        let now = rec.datetime() + Duration::milliseconds(3500);
+        layout.format_at(&rec, now, &mut buf).unwrap();
+
+        assert_eq!("3s ago le message", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn describe_buckets() {
+        assert_eq!("just now", describe(Duration::milliseconds(999)));
+        assert_eq!("just now", describe(Duration::seconds(-5)));
+        assert_eq!("1s ago", describe(Duration::seconds(1)));
+        assert_eq!("59s ago", describe(Duration::seconds(59)));
+        assert_eq!("1m ago", describe(Duration::seconds(60)));
+        assert_eq!("59m ago", describe(Duration::minutes(59)));
+        assert_eq!("2h ago", describe(Duration::minutes(150)));
+        assert_eq!("3d ago", describe(Duration::hours(75)));
+    }
+}
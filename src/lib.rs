@@ -1,6 +1,7 @@
 #![cfg_attr(feature="benchmark", feature(test))]
 
 #![feature(box_syntax)]
+#![feature(integer_atomics)]
 #![feature(plugin)]
 #![feature(question_mark)]
 #![feature(unicode)]
@@ -8,10 +9,12 @@
 #![plugin(peg_syntax_ext)]
 
 #[cfg(unix)] extern crate libc;
+extern crate backtrace;
 #[cfg(feature="benchmark")] extern crate test;
 extern crate chrono;
 extern crate serde_json;
 extern crate log;
+#[cfg(feature="rusqlite")] extern crate rusqlite;
 
 mod factory;
 pub mod filter;
@@ -20,18 +23,25 @@ pub mod layout;
 pub mod logger;
 mod meta;
 pub mod output;
+mod panic;
+mod random;
 mod record;
 mod registry;
-mod severity;
+pub mod severity;
+#[cfg(test)]
+mod support;
+pub mod testing;
 mod thread;
 
 pub use self::filter::Filter;
 pub use self::handle::Handle;
 pub use self::layout::Layout;
 pub use self::logger::Logger;
-pub use self::meta::{FnMeta, Meta, MetaBuf, MetaLink};
+pub use self::meta::{Backtrace, ByteSize, ErrorMeta, FnMeta, IoErrorKind, IoErrorMeta, Meta,
+                     MetaBuf, MetaLink, OwnedContext};
 pub use self::meta::format::{Format, Formatter, IntoBoxedFormat};
 pub use self::output::Output;
-pub use self::record::{Record};
+pub use self::panic::{install_panic_hook, install_panic_hook_with_backtrace};
+pub use self::record::{Context, Record, RecordBuf, Timestamping};
 pub use self::registry::{Config, Registry};
-pub use self::severity::Severity;
+pub use self::severity::{severity_color, syslog_priority, Severity};
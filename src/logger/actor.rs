@@ -1,11 +1,13 @@
 use std::fmt::Arguments;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::{self, JoinHandle};
 
+use filter::FilterAction;
 use handle::Handle;
 use logger::Logger;
-use record::{Record, RecordBuf};
+use record::{Record, RecordBuf, Timestamping};
 
 enum Event {
     Record(RecordBuf),
@@ -16,16 +18,33 @@ struct Inner {
     // TODO: Maybe use tx/rx connectivity to auto break the loop?
     tx: Mutex<mpsc::Sender<Event>>,
     thread: Option<JoinHandle<()>>,
+    /// Number of records sent, but not yet dequeued by the worker.
+    pending: Arc<AtomicUsize>,
 }
 
 impl Inner {
-    fn new(tx: Sender<Event>, rx: Receiver<Event>, handlers: Vec<Box<Handle>>) -> Inner {
+    fn new(tx: Sender<Event>, rx: Receiver<Event>, handlers: Vec<Box<Handle>>,
+           timestamping: Timestamping) -> Inner
+    {
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let counter = pending.clone();
         let thread = thread::spawn(move || {
             for event in rx {
                 match event {
                     Event::Record(rec) => {
+                        counter.fetch_sub(1, Ordering::Relaxed);
+
                         rec.borrow_and(|rec| {
+                            if let Timestamping::Handling = timestamping {
+                                rec.restamp();
+                            }
+
                             for handle in handlers.iter() {
+                                if let FilterAction::Deny = handle.filter(rec) {
+                                    continue;
+                                }
+
                                 handle.handle(rec).unwrap();
                             }
                         });
@@ -38,6 +57,7 @@ impl Inner {
         Inner {
             tx: Mutex::new(tx),
             thread: Some(thread),
+            pending: pending,
         }
     }
 }
@@ -52,28 +72,62 @@ impl Drop for Inner {
 }
 
 // TODO: Maybe better AsyncLoggerAdaptor?
+/// Asynchronous logger, which handles records in a dedicated thread.
+///
+/// Records are activated in the caller's thread, so by default their timestamps reflect the time
+/// of the logging call regardless of how long they wait in the queue. Use `with_timestamping` to
+/// capture the time of handling instead.
 #[derive(Clone)]
 pub struct ActorLogger {
     tx: Sender<Event>,
     inner: Arc<Inner>,
+    max_message_len: Option<usize>,
 }
 
 impl ActorLogger {
     pub fn new(handlers: Vec<Box<Handle>>) -> ActorLogger {
+        ActorLogger::with_timestamping(handlers, Timestamping::default())
+    }
+
+    /// Constructs a logger with the given timestamp capture policy.
+    pub fn with_timestamping(handlers: Vec<Box<Handle>>, timestamping: Timestamping) ->
+        ActorLogger
+    {
         let (tx, rx) = mpsc::channel();
 
         ActorLogger {
             tx: tx.clone(),
-            inner: Arc::new(Inner::new(tx, rx, handlers)),
+            inner: Arc::new(Inner::new(tx, rx, handlers, timestamping)),
+            max_message_len: None,
         }
     }
+
+    /// Sets the maximum length of formatted messages in bytes, truncating longer ones.
+    ///
+    /// A truncated message is followed by a marker with the size of the dropped part, like
+    /// `…[truncated 4.0 KiB]`. The marker is not counted in the limit, so truncated messages are
+    /// slightly longer than the given length. See `Record::activate_with_limit` for details.
+    pub fn with_max_message_len(mut self, len: usize) -> ActorLogger {
+        self.max_message_len = Some(len);
+        self
+    }
+
+    /// Returns the number of records waiting in the queue to be handled.
+    ///
+    /// The value is approximate, because the worker may dequeue records concurrently, but it's
+    /// enough to detect that the queue is backing up, for example to shed non-critical logs.
+    pub fn pending(&self) -> usize {
+        self.inner.pending.load(Ordering::Relaxed)
+    }
 }
 
 impl Logger for ActorLogger {
     fn log<'a, 'b>(&self, rec: &mut Record<'a>, args: Arguments<'b>) {
-        rec.activate(args);
+        rec.activate_with_limit(args, self.max_message_len);
 
+        self.inner.pending.fetch_add(1, Ordering::Relaxed);
         if let Err(..) = self.tx.send(Event::Record(RecordBuf::from(&*rec))) {
+            self.inner.pending.fetch_sub(1, Ordering::Relaxed);
             // TODO: Return error.
         }
     }
@@ -0,0 +1,111 @@
+use {Filter, MetaBuf};
+
+use handle::Handle;
+use logger::{Logger, SeverityFilteredLoggerAdapter, SyncLogger};
+
+/// A builder, that assembles a synchronous logger with all of its optional stages.
+///
+/// Stages are composed in the order of their cost: records below the severity threshold are
+/// dropped first using a single atomic load, then the logger-level filter is checked, and only
+/// after that records are activated and passed through handles with default meta attributes
+/// attached.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::{Logger, MetaBuf};
+/// use blacklog::logger::LoggerBuilder;
+///
+/// fn main() {
+///     let log = LoggerBuilder::new()
+///         .threshold(2)
+///         .default_meta(MetaBuf::new("service", Box::new("api")))
+///         .build();
+///
+///     log!(log, 1, "dropped by the threshold");
+///     log!(log, 2, "file does not exist: {}", ["favicon.ico"]);
+/// }
+/// ```
+pub struct LoggerBuilder {
+    handles: Vec<Box<Handle>>,
+    threshold: Option<i32>,
+    filter: Option<Box<Filter>>,
+    defaults: Vec<MetaBuf>,
+    max_message_len: Option<usize>,
+}
+
+impl LoggerBuilder {
+    pub fn new() -> LoggerBuilder {
+        LoggerBuilder {
+            handles: Vec::new(),
+            threshold: None,
+            filter: None,
+            defaults: Vec::new(),
+            max_message_len: None,
+        }
+    }
+
+    /// Appends the given handle. Handles are called in the order they were added.
+    pub fn handle(mut self, handle: Box<Handle>) -> LoggerBuilder {
+        self.handles.push(handle);
+        self
+    }
+
+    /// Sets the minimum severity of records to be logged.
+    pub fn threshold(mut self, threshold: i32) -> LoggerBuilder {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Sets the filter, that is applied to records before their activation.
+    pub fn filter(mut self, filter: Box<Filter>) -> LoggerBuilder {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Appends the given meta attribute, that is attached to every record.
+    pub fn default_meta(mut self, meta: MetaBuf) -> LoggerBuilder {
+        self.defaults.push(meta);
+        self
+    }
+
+    /// Sets the maximum length of formatted messages in bytes, truncating longer ones.
+    ///
+    /// A truncated message is followed by a marker with the size of the dropped part, like
+    /// `…[truncated 4.0 KiB]`. The marker is not counted in the limit, so truncated messages are
+    /// slightly longer than the given length. See `Record::activate_with_limit` for details.
+    pub fn max_message_len(mut self, len: usize) -> LoggerBuilder {
+        self.max_message_len = Some(len);
+        self
+    }
+
+    /// Consumes the builder, returning the assembled logger.
+    pub fn build(self) -> Box<Logger> {
+        let mut logger = SyncLogger::with_defaults(self.handles, self.defaults);
+
+        if let Some(filter) = self.filter {
+            logger = logger.with_filter(filter);
+        }
+
+        if let Some(len) = self.max_message_len {
+            logger = logger.with_max_message_len(len);
+        }
+
+        match self.threshold {
+            Some(threshold) => {
+                let logger = SeverityFilteredLoggerAdapter::new(logger);
+                logger.filter(threshold);
+                box logger
+            }
+            None => box logger,
+        }
+    }
+}
+
+impl Default for LoggerBuilder {
+    fn default() -> LoggerBuilder {
+        LoggerBuilder::new()
+    }
+}
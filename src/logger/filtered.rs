@@ -47,6 +47,10 @@ impl<L: Logger> Logger for FilteredLoggerAdapter<L> {
             }
         }
     }
+
+    fn enabled(&self, sev: i32) -> bool {
+        self.logger.enabled(sev)
+    }
 }
 
 /// Extends the given logger with an ability to fast filter incoming events by their severity value.
@@ -81,4 +85,8 @@ impl<L: Logger> Logger for SeverityFilteredLoggerAdapter<L> {
             self.logger.log(rec, args)
         }
     }
+
+    fn enabled(&self, sev: i32) -> bool {
+        sev >= self.threshold.load(Ordering::Relaxed) as i32 && self.logger.enabled(sev)
+    }
 }
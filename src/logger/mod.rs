@@ -4,12 +4,16 @@ use std::ops::Deref;
 use record::Record;
 
 pub use self::actor::ActorLogger;
+pub use self::builder::LoggerBuilder;
 pub use self::filtered::{FilteredLoggerAdapter, SeverityFilteredLoggerAdapter};
 pub use self::sync::SyncLogger;
+pub use self::tee::TeeLogger;
 
 mod actor;
+mod builder;
 mod filtered;
 mod sync;
+mod tee;
 
 /// Loggers are, well, responsible for logging. Nuff said.
 pub trait Logger: Send {
@@ -19,19 +23,59 @@ pub trait Logger: Send {
     ///
     /// Loggers can be combined into chains with various fitlering stages.
     fn log<'a, 'b>(&self, rec: &mut Record<'a>, args: Arguments<'b>);
+
+    /// Returns `false` if records with the given severity are definitely dropped by this logger.
+    ///
+    /// This allows to skip building expensive arguments and meta information entirely. Note, that
+    /// `true` doesn't guarantee that a record will be logged, because some filters can make their
+    /// decision only when seeing the entire record.
+    fn enabled(&self, sev: i32) -> bool {
+        let _ = sev;
+        true
+    }
 }
 
 impl<T: Logger + ?Sized, U: Deref<Target=T> + Send> Logger for U {
     fn log<'a, 'b>(&self, rec: &mut Record<'a>, args: Arguments<'b>) {
         self.deref().log(rec, args)
     }
+
+    fn enabled(&self, sev: i32) -> bool {
+        self.deref().enabled(sev)
+    }
 }
 
+/// Checks whether records with the given severity may be logged by the given logger.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::Logger;
+/// use blacklog::logger::{SeverityFilteredLoggerAdapter, SyncLogger};
+///
+/// fn main() {
+///     let log = SeverityFilteredLoggerAdapter::new(SyncLogger::new(vec![]));
+///     log.filter(2);
+///
+///     if log_enabled!(log, 1) {
+///         unreachable!();
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_enabled (
+    ($log:ident, $sev:expr) => {{
+        $log.enabled($crate::Severity::as_i32(&$sev))
+    }};
+);
+
 // TODO: Docs.
 #[macro_export]
 macro_rules! log (
     ($log:ident, $sev:expr, $fmt:expr, [$($args:tt)*], {$($name:ident: $val:expr,)*}) => {{
-        $log.log(&mut $crate::Record::new($sev, line!(), module_path!(),
+        $log.log(&mut $crate::Record::new_with_file($sev, line!(), module_path!(), file!(),
             &$crate::MetaLink::new(&[
                 $($crate::Meta::new(stringify!($name), &$val)),*
             ])
@@ -1,9 +1,10 @@
 use std::fmt::Arguments;
 use std::sync::{Arc, Mutex};
 
-use {Config, Registry};
+use {Config, Filter, MetaBuf, OwnedContext, Registry};
 
 use factory::Factory;
+use filter::{FilterAction, NullFilter};
 use handle::Handle;
 use logger::Logger;
 use record::Record;
@@ -25,35 +26,101 @@ use record::Record;
 /// By reloading we mean that this logger can be safely reassigned in runtime, allowing both to
 /// change configuration and to correctly finish all outstanding operations, like flushing. This
 /// feature gives an ability to implement popular SIGHUP logging rotation.
+///
+/// Optionally a logger may contain a set of default meta attributes, like service name or
+/// version, which are prepended to every record's own attributes. Thus defaults come first while
+/// iterating, and lookups taking the last attribute with a name, like `Record::meta_map`, see the
+/// record's own attribute if it shadows a default one.
+///
+/// Records are activated lazily, i.e. right before the first handle that doesn't deny them. Thus
+/// if all handles deny a record, its message is never formatted. The same is true for the
+/// optional logger-level filter, which is checked before all handles. Since records are handled
+/// right in the caller's thread, their timestamps always reflect the event time, i.e. both
+/// `Timestamping` policies are equivalent here.
+///
+/// Messages can be limited in length to protect downstream storages from enormous records, see
+/// `Record::activate_with_limit` for details.
 #[derive(Clone)]
 pub struct SyncLogger {
+    filter: Arc<Box<Filter>>,
     handlers: Arc<Mutex<Arc<Vec<Box<Handle>>>>>,
+    defaults: Arc<OwnedContext>,
+    max_message_len: Option<usize>,
 }
 
 impl SyncLogger {
     pub fn new(handlers: Vec<Box<Handle>>) -> SyncLogger {
+        SyncLogger::with_defaults(handlers, Vec::new())
+    }
+
+    /// Constructs a logger, that prepends the given meta attributes to every record.
+    pub fn with_defaults(handlers: Vec<Box<Handle>>, defaults: Vec<MetaBuf>) -> SyncLogger {
         SyncLogger {
+            filter: Arc::new(box NullFilter),
             handlers: Arc::new(Mutex::new(Arc::new(handlers))),
+            defaults: Arc::new(OwnedContext::new(defaults)),
+            max_message_len: None,
         }
     }
 
+    /// Sets the filter, that is applied to records before their activation.
+    ///
+    /// Denied records are dropped immediately without formatting their messages and without
+    /// passing them to handles.
+    pub fn with_filter(mut self, filter: Box<Filter>) -> SyncLogger {
+        self.filter = Arc::new(filter);
+        self
+    }
+
+    /// Sets the maximum length of formatted messages in bytes, truncating longer ones.
+    ///
+    /// A truncated message is followed by a marker with the size of the dropped part, like
+    /// `…[truncated 4.0 KiB]`. The marker is not counted in the limit, so truncated messages are
+    /// slightly longer than the given length. See `Record::activate_with_limit` for details.
+    pub fn with_max_message_len(mut self, len: usize) -> SyncLogger {
+        self.max_message_len = Some(len);
+        self
+    }
+
     pub fn reset(&self, handlers: Vec<Box<Handle>>) {
         *self.handlers.lock().unwrap() = Arc::new(handlers);
     }
 }
 
-impl Logger for SyncLogger {
-    fn log<'a, 'b>(&self, rec: &mut Record<'a>, args: Arguments<'b>) {
-        // TODO: Maybe check whether a record was activated before.
-        rec.activate(args);
+impl SyncLogger {
+    fn handle(&self, rec: &mut Record, args: Arguments) {
+        let mut active = false;
 
         let handlers = self.handlers.lock().unwrap();
         for handle in handlers.iter() {
+            if let FilterAction::Deny = handle.filter(rec) {
+                continue;
+            }
+
+            if !active {
+                rec.activate_with_limit(args, self.max_message_len);
+                active = true;
+            }
+
             handle.handle(rec).unwrap();
         }
     }
 }
 
+impl Logger for SyncLogger {
+    fn log<'a, 'b>(&self, rec: &mut Record<'a>, args: Arguments<'b>) {
+        if let FilterAction::Deny = self.filter.filter(rec) {
+            return;
+        }
+
+        if self.defaults.is_empty() {
+            self.handle(rec, args);
+        } else {
+            rec.with_meta_prepended(&self.defaults.meta(), |rec| self.handle(rec, args));
+        }
+    }
+}
+
 impl Factory for SyncLogger {
     type Item = Logger;
 
@@ -68,10 +135,19 @@ impl Factory for SyncLogger {
             .ok_or("field \"handlers\" must be an array")?
             .iter()
             .map(|cfg| registry.handle(cfg))
-            .collect()?;
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut res = SyncLogger::new(handlers);
 
-        let res = box SyncLogger::new(handlers);
+        if let Some(filter) = cfg.find("filter") {
+            res = res.with_filter(registry.filter(filter)?);
+        }
+
+        if let Some(len) = cfg.find("max_message_len") {
+            let len = len.as_u64().ok_or("field \"max_message_len\" must be an integer")?;
+            res = res.with_max_message_len(len as usize);
+        }
 
-        Ok(res)
+        Ok(box res)
     }
 }
@@ -0,0 +1,65 @@
+use std::error;
+use std::fmt::Arguments;
+
+use {Config, Registry};
+
+use factory::Factory;
+use logger::Logger;
+use record::Record;
+
+/// A logger, that forwards each record to all of the given loggers in order.
+///
+/// This allows to feed several fully independent pipelines, like a fast console logger and a slow
+/// audit one, with a single call.
+///
+/// The same record is passed to each logger in order, but each of them activates it on its own,
+/// i.e. renders the message with its own length limit. Loggers, that attach extra meta attributes,
+/// like `SyncLogger` with defaults, activate a copy of the record instead, so neither these
+/// attributes nor the activation are seen by the next loggers. Thus records are identical only
+/// among loggers sharing the same configuration, while the timestamp is kept from the first
+/// activation of the shared record.
+///
+/// Note, that filters of a logger may see the record already activated by the previous ones.
+pub struct TeeLogger {
+    loggers: Vec<Box<Logger>>,
+}
+
+impl TeeLogger {
+    pub fn new(loggers: Vec<Box<Logger>>) -> TeeLogger {
+        TeeLogger {
+            loggers: loggers,
+        }
+    }
+}
+
+impl Logger for TeeLogger {
+    fn log<'a, 'b>(&self, rec: &mut Record<'a>, args: Arguments<'b>) {
+        for logger in &self.loggers {
+            logger.log(rec, args);
+        }
+    }
+
+    fn enabled(&self, sev: i32) -> bool {
+        self.loggers.iter().any(|logger| logger.enabled(sev))
+    }
+}
+
+impl Factory for TeeLogger {
+    type Item = Logger;
+
+    fn ty() -> &'static str {
+        "tee"
+    }
+
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Logger>, Box<error::Error>> {
+        let loggers = cfg.find("loggers")
+            .ok_or("section \"loggers\" is required")?
+            .as_array()
+            .ok_or("section \"loggers\" must be an array")?
+            .iter()
+            .map(|logger| registry.logger(logger))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(box TeeLogger::new(loggers))
+    }
+}
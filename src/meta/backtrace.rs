@@ -0,0 +1,70 @@
+use backtrace;
+
+use {Format, Formatter, IntoBoxedFormat};
+
+use meta::format::FormatInto;
+
+pub type Error = ::std::io::Error;
+
+/// Represents a current stack backtrace as a meta information.
+///
+/// Capturing a backtrace is an expensive operation, that's why it is performed lazily only when
+/// the meta is being formatted, i.e. only for records that have passed all filters and reached
+/// a layout. In the case of asynchronous logging the backtrace is captured eagerly during boxing,
+/// because otherwise it will reflect the stack of the worker thread.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::{Backtrace, Logger};
+/// use blacklog::logger::SyncLogger;
+///
+/// fn main() {
+///     let log = SyncLogger::new(vec![]);
+///
+///     log!(log, 0, "failed to open file", {
+///         backtrace: Backtrace,
+///     });
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Backtrace;
+
+impl Backtrace {
+    fn capture() -> String {
+        format!("{:?}", backtrace::Backtrace::new())
+    }
+}
+
+impl Format for Backtrace {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        Backtrace::capture().format(format)
+    }
+}
+
+impl IntoBoxedFormat for Backtrace {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box Backtrace::capture()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use {Format, Formatter};
+
+    use super::Backtrace;
+
+    #[test]
+    fn format() {
+        let mut buf = Vec::new();
+        Backtrace.format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        let backtrace = from_utf8(&buf[..]).unwrap();
+        assert!(!backtrace.is_empty());
+        assert!(backtrace.lines().count() > 1);
+    }
+}
@@ -0,0 +1,130 @@
+use {Format, Formatter, IntoBoxedFormat};
+
+use meta::format::FormatInto;
+
+pub type Error = ::std::io::Error;
+
+const UNITS: [&'static str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Represents a number of bytes, which is formatted in a human-readable way using IEC units.
+///
+/// Values less than 1024 are formatted as is with `B` suffix, for example `42 B`. Larger values
+/// are divided by the largest power of 1024 not exceeding them and rounded half away from zero to
+/// a single decimal place, for example `1536` gives `1.5 KiB` and `1048576` gives `1.0 MiB`. If
+/// rounding reaches 1024 the next unit is used instead, i.e. `1048575` gives `1.0 MiB` rather
+/// than `1024.0 KiB`.
+///
+/// Width, alignment and fill are applied to the whole string representation.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::{ByteSize, Logger};
+/// use blacklog::logger::SyncLogger;
+///
+/// fn main() {
+///     let log = SyncLogger::new(vec![]);
+///
+///     log!(log, 0, "file uploaded", {
+///         size: ByteSize(1536),
+///     });
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    fn to_string(&self) -> String {
+        if self.0 < 1024 {
+            return format!("{} B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+
+        while unit + 1 < UNITS.len() && (value * 10.0).round() >= 1024.0 * 10.0 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        format!("{:.1} {}", (value * 10.0).round() / 10.0, UNITS[unit])
+    }
+}
+
+impl Format for ByteSize {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.to_string().format(format)
+    }
+}
+
+impl IntoBoxedFormat for ByteSize {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use {Format, Formatter};
+    use meta::format::FormatSpec;
+
+    use super::ByteSize;
+
+    fn format(value: u64) -> String {
+        let mut buf = Vec::new();
+        ByteSize(value).format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        from_utf8(&buf[..]).unwrap().into()
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!("0 B", format(0));
+        assert_eq!("42 B", format(42));
+        assert_eq!("1023 B", format(1023));
+    }
+
+    #[test]
+    fn kib() {
+        assert_eq!("1.0 KiB", format(1024));
+        assert_eq!("1.5 KiB", format(1536));
+        assert_eq!("1023.0 KiB", format(1023 * 1024));
+    }
+
+    #[test]
+    fn mib() {
+        assert_eq!("1.0 MiB", format(1024 * 1024));
+        assert_eq!("2.5 MiB", format(5 * 512 * 1024));
+    }
+
+    #[test]
+    fn power_of_two_boundary() {
+        assert_eq!("1.0 GiB", format(1 << 30));
+        assert_eq!("1023.0 MiB", format((1 << 30) - (1 << 20)));
+    }
+
+    #[test]
+    fn rounding_promotes_to_next_unit() {
+        assert_eq!("1.0 MiB", format(1024 * 1024 - 1));
+    }
+
+    #[test]
+    fn largest_unit() {
+        assert_eq!("16.0 EiB", format(::std::u64::MAX));
+    }
+
+    #[test]
+    fn width() {
+        let mut spec = FormatSpec::default();
+        spec.width = 10;
+
+        let mut buf = Vec::new();
+        ByteSize(1536).format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!("1.5 KiB   ", from_utf8(&buf[..]).unwrap());
+    }
+}
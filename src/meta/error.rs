@@ -0,0 +1,250 @@
+use std::error;
+use std::io::{self, ErrorKind};
+
+use {Format, Formatter, IntoBoxedFormat};
+
+use meta::format::FormatInto;
+
+pub type Error = ::std::io::Error;
+
+/// Represents an error with its entire cause chain as a meta information.
+///
+/// The error is formatted using its `Display` implementation followed by all of its causes, each
+/// prepended with `: caused by `. Since errors are neither `'static` nor clonable, the chain is
+/// rendered eagerly into a string during boxing.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use std::io;
+///
+/// use blacklog::{ErrorMeta, Logger};
+/// use blacklog::logger::SyncLogger;
+///
+/// fn main() {
+///     let log = SyncLogger::new(vec![]);
+///     let err = io::Error::new(io::ErrorKind::NotFound, "no such file or directory");
+///
+///     log!(log, 0, "failed to open file", {
+///         error: ErrorMeta(&err),
+///     });
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct ErrorMeta<'a>(pub &'a (error::Error + Send + Sync + 'a));
+
+impl<'a> ErrorMeta<'a> {
+    fn chain(&self) -> String {
+        let mut result = self.0.to_string();
+
+        let mut cause = self.0.cause();
+        while let Some(err) = cause {
+            result.push_str(": caused by ");
+            result.push_str(&err.to_string());
+            cause = err.cause();
+        }
+
+        result
+    }
+}
+
+impl<'a> Format for ErrorMeta<'a> {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.chain().format(format)
+    }
+}
+
+impl<'a> IntoBoxedFormat for ErrorMeta<'a> {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box self.chain()
+    }
+}
+
+/// Represents an I/O error as a meta information, giving an access to its kind.
+///
+/// The error itself is formatted using its `Display` implementation, while the kind is available
+/// as a separate meta value using `kind()` method, for example to be able to filter records by
+/// `NotFound` or `PermissionDenied` kinds. Since `io::Error` is not clonable, the message is
+/// rendered eagerly into a string during boxing.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use std::io;
+///
+/// use blacklog::{IoErrorMeta, Logger};
+/// use blacklog::logger::SyncLogger;
+///
+/// fn main() {
+///     let log = SyncLogger::new(vec![]);
+///     let err = io::Error::new(io::ErrorKind::PermissionDenied, "permission denied");
+///
+///     log!(log, 4, "failed to open file", {
+///         error: IoErrorMeta(&err),
+///         error_kind: IoErrorMeta(&err).kind(),
+///     });
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct IoErrorMeta<'a>(pub &'a io::Error);
+
+impl<'a> IoErrorMeta<'a> {
+    /// Returns the kind of the wrapped error as a meta value.
+    pub fn kind(&self) -> IoErrorKind {
+        IoErrorKind(self.0.kind())
+    }
+}
+
+impl<'a> Format for IoErrorMeta<'a> {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.0.to_string().format(format)
+    }
+}
+
+impl<'a> IntoBoxedFormat for IoErrorMeta<'a> {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box self.0.to_string()
+    }
+}
+
+/// Represents a kind of an I/O error as a meta information, which is formatted as its name, i.e.
+/// `NotFound`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IoErrorKind(pub ErrorKind);
+
+impl Format for IoErrorKind {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        format!("{:?}", self.0).format(format)
+    }
+}
+
+impl IntoBoxedFormat for IoErrorKind {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error;
+    use std::fmt::{self, Display};
+    use std::io::{self, ErrorKind};
+    use std::str::from_utf8;
+
+    use serde_json::{self, Value};
+
+    use {Format, Formatter, Layout, Meta, MetaLink, Record};
+    use layout::JsonLayout;
+
+    use super::{ErrorMeta, IoErrorMeta};
+
+    #[derive(Debug)]
+    struct ConfigError {
+        cause: io::Error,
+    }
+
+    impl Display for ConfigError {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+            write!(fmt, "failed to read config")
+        }
+    }
+
+    impl error::Error for ConfigError {
+        fn description(&self) -> &str {
+            "failed to read config"
+        }
+
+        fn cause(&self) -> Option<&error::Error> {
+            Some(&self.cause)
+        }
+    }
+
+    #[test]
+    fn format() {
+        let err = io::Error::new(ErrorKind::NotFound, "no such file or directory");
+
+        let mut buf = Vec::new();
+        ErrorMeta(&err).format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("no such file or directory", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_cause_chain() {
+        let err = ConfigError {
+            cause: io::Error::new(ErrorKind::NotFound, "no such file or directory"),
+        };
+
+        let mut buf = Vec::new();
+        ErrorMeta(&err).format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("failed to read config: caused by no such file or directory",
+            from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn to_boxed_format_cause_chain() {
+        use IntoBoxedFormat;
+
+        let err = ConfigError {
+            cause: io::Error::new(ErrorKind::NotFound, "no such file or directory"),
+        };
+
+        let boxed = ErrorMeta(&err).to_boxed_format();
+
+        let mut buf = Vec::new();
+        boxed.format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("failed to read config: caused by no such file or directory",
+            from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_io_error_kind() {
+        let err = io::Error::new(ErrorKind::PermissionDenied, "permission denied");
+
+        let mut buf = Vec::new();
+        IoErrorMeta(&err).kind().format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("PermissionDenied", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_io_error_with_kind_as_json() {
+        let err = io::Error::new(ErrorKind::PermissionDenied, "permission denied");
+        let kind = IoErrorMeta(&err).kind();
+
+        let meta = [
+            Meta::new("error", &IoErrorMeta(&err)),
+            Meta::new("error.kind", &kind),
+        ];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(4, 0, "", &metalink);
+        rec.activate(format_args!("failed to open file"));
+
+        let mut buf = Vec::new();
+        JsonLayout::new().format(&rec, &mut buf).unwrap();
+
+        let value: Value = serde_json::from_str(from_utf8(&buf[..]).unwrap()).unwrap();
+        assert_eq!(Some("permission denied"), value.find("error").and_then(|v| v.as_string()));
+        assert_eq!(Some("PermissionDenied"), value.find("error.kind").and_then(|v| v.as_string()));
+    }
+
+    #[test]
+    fn to_boxed_format_io_error() {
+        use IntoBoxedFormat;
+
+        let err = io::Error::new(ErrorKind::NotFound, "no such file or directory");
+        let boxed = IoErrorMeta(&err).to_boxed_format();
+        drop(err);
+
+        let mut buf = Vec::new();
+        boxed.format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("no such file or directory", from_utf8(&buf[..]).unwrap());
+    }
+}
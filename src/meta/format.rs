@@ -7,7 +7,10 @@
 //! own types.
 
 use std::borrow::Cow;
+use std::cmp;
 use std::io::{Cursor, Write};
+use std::str;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicIsize, AtomicU64, AtomicUsize, Ordering};
 
 pub type Error = ::std::io::Error;
 
@@ -106,7 +109,7 @@ impl<'a> Formatter<'a> {
                 match self.width() {
                     0 => self.wr.write_all(data.as_bytes()),
                     width => {
-                        let pad = width.saturating_sub(data.len());
+                        let pad = width.saturating_sub(data.chars().count());
                         self.with_pad(pad, Alignment::AlignLeft, |format| {
                             format.write_all(data.as_bytes())
                         })
@@ -114,13 +117,9 @@ impl<'a> Formatter<'a> {
                 }
             }
             Some(prec) => {
-                let data = if prec < data.len() {
-                    &data[..prec]
-                } else {
-                    &data
-                };
+                let data = truncate(data, prec);
 
-                let pad = self.width().saturating_sub(data.len());
+                let pad = self.width().saturating_sub(data.chars().count());
                 self.with_pad(pad, Alignment::AlignLeft, |format| {
                     format.write_all(data.as_bytes())
                 })
@@ -128,6 +127,33 @@ impl<'a> Formatter<'a> {
         }
     }
 
+    /// Emits the given string slice wrapped in double quotes, escaping interior quotes and
+    /// backslashes with a backslash.
+    ///
+    /// Precision truncates the original string, while width and alignment are applied to the
+    /// quoted one, i.e. padding is written outside of quotes.
+    pub fn write_quoted(&mut self, data: &str) -> Result<(), Error> {
+        let data = match *self.precision() {
+            Some(prec) => truncate(data, prec),
+            None => data,
+        };
+
+        let mut quoted = String::with_capacity(data.len() + 2);
+        quoted.push('"');
+        for ch in data.chars() {
+            if ch == '"' || ch == '\\' {
+                quoted.push('\\');
+            }
+            quoted.push(ch);
+        }
+        quoted.push('"');
+
+        let pad = self.width().saturating_sub(quoted.chars().count());
+        self.with_pad(pad, Alignment::AlignLeft, |format| {
+            format.write_all(quoted.as_bytes())
+        })
+    }
+
     pub fn fill(&self) -> char {
         self.spec.fill
     }
@@ -160,6 +186,33 @@ impl<'a> Formatter<'a> {
         self.spec.flags & (1 << 2) != 0
     }
 
+    /// Writes a number, consisting of the given sign, prefix and digits, padding it according to
+    /// the spec.
+    ///
+    /// Like `std::fmt`, the sign and the prefix are padded together with digits, unless the `0`
+    /// flag is set, in which case they are written before zeroes. Zero padding overrides both the
+    /// fill character and the alignment given in the spec.
+    fn pad_integral(&mut self, sign: &[u8], prefix: &[u8], digits: &[u8]) -> Result<(), Error> {
+        let pad = self.spec.width.saturating_sub(sign.len() + prefix.len() + digits.len());
+
+        if self.sign_aware_zero_pad() {
+            self.write_all(sign)?;
+            self.write_all(prefix)?;
+            self.spec.fill = '0';
+            self.spec.align = Alignment::AlignRight;
+
+            self.with_pad(pad, Alignment::AlignRight, |format| {
+                format.write_all(digits)
+            })
+        } else {
+            self.with_pad(pad, Alignment::AlignRight, |format| {
+                format.write_all(sign)?;
+                format.write_all(prefix)?;
+                format.write_all(digits)
+            })
+        }
+    }
+
     fn with_pad<F>(&mut self, pad: usize, align: Alignment, f: F) -> Result<(), Error>
         where F: FnOnce(&mut Formatter) -> Result<(), Error>
     {
@@ -196,6 +249,17 @@ impl<'a> Formatter<'a> {
     }
 }
 
+/// Returns the longest prefix of the given string with at most `prec` characters.
+///
+/// Like `std::fmt`, precision counts characters rather than bytes, so the string is never cut
+/// inside of a multibyte code point.
+fn truncate(data: &str, prec: usize) -> &str {
+    match data.char_indices().nth(prec) {
+        Some((idx, ..)) => &data[..idx],
+        None => data,
+    }
+}
+
 /// Represents a formattable entity.
 ///
 /// Every meta information type that wishes to be printed into layout should implement this trait.
@@ -257,8 +321,8 @@ impl Format for i64 {
         let charset = charset.as_bytes();
 
         // Calculate width and do a simple formatting into a fixed-size buffer.
-        let mut val = *self;
         let mut buf = ['0' as u8; 1 + 2 + 64];
+        let mut val = *self;
         let mut pos = buf.len();
         for c in buf.iter_mut().rev() {
             *c = charset[(val % base).abs() as usize];
@@ -270,29 +334,17 @@ impl Format for i64 {
             }
         }
 
-        let buf = &buf[pos..];
-        let mut pad = format.spec.width.saturating_sub(buf.len());
-
-        if *self < 0 {
-            format.write_all("-".as_bytes())?;
-            pad = pad.saturating_sub(1);
+        let sign = if *self < 0 {
+            "-"
         } else if format.sign_plus() {
-            format.write_all("+".as_bytes())?;
-            pad = pad.saturating_sub(1);
-        }
-
-        if format.alternate() {
-            format.write_all(prefix)?;
-            pad = pad.saturating_sub(prefix.len());
-        }
+            "+"
+        } else {
+            ""
+        };
 
-        if format.sign_aware_zero_pad() {
-            format.spec.fill = '0';
-        }
+        let prefix = if format.alternate() { prefix } else { &[] };
 
-        format.with_pad(pad, Alignment::AlignRight, |format| {
-            format.write_all(buf)
-        })
+        format.pad_integral(sign.as_bytes(), prefix, &buf[pos..])
     }
 }
 
@@ -337,8 +389,8 @@ impl Format for u64 {
         let charset = charset.as_bytes();
 
         // Calculate width and do a simple formatting into a fixed-size buffer.
-        let mut val = *self;
         let mut buf = ['0' as u8; 1 + 2 + 64];
+        let mut val = *self;
         let mut pos = buf.len();
         for c in buf.iter_mut().rev() {
             *c = charset[(val % base) as usize];
@@ -350,26 +402,43 @@ impl Format for u64 {
             }
         }
 
-        let buf = &buf[pos..];
-        let mut pad = format.spec.width.saturating_sub(buf.len());
+        let sign = if format.sign_plus() { "+" } else { "" };
+        let prefix = if format.alternate() { prefix } else { &[] };
 
-        if format.sign_plus() {
-            format.write_all("+".as_bytes())?;
-            pad = pad.saturating_sub(1);
-        }
+        format.pad_integral(sign.as_bytes(), prefix, &buf[pos..])
+    }
+}
 
-        if format.alternate() {
-            format.write_all(prefix)?;
-            pad = pad.saturating_sub(prefix.len());
-        }
+// Atomics are formatted as a snapshot of their current value. There is no need in a stronger
+// ordering, because logging never synchronizes anything.
 
-        if format.sign_aware_zero_pad() {
-            format.spec.fill = '0';
-        }
+impl Format for AtomicBool {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.load(Ordering::Relaxed).format(format)
+    }
+}
 
-        format.with_pad(pad, Alignment::AlignRight, |format| {
-            format.write_all(buf)
-        })
+impl Format for AtomicIsize {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.load(Ordering::Relaxed).format(format)
+    }
+}
+
+impl Format for AtomicUsize {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.load(Ordering::Relaxed).format(format)
+    }
+}
+
+impl Format for AtomicI64 {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.load(Ordering::Relaxed).format(format)
+    }
+}
+
+impl Format for AtomicU64 {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        self.load(Ordering::Relaxed).format(format)
     }
 }
 
@@ -388,57 +457,151 @@ impl Format for f64 {
             (Some('E'), Some(prec)) => write!(&mut cur, "{:.*E}", prec, *self)?,
             (Some('e'), None) => write!(&mut cur, "{:e}", *self)?,
             (Some('E'), None) => write!(&mut cur, "{:E}", *self)?,
+            (Some('g'), prec) => write_general(&mut cur, *self, prec.unwrap_or(6), false)?,
+            (Some('G'), prec) => write_general(&mut cur, *self, prec.unwrap_or(6), true)?,
             (_, Some(prec)) => write!(&mut cur, "{:.*}", prec, *self)?,
             (_, None) => write!(&mut cur, "{}", *self)?,
         }
         let pos = cur.position() as usize;
+        let buf = cur.into_inner();
+        let buf = &buf[..pos];
 
-        let mut pad = format.spec.width.saturating_sub(pos);
+        // The minus sign is already rendered, split it out to pad properly.
+        let (sign, buf) = if buf.starts_with(b"-") {
+            ("-", &buf[1..])
+        } else if format.sign_plus() {
+            ("+", buf)
+        } else {
+            ("", buf)
+        };
 
-        if format.sign_plus() {
-            if *self < 0.0 {
-                format.write_all("-".as_bytes())?;
-            } else {
-                format.write_all("+".as_bytes())?;
-            }
+        format.pad_integral(sign.as_bytes(), &[], buf)
+    }
+}
 
-            pad = pad.saturating_sub(1);
-        }
+/// Writes the given float using either fixed or scientific notation, like C's `%g` does.
+///
+/// The precision is the number of significant digits, zero is treated as one. Having the decimal
+/// exponent X of the value rounded to that precision P, the scientific notation is used when
+/// X < -4 or X >= P, otherwise the value is written in fixed notation with P - 1 - X digits after
+/// the decimal point. Trailing zeros are removed from the fractional part in both cases.
+///
+/// Unlike C the exponent is written as is, without the sign and padding, i.e. `1.5e-5` or `1.5e8`
+/// to be consistent with `e` type.
+fn write_general<W: Write>(wr: &mut W, val: f64, prec: usize, upper: bool) -> Result<(), Error> {
+    if !val.is_finite() {
+        return write!(wr, "{}", val);
+    }
 
-        if format.sign_aware_zero_pad() {
-            format.spec.fill = '0';
-        }
+    let prec = cmp::max(prec, 1);
 
-        format.with_pad(pad, Alignment::AlignRight, |format| {
-            format.write_all(&cur.into_inner()[..pos])
-        })
+    let mut buf = [0; 128];
+    let mut cur = Cursor::new(&mut buf[..]);
+    write!(&mut cur, "{:.*e}", prec - 1, val)?;
+    let pos = cur.position() as usize;
+    let buf = &cur.into_inner()[..pos];
+
+    let e = buf.iter().position(|&c| c == b'e').unwrap();
+    let exp: i32 = str::from_utf8(&buf[e + 1..]).unwrap().parse().unwrap();
+
+    if exp < -4 || exp >= prec as i32 {
+        wr.write_all(trim_zeros(&buf[..e]))?;
+        wr.write_all(if upper { b"E" } else { b"e" })?;
+        wr.write_all(&buf[e + 1..])
+    } else {
+        let mut buf = [0; 128];
+        let mut cur = Cursor::new(&mut buf[..]);
+        write!(&mut cur, "{:.*}", (prec as i32 - 1 - exp) as usize, val)?;
+        let pos = cur.position() as usize;
+
+        wr.write_all(trim_zeros(&cur.into_inner()[..pos]))
+    }
+}
+
+/// Removes trailing zeros from the fractional part of the given number, including the decimal
+/// point if nothing remains after it.
+fn trim_zeros(buf: &[u8]) -> &[u8] {
+    if !buf.contains(&b'.') {
+        return buf;
+    }
+
+    let buf = &buf[..buf.iter().rposition(|&c| c != b'0').unwrap() + 1];
+
+    if buf.ends_with(b".") {
+        &buf[..buf.len() - 1]
+    } else {
+        buf
+    }
+}
+
+/// Formats a string either as is or quoted if the `q` type is specified.
+fn format_str(data: &str, format: &mut Formatter) -> Result<(), Error> {
+    match format.spec.ty {
+        Some('q') => format.write_quoted(data),
+        Some(..) | None => format.write_str(data),
     }
 }
 
 impl Format for str {
     fn format(&self, format: &mut Formatter) -> Result<(), Error> {
-        format.write_str(self)
+        format_str(self, format)
     }
 }
 
 impl Format for &'static str {
     fn format(&self, format: &mut Formatter) -> Result<(), Error> {
-        format.write_str(self)
+        format_str(self, format)
     }
 }
 
 impl Format for String {
     fn format(&self, format: &mut Formatter) -> Result<(), Error> {
-        format.write_str(&self[..])
+        format_str(&self[..], format)
     }
 }
 
 impl<'a> Format for Cow<'a, str> {
     fn format(&self, format: &mut Formatter) -> Result<(), Error> {
-        format.write_str(self)
+        format_str(self, format)
     }
 }
 
+macro_rules! tuple_format {
+    ($head:ident: $hidx:tt $(, $name:ident: $idx:tt)+) => {
+        /// Formats a tuple as `(a, b, ...)` with each element formatted using the default spec,
+        /// while the given spec is applied to the whole result.
+        impl<$head: Format, $($name: Format),+> Format for ($head, $($name,)+) {
+            fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+                let mut buf = Vec::new();
+                buf.write_all(b"(")?;
+                self.$hidx.format(&mut Formatter::new(&mut buf, Default::default()))?;
+                $(
+                    buf.write_all(b", ")?;
+                    self.$idx.format(&mut Formatter::new(&mut buf, Default::default()))?;
+                )+
+                buf.write_all(b")")?;
+
+                match str::from_utf8(&buf) {
+                    Ok(buf) => format.write_str(buf),
+                    Err(..) => format.write_all(&buf),
+                }
+            }
+        }
+
+        impl<$head, $($name),+> IntoBoxedFormat for ($head, $($name,)+)
+            where $head: IntoBoxedFormat + Clone + 'static,
+                  $($name: IntoBoxedFormat + Clone + 'static),+
+        {
+            fn to_boxed_format(&self) -> Box<FormatInto> {
+                box self.clone()
+            }
+        }
+    }
+}
+
+tuple_format!(A: 0, B: 1);
+tuple_format!(A: 0, B: 1, C: 2);
+
 pub trait FormatInto: Format + IntoBoxedFormat {}
 
 impl<T: Format + IntoBoxedFormat> FormatInto for T {}
@@ -528,6 +691,36 @@ impl IntoBoxedFormat for f64 {
     }
 }
 
+impl IntoBoxedFormat for AtomicBool {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box self.load(Ordering::Relaxed)
+    }
+}
+
+impl IntoBoxedFormat for AtomicIsize {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box self.load(Ordering::Relaxed)
+    }
+}
+
+impl IntoBoxedFormat for AtomicUsize {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box self.load(Ordering::Relaxed)
+    }
+}
+
+impl IntoBoxedFormat for AtomicI64 {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box self.load(Ordering::Relaxed)
+    }
+}
+
+impl IntoBoxedFormat for AtomicU64 {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        box self.load(Ordering::Relaxed)
+    }
+}
+
 impl IntoBoxedFormat for &'static str {
     fn to_boxed_format(&self) -> Box<FormatInto> {
         box Cow::Borrowed(*self)
@@ -564,6 +757,62 @@ mod tests {
         assert_eq!(None, spec.ty);
     }
 
+    #[test]
+    fn format_tuple2() {
+        let mut buf = Vec::new();
+        (1, "two").format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("(1, two)", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_tuple3_with_spec() {
+        let spec = FormatSpec {
+            fill: '.',
+            align: Alignment::AlignRight,
+            width: 16,
+            .. FormatSpec::default()
+        };
+
+        let mut buf = Vec::new();
+        (1, 2.5, true).format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!("..(1, 2.5, true)", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_tuple_boxed() {
+        let boxed = (1, "two").to_boxed_format();
+
+        let mut buf = Vec::new();
+        boxed.format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("(1, two)", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_atomic() {
+        let counter = AtomicU64::new(41);
+        counter.fetch_add(1, Ordering::SeqCst);
+
+        let mut buf = Vec::new();
+        counter.format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("42", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_atomic_boxed_is_snapshot() {
+        let counter = AtomicUsize::new(42);
+        let boxed = counter.to_boxed_format();
+        counter.fetch_add(1, Ordering::SeqCst);
+
+        let mut buf = Vec::new();
+        boxed.format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+
+        assert_eq!("42", from_utf8(&buf[..]).unwrap());
+    }
+
     #[test]
     fn format_i64() {
         let spec = FormatSpec::default();
@@ -586,6 +835,38 @@ mod tests {
         assert_eq!("-42", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn format_i64_zero() {
+        let spec = FormatSpec::default();
+
+        let mut buf = Vec::new();
+        0i64.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!("0", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_i64_max() {
+        let spec = FormatSpec::default();
+
+        let mut buf = Vec::new();
+        let val = 9223372036854775807i64;
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!("9223372036854775807", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_i64_min() {
+        let spec = FormatSpec::default();
+
+        let mut buf = Vec::new();
+        let val = -9223372036854775808i64;
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!("-9223372036854775808", from_utf8(&buf[..]).unwrap());
+    }
+
     #[test]
     fn format_i64_max_bin() {
         let mut spec = FormatSpec::default();
@@ -668,6 +949,33 @@ mod tests {
         assert_eq!("+0x2a00000", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn format_i64_alternate_upper_hex() {
+        let mut spec = FormatSpec::default();
+        spec.flags = 0b010;
+        spec.ty = Some('X');
+
+        let mut buf = Vec::new();
+        let val = -42i64;
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!("-0x2A", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_u64_alternate_upper_hex() {
+        let mut spec = FormatSpec::default();
+        spec.flags = 0b010;
+        spec.ty = Some('X');
+
+        let mut buf = Vec::new();
+        let val = 42u64;
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!(format!("{:#X}", 42u64), from_utf8(&buf[..]).unwrap());
+        assert_eq!("0x2A", from_utf8(&buf[..]).unwrap());
+    }
+
     #[test]
     fn format_i32() {
         let spec = FormatSpec::default();
@@ -679,6 +987,32 @@ mod tests {
         assert_eq!("42", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn format_u64_max() {
+        let spec = FormatSpec::default();
+
+        let mut buf = Vec::new();
+        let val = 18446744073709551615u64;
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!("18446744073709551615", from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_u64_every_digit_count() {
+        let mut val = 1u64;
+        for _ in 0..20 {
+            for &v in &[val - 1, val, val + 9] {
+                let mut buf = Vec::new();
+                v.format(&mut Formatter::new(&mut buf, FormatSpec::default())).unwrap();
+
+                assert_eq!(format!("{}", v), from_utf8(&buf[..]).unwrap());
+            }
+
+            val = val.wrapping_mul(10);
+        }
+    }
+
     #[test]
     fn format_u64_max_bin() {
         let mut spec = FormatSpec::default();
@@ -770,6 +1104,51 @@ mod tests {
         assert_eq!("1.0050E5", from_utf8(&buf[..]).unwrap());
     }
 
+    fn format_general(val: f64, ty: char, precision: Option<usize>) -> String {
+        let mut spec = FormatSpec::default();
+        spec.precision = precision;
+        spec.ty = Some(ty);
+
+        let mut buf = Vec::new();
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn format_f64_general_small() {
+        assert_eq!("0.0001234", format_general(0.0001234, 'g', None));
+        assert_eq!("1.234e-5", format_general(0.00001234, 'g', None));
+        assert_eq!("1.234E-5", format_general(0.00001234, 'G', None));
+        assert_eq!("0.5", format_general(0.5, 'g', None));
+        assert_eq!("-0.25", format_general(-0.25, 'g', None));
+        assert_eq!("0", format_general(0.0, 'g', None));
+    }
+
+    #[test]
+    fn format_f64_general_large() {
+        assert_eq!("100500", format_general(100500.0, 'g', None));
+        assert_eq!("123457", format_general(123456.7, 'g', None));
+        assert_eq!("1.23457e6", format_general(1234567.0, 'g', None));
+        assert_eq!("1e6", format_general(1000000.0, 'g', None));
+        assert_eq!("1.23457E8", format_general(123456789.0, 'G', None));
+    }
+
+    #[test]
+    fn format_f64_general_with_precision() {
+        assert_eq!("3.14", format_general(3.1415, 'g', Some(3)));
+        assert_eq!("3", format_general(3.1415, 'g', Some(0)));
+        assert_eq!("1e2", format_general(99.9, 'g', Some(2)));
+        assert_eq!("99.9", format_general(99.9, 'g', Some(3)));
+        assert_eq!("1e5", format_general(100500.0, 'g', Some(3)));
+    }
+
+    #[test]
+    fn format_f64_general_non_finite() {
+        assert_eq!("inf", format_general(::std::f64::INFINITY, 'g', None));
+        assert_eq!("NaN", format_general(::std::f64::NAN, 'g', None));
+    }
+
     #[test]
     fn format_f32_spec() {
         let mut spec = FormatSpec::default();
@@ -782,6 +1161,111 @@ mod tests {
         assert_eq!("3.14", from_utf8(&buf[..]).unwrap());
     }
 
+    fn format_with<T: Format>(val: T, align: Alignment, flags: u32, width: usize) -> String {
+        let mut spec = FormatSpec::default();
+        spec.align = align;
+        spec.flags = flags;
+        spec.width = width;
+
+        let mut buf = Vec::new();
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn format_quoted(val: &'static str, spec: FormatSpec) -> String {
+        let mut buf = Vec::new();
+        val.format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn format_str_quoted() {
+        let mut spec = FormatSpec::default();
+        spec.ty = Some('q');
+
+        assert_eq!(r#""he said \"hi\"""#, format_quoted(r#"he said "hi""#, spec));
+        assert_eq!(r#""C:\\temp""#, format_quoted(r#"C:\temp"#, spec));
+        assert_eq!(r#""""#, format_quoted("", spec));
+    }
+
+    #[test]
+    fn format_str_quoted_with_width() {
+        let mut spec = FormatSpec::default();
+        spec.align = Alignment::AlignRight;
+        spec.width = 8;
+        spec.ty = Some('q');
+
+        assert_eq!(r#"  "a\"b""#, format_quoted(r#"a"b"#, spec));
+    }
+
+    #[test]
+    fn format_string_quoted_with_precision() {
+        let mut spec = FormatSpec::default();
+        spec.precision = Some(2);
+        spec.ty = Some('q');
+
+        let mut buf = Vec::new();
+        String::from("value").format(&mut Formatter::new(&mut buf, spec)).unwrap();
+
+        assert_eq!(r#""va""#, from_utf8(&buf[..]).unwrap());
+    }
+
+    #[test]
+    fn format_str_quoted_multibyte() {
+        let mut spec = FormatSpec::default();
+        spec.align = Alignment::AlignRight;
+        spec.width = 6;
+        spec.precision = Some(2);
+        spec.ty = Some('q');
+
+        assert_eq!(r#"  "日本""#, format_quoted("日本語", spec));
+    }
+
+    #[test]
+    fn format_str_center_matches_std() {
+        // Odd leftover padding goes to the right.
+        assert_eq!(format!("{:^5}", "ab"), format_with("ab", Alignment::AlignCenter, 0, 5));
+        assert_eq!(format!("{:^6}", "ab"), format_with("ab", Alignment::AlignCenter, 0, 6));
+        assert_eq!(format!("{:^6}", "abc"), format_with("abc", Alignment::AlignCenter, 0, 6));
+        assert_eq!(format!("{:^2}", "abc"), format_with("abc", Alignment::AlignCenter, 0, 2));
+    }
+
+    #[test]
+    fn format_int_center_matches_std() {
+        assert_eq!(format!("{:^5}", 42), format_with(42i64, Alignment::AlignCenter, 0, 5));
+        assert_eq!(format!("{:^6}", 42), format_with(42i64, Alignment::AlignCenter, 0, 6));
+        assert_eq!(format!("{:^5}", 42u64), format_with(42u64, Alignment::AlignCenter, 0, 5));
+        assert_eq!(format!("{:^6}", -5), format_with(-5i64, Alignment::AlignCenter, 0, 6));
+        assert_eq!(format!("{:^7}", -5), format_with(-5i64, Alignment::AlignCenter, 0, 7));
+        assert_eq!(format!("{:^+6}", 5), format_with(5i64, Alignment::AlignCenter, 0b001, 6));
+        assert_eq!(format!("{:^+7}", 5u64), format_with(5u64, Alignment::AlignCenter, 0b001, 7));
+    }
+
+    #[test]
+    fn format_int_sign_is_padded_with_digits() {
+        assert_eq!(format!("{:5}", -5), format_with(-5i64, Alignment::AlignUnknown, 0, 5));
+        assert_eq!(format!("{:<5}", -5), format_with(-5i64, Alignment::AlignLeft, 0, 5));
+        assert_eq!(format!("{:05}", -5), format_with(-5i64, Alignment::AlignUnknown, 0b100, 5));
+        assert_eq!(format!("{:+5}", 5u64), format_with(5u64, Alignment::AlignUnknown, 0b001, 5));
+    }
+
+    #[test]
+    fn format_zero_pad_ignores_align_matches_std() {
+        assert_eq!(format!("{:<08}", -42), format_with(-42i64, Alignment::AlignLeft, 0b100, 8));
+        assert_eq!(format!("{:^08}", 42), format_with(42u64, Alignment::AlignCenter, 0b100, 8));
+        assert_eq!(format!("{:<+08}", 1.5), format_with(1.5f64, Alignment::AlignLeft, 0b101, 8));
+    }
+
+    #[test]
+    fn format_f64_center_matches_std() {
+        assert_eq!(format!("{:^7}", 1.5), format_with(1.5f64, Alignment::AlignCenter, 0, 7));
+        assert_eq!(format!("{:^8}", -1.5), format_with(-1.5f64, Alignment::AlignCenter, 0, 8));
+        assert_eq!(format!("{:^+8}", 1.5), format_with(1.5f64, Alignment::AlignCenter, 0b001, 8));
+        assert_eq!(format!("{:08}", -1.5), format_with(-1.5f64, Alignment::AlignUnknown, 0b100, 8));
+    }
+
     #[test]
     fn format_str() {
         let spec = FormatSpec::default();
@@ -823,6 +1307,22 @@ mod tests {
         assert_eq!("/le messa/", from_utf8(&buf[..]).unwrap());
     }
 
+    #[test]
+    fn format_str_multibyte_with_precision() {
+        let mut spec = FormatSpec::default();
+        spec.precision = Some(3);
+
+        assert_eq!("héé", format_quoted("héééllo", spec));
+        assert_eq!("日本語", format_quoted("日本語です", spec));
+        assert_eq!("é", format_quoted("é", spec));
+    }
+
+    #[test]
+    fn format_str_multibyte_width_matches_std() {
+        assert_eq!(format!("{:<6}", "日本"), format_with("日本", Alignment::AlignLeft, 0, 6));
+        assert_eq!(format!("{:^6}", "éé"), format_with("éé", Alignment::AlignCenter, 0, 6));
+    }
+
     #[test]
     fn format_bool() {
         let spec = FormatSpec::default();
@@ -858,6 +1358,22 @@ mod bench {
         });
     }
 
+    #[bench]
+    fn bench_format_i64_max(b: &mut Bencher) {
+        let spec = FormatSpec::default();
+
+        let mut buf = Vec::with_capacity(64);
+
+        b.iter(|| {
+            {
+                let mut format = Formatter::new(&mut buf, spec);
+                let val = 9223372036854775807i64;
+                val.format(&mut format).unwrap();
+            }
+            buf.clear();
+        });
+    }
+
     #[bench]
     fn bench_format_i64_spec(b: &mut Bencher) {
         let spec = FormatSpec {
@@ -3,9 +3,15 @@ use std::slice::Iter;
 
 use self::format::FormatInto;
 
+pub use self::backtrace::Backtrace;
+pub use self::bytesize::ByteSize;
+pub use self::error::{ErrorMeta, IoErrorKind, IoErrorMeta};
 pub use self::format::Error;
 pub use self::func::FnMeta;
 
+mod backtrace;
+mod bytesize;
+mod error;
 pub mod format;
 mod func;
 
@@ -185,7 +191,8 @@ pub struct MetaBuf {
 }
 
 impl MetaBuf {
-    fn new(name: &'static str, value: Box<FormatInto>) -> MetaBuf {
+    /// Constructs a new owned meta with the given name and boxed value.
+    pub fn new(name: &'static str, value: Box<FormatInto>) -> MetaBuf {
         MetaBuf {
             name: name,
             value: value,
@@ -202,6 +209,81 @@ impl<'a> Into<Meta<'a>> for &'a MetaBuf {
     }
 }
 
+/// Owned set of meta attributes, that can be cheaply linked with borrowed ones.
+///
+/// Since `MetaLink` borrows its attributes, owned attributes must be converted into a borrowed
+/// representation, which lives no longer than the owner, before chaining. This struct takes care
+/// of it, allowing to keep long-living context, like in child loggers, and to attach it to records
+/// on demand.
+///
+/// # Examples
+///
+/// ```
+/// use blacklog::{Meta, MetaBuf, MetaLink, OwnedContext};
+///
+/// let context = OwnedContext::new(vec![MetaBuf::new("service", Box::new("api"))]);
+///
+/// let val = 42;
+/// let meta = [Meta::new("id", &val)];
+///
+/// context.link(None, |link| {
+///     let metalink = MetaLink::with_link(&meta, link);
+///
+///     let mut iter = metalink.iter();
+///     assert_eq!("service", iter.next().unwrap().name);
+///     assert_eq!("id", iter.next().unwrap().name);
+///     assert!(iter.next().is_none());
+/// });
+/// ```
+pub struct OwnedContext {
+    meta: Vec<MetaBuf>,
+}
+
+impl OwnedContext {
+    /// Constructs a new context with the given owned attributes.
+    pub fn new(meta: Vec<MetaBuf>) -> OwnedContext {
+        OwnedContext {
+            meta: meta,
+        }
+    }
+
+    /// Appends an attribute to this context.
+    pub fn push(&mut self, meta: MetaBuf) {
+        self.meta.push(meta);
+    }
+
+    /// Returns `true` if this context contains no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.meta.is_empty()
+    }
+
+    /// Returns borrowed representations of all attributes in this context.
+    pub fn meta(&self) -> Vec<Meta> {
+        self.meta.iter().map(Into::into).collect()
+    }
+
+    /// Calls the given function with a link, which borrows attributes from this context.
+    ///
+    /// If the previous link is specified, the new one is chained to it, i.e. context attributes
+    /// come after previous ones while iterating.
+    pub fn link<F, R>(&self, prev: Option<&MetaLink>, f: F) -> R
+        where F: FnOnce(&MetaLink) -> R
+    {
+        let meta = self.meta();
+
+        match prev {
+            Some(prev) => f(&MetaLink::with_link(&meta, prev)),
+            None => f(&MetaLink::new(&meta)),
+        }
+    }
+}
+
+impl From<Vec<MetaBuf>> for OwnedContext {
+    fn from(meta: Vec<MetaBuf>) -> OwnedContext {
+        OwnedContext::new(meta)
+    }
+}
+
 impl<'a> From<&'a MetaLink<'a>> for Vec<MetaBuf> {
     fn from(val: &'a MetaLink<'a>) -> Vec<MetaBuf> {
         let mut result = Vec::with_capacity(32);
@@ -229,6 +311,40 @@ mod tests {
     use super::*;
     use super::LinkIter;
 
+    #[test]
+    fn owned_context_with_callsite_meta() {
+        use Record;
+
+        let mut context = OwnedContext::new(vec![MetaBuf::new("service", box "api")]);
+        context.push(MetaBuf::new("version", box 2));
+
+        let path = "/var/www";
+        let meta = [Meta::new("path", &path)];
+
+        context.link(None, |link| {
+            let metalink = MetaLink::with_link(&meta, link);
+            let rec = Record::new(0, 0, "", &metalink);
+
+            let names = rec.iter().map(|meta| meta.name).collect::<Vec<_>>();
+            assert_eq!(vec!["service", "version", "path"], names);
+        });
+    }
+
+    #[test]
+    fn owned_context_after_prev_link() {
+        let context = OwnedContext::new(vec![MetaBuf::new("service", box "api")]);
+
+        let path = "/var/www";
+        let meta = [Meta::new("path", &path)];
+        let metalink = MetaLink::new(&meta);
+
+        let names = context.link(Some(&metalink), |link| {
+            link.iter().map(|meta| meta.name).collect::<Vec<_>>()
+        });
+
+        assert_eq!(vec!["path", "service"], names);
+    }
+
     #[test]
     fn meta_name() {
         let val = 42;
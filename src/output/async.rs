@@ -0,0 +1,231 @@
+use std::error;
+use std::io::Error;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use {Config, Output, Record, Registry};
+
+use factory::Factory;
+use record::RecordBuf;
+
+enum Event {
+    Record(RecordBuf, Vec<u8>),
+    Shutdown,
+}
+
+enum Tx {
+    Bounded(SyncSender<Event>),
+    Unbounded(Sender<Event>),
+}
+
+impl Tx {
+    fn send(&self, event: Event) -> Result<(), mpsc::SendError<Event>> {
+        match *self {
+            Tx::Bounded(ref tx) => tx.send(event),
+            Tx::Unbounded(ref tx) => tx.send(event),
+        }
+    }
+}
+
+/// An output, that offloads writing to a background thread.
+///
+/// Blocking outputs, like files, may stall the caller's thread on I/O. This output copies both the
+/// record and the formatted message into a queue and returns immediately, while a dedicated
+/// worker thread drains the queue, passing messages to the wrapped output in the same order.
+///
+/// The queue is either unbounded, or bounded, in that case writing blocks the caller until there
+/// is a free slot. Errors of the wrapped output can't be returned to the caller, instead the output
+/// is considered unhealthy until the next successful write, as like as the wrapped output itself.
+///
+/// Dropping the output blocks until all queued messages are written.
+pub struct AsyncOutput {
+    output: Arc<Box<Output>>,
+    /// Whether the last write performed by the worker thread has succeeded.
+    healthy: Arc<AtomicBool>,
+    tx: Mutex<Tx>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AsyncOutput {
+    /// Constructs a new asynchronous output with an unbounded queue by wrapping the given one.
+    pub fn new(output: Box<Output>) -> AsyncOutput {
+        let (tx, rx) = mpsc::channel();
+        AsyncOutput::spawn(output, Tx::Unbounded(tx), rx)
+    }
+
+    /// Constructs a new asynchronous output with a queue, that holds at most the given number of
+    /// messages.
+    pub fn bounded(output: Box<Output>, capacity: usize) -> AsyncOutput {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        AsyncOutput::spawn(output, Tx::Bounded(tx), rx)
+    }
+
+    fn spawn(output: Box<Output>, tx: Tx, rx: mpsc::Receiver<Event>) -> AsyncOutput {
+        let output = Arc::new(output);
+        let healthy = Arc::new(AtomicBool::new(true));
+
+        let thread = {
+            let output = output.clone();
+            let healthy = healthy.clone();
+
+            thread::spawn(move || {
+                for event in rx {
+                    match event {
+                        Event::Record(rec, message) => {
+                            rec.borrow_and(|rec| {
+                                let result = output.write(rec, &message);
+                                healthy.store(result.is_ok(), Ordering::Relaxed);
+                            });
+                        }
+                        Event::Shutdown => break,
+                    }
+                }
+            })
+        };
+
+        AsyncOutput {
+            output: output,
+            healthy: healthy,
+            tx: Mutex::new(tx),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Output for AsyncOutput {
+    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+        let event = Event::Record(RecordBuf::from(rec), message.to_vec());
+
+        if let Err(..) = self.tx.lock().unwrap().send(event) {
+            // The worker is gone, nothing to do here.
+        }
+
+        Ok(())
+    }
+
+    fn structured(&self) -> bool {
+        self.output.structured()
+    }
+
+    fn healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed) && self.output.healthy()
+    }
+}
+
+impl Drop for AsyncOutput {
+    fn drop(&mut self) {
+        if let Err(..) = self.tx.lock().unwrap().send(Event::Shutdown) {
+            // Ignore, but the thread should join anyway.
+        }
+        if let Err(..) = self.thread.take().unwrap().join() {
+            // The wrapped output has panicked, nowhere to report it.
+        }
+    }
+}
+
+impl Factory for AsyncOutput {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "async"
+    }
+
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        let output = registry.output(cfg.find("output").ok_or("field \"output\" is required")?)?;
+
+        let res = match cfg.find("capacity") {
+            Some(capacity) => {
+                let capacity = capacity.as_u64().ok_or("field \"capacity\" must be an integer")?;
+                AsyncOutput::bounded(output, capacity as usize)
+            }
+            None => AsyncOutput::new(output),
+        };
+
+        Ok(box res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind};
+    use std::thread;
+    use std::time::Duration;
+
+    use {MetaLink, Output, Record};
+    use output::InMemoryRingOutput;
+
+    use super::AsyncOutput;
+
+    fn write(output: &AsyncOutput, count: usize) {
+        for id in 0..count {
+            let metalink = MetaLink::new(&[]);
+            let mut rec = Record::new(0, 0, "", &metalink);
+            rec.activate(format_args!("message#{}", id));
+
+            output.write(&rec, format!("message#{}", id).as_bytes()).unwrap();
+        }
+    }
+
+    fn expected(count: usize) -> Vec<String> {
+        (0..count).map(|id| format!("message#{}", id)).collect()
+    }
+
+    #[test]
+    fn deliver_all_on_drop() {
+        let ring = InMemoryRingOutput::new(1000);
+
+        write(&AsyncOutput::new(box ring.clone()), 1000);
+
+        assert_eq!(expected(1000), ring.lines());
+    }
+
+    #[test]
+    fn deliver_all_on_drop_bounded() {
+        let ring = InMemoryRingOutput::new(1000);
+
+        write(&AsyncOutput::bounded(box ring.clone(), 4), 1000);
+
+        assert_eq!(expected(1000), ring.lines());
+    }
+
+    struct FailingOutput;
+
+    impl Output for FailingOutput {
+        fn write(&self, _rec: &Record, _message: &[u8]) -> Result<(), Error> {
+            Err(Error::new(ErrorKind::Other, "failed"))
+        }
+    }
+
+    struct PanickingOutput;
+
+    impl Output for PanickingOutput {
+        fn write(&self, _rec: &Record, _message: &[u8]) -> Result<(), Error> {
+            panic!("failed");
+        }
+    }
+
+    #[test]
+    fn unhealthy_after_failed_write() {
+        let output = AsyncOutput::new(box FailingOutput);
+        assert!(output.healthy());
+
+        write(&output, 1);
+
+        // The record is written in the background, so wait for the worker for a while.
+        for _ in 0..1000 {
+            if !output.healthy() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert!(!output.healthy());
+    }
+
+    #[test]
+    fn drop_after_output_panicked() {
+        write(&AsyncOutput::new(box PanickingOutput), 1);
+    }
+}
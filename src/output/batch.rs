@@ -0,0 +1,240 @@
+use std::io::{Error, Write};
+use std::sync::Mutex;
+
+use {Output, Record};
+
+struct State<W> {
+    /// Pending payload with the opening bracket and comma separated objects written so far.
+    buf: Vec<u8>,
+    /// Number of objects in the pending payload.
+    len: usize,
+    wr: W,
+}
+
+/// Accumulates rendered JSON objects and writes them as a single JSON array.
+///
+/// This is intended for sinks, that accept batches of records, like HTTP collectors expecting
+/// `[obj, obj, ...]` payloads. Messages must be rendered by `JsonLayout` or by any other layout,
+/// that produces valid JSON values, because they are written as is.
+///
+/// The batch is written into the inner writer either explicitly, using `flush` method, or when
+/// it reaches its maximum size if set. Objects are separated by commas without any trailing one,
+/// and an explicit flush of the empty batch gives `[]`. Pending objects are flushed on drop.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::Output;
+/// use blacklog::output::JsonArrayOutput;
+///
+/// fn main() {
+///     let output = JsonArrayOutput::new(Vec::new());
+///     output.write(&record!(0), br#"{"message":"first"}"#).unwrap();
+///     output.write(&record!(0), br#"{"message":"second"}"#).unwrap();
+///     output.flush().unwrap();
+/// }
+/// ```
+pub struct JsonArrayOutput<W: Write + Send> {
+    limit: Option<usize>,
+    state: Mutex<State<W>>,
+}
+
+impl<W: Write + Send> JsonArrayOutput<W> {
+    /// Constructs an output, that writes batches into the given writer.
+    ///
+    /// By default batches are unlimited, i.e. written on explicit flushing only.
+    pub fn new(wr: W) -> JsonArrayOutput<W> {
+        JsonArrayOutput {
+            limit: None,
+            state: Mutex::new(State {
+                buf: Vec::new(),
+                len: 0,
+                wr: wr,
+            }),
+        }
+    }
+
+    /// Sets the maximum number of objects in a batch, reaching which triggers flushing.
+    pub fn limit(mut self, limit: usize) -> JsonArrayOutput<W> {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Writes all pending objects as a single JSON array into the inner writer.
+    pub fn flush(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        JsonArrayOutput::flush_state(&mut state)
+    }
+
+    fn flush_state(state: &mut State<W>) -> Result<(), Error> {
+        if state.len == 0 {
+            state.buf.push(b'[');
+        }
+        state.buf.push(b']');
+
+        let result = state.wr.write_all(&state.buf).and_then(|()| state.wr.flush());
+
+        state.buf.clear();
+        state.len = 0;
+
+        result
+    }
+}
+
+impl<W: Write + Send> Output for JsonArrayOutput<W> {
+    fn write(&self, _rec: &Record, message: &[u8]) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        state.buf.push(if state.len == 0 { b'[' } else { b',' });
+        state.buf.extend_from_slice(message);
+        state.len += 1;
+
+        match self.limit {
+            Some(limit) if state.len >= limit => JsonArrayOutput::flush_state(state),
+            Some(..) | None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write + Send> Drop for JsonArrayOutput<W> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+
+        if state.len > 0 {
+            if let Err(..) = JsonArrayOutput::flush_state(&mut state) {
+                // Nowhere to report the error.
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::str::from_utf8;
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{self, Value};
+
+    use {MetaLink, Output, Record};
+    use layout::{JsonLayout, Layout};
+
+    use super::JsonArrayOutput;
+
+    /// Writer, that records each written payload separately.
+    #[derive(Clone)]
+    struct Payloads {
+        payloads: Arc<Mutex<Vec<Vec<u8>>>>,
+        current: Vec<u8>,
+    }
+
+    impl Payloads {
+        fn new() -> Payloads {
+            Payloads {
+                payloads: Arc::new(Mutex::new(Vec::new())),
+                current: Vec::new(),
+            }
+        }
+
+        fn get(&self) -> Vec<String> {
+            self.payloads.lock().unwrap().iter()
+                .map(|payload| from_utf8(payload).unwrap().into())
+                .collect()
+        }
+    }
+
+    impl Write for Payloads {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.current.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            let payload = self.current.drain(..).collect();
+            self.payloads.lock().unwrap().push(payload);
+            Ok(())
+        }
+    }
+
+    fn write(output: &JsonArrayOutput<Payloads>, message: &str) {
+        let layout = JsonLayout::new();
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(2, 0, "", &metalink);
+        rec.activate(format_args!("{}", message));
+
+        let mut buf = Vec::new();
+        layout.format(&rec, &mut buf).unwrap();
+        output.write(&rec, &buf).unwrap();
+    }
+
+    #[test]
+    fn flush_three_records() {
+        let payloads = Payloads::new();
+        let output = JsonArrayOutput::new(payloads.clone());
+
+        write(&output, "first");
+        write(&output, "second");
+        write(&output, "third");
+
+        assert!(payloads.get().is_empty());
+        output.flush().unwrap();
+
+        let payloads = payloads.get();
+        assert_eq!(1, payloads.len());
+
+        let value: Value = serde_json::from_str(&payloads[0]).unwrap();
+        let array = value.as_array().unwrap();
+        assert_eq!(3, array.len());
+        assert_eq!(Some("first"), array[0].find("message").and_then(|v| v.as_string()));
+        assert_eq!(Some("second"), array[1].find("message").and_then(|v| v.as_string()));
+        assert_eq!(Some("third"), array[2].find("message").and_then(|v| v.as_string()));
+    }
+
+    #[test]
+    fn flush_empty() {
+        let payloads = Payloads::new();
+        let output = JsonArrayOutput::new(payloads.clone());
+
+        output.flush().unwrap();
+
+        assert_eq!(vec!["[]"], payloads.get());
+    }
+
+    fn write_raw(output: &JsonArrayOutput<Payloads>, message: &[u8]) {
+        let metalink = MetaLink::new(&[]);
+        output.write(&Record::new(0, 0, "", &metalink), message).unwrap();
+    }
+
+    #[test]
+    fn flush_on_limit() {
+        let payloads = Payloads::new();
+        let output = JsonArrayOutput::new(payloads.clone()).limit(2);
+
+        write_raw(&output, b"1");
+        write_raw(&output, b"2");
+        write_raw(&output, b"3");
+
+        assert_eq!(vec!["[1,2]"], payloads.get());
+    }
+
+    #[test]
+    fn flush_pending_on_drop() {
+        let payloads = Payloads::new();
+
+        {
+            let output = JsonArrayOutput::new(payloads.clone());
+            write_raw(&output, b"1");
+        }
+
+        {
+            // Empty batches aren't written on drop.
+            JsonArrayOutput::new(payloads.clone());
+        }
+
+        assert_eq!(vec!["[1]"], payloads.get());
+    }
+}
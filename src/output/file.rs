@@ -2,67 +2,362 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::error;
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Error, Write};
-use std::path::{Path, PathBuf};
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::mem;
+use std::path::{self, Component, Path, PathBuf};
+#[cfg(unix)]
+use std::ptr;
 use std::str;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
 
+#[cfg(unix)]
+use libc;
+
+use {Format, Formatter, Meta, MetaLink};
 use factory::Factory;
-use layout::Layout;
+use layout::{Error as LayoutError, Layout};
 use layout::pattern::{ParseError, PatternLayout};
+use meta::format::{FormatInto, IntoBoxedFormat};
 use output::Output;
 use registry::{Config, Registry};
 use record::Record;
 
+/// Describes when buffered data of a file is flushed into the file itself.
+///
+/// Buffering drastically reduces the number of system calls, but messages written since the last
+/// flush are lost if the process exits abnormally.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlushPolicy {
+    /// Flush after each record, so readers see messages immediately.
+    EveryRecord,
+    /// Flush after each N records written into the same file.
+    EveryN(usize),
+    /// Flush only when the buffer is full, on explicit `flush` call or on drop.
+    Never,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> FlushPolicy {
+        FlushPolicy::Never
+    }
+}
+
+/// Number of SIGHUP signals received, see `FileOutput::reopen_on_sighup`.
+static GENERATION: AtomicUsize = ATOMIC_USIZE_INIT;
+
+#[cfg(unix)]
+extern "C" fn on_sighup(_signum: libc::c_int) {
+    // Only async-signal-safe operations are allowed here, so files are reopened lazily.
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// The last SIGHUP generation seen by an output.
+///
+/// File based outputs check it on each write and reopen their files when it has changed.
+pub struct Generation(AtomicUsize);
+
+impl Generation {
+    pub fn new() -> Generation {
+        Generation(AtomicUsize::new(GENERATION.load(Ordering::SeqCst)))
+    }
+
+    /// Returns `true` if at least one SIGHUP has been received since the previous call.
+    pub fn changed(&self) -> bool {
+        let generation = GENERATION.load(Ordering::SeqCst);
+        self.0.swap(generation, Ordering::SeqCst) != generation
+    }
+}
+
+/// Meta value, that is checked to be a single normal path component before being formatted.
+struct PathComponent<'a>(&'a FormatInto);
+
+impl<'a> Format for PathComponent<'a> {
+    fn format(&self, format: &mut Formatter) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        self.0.format(&mut Formatter::new(&mut buf, Default::default()))?;
+
+        let value = str::from_utf8(&buf)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+        let mut components = Path::new(value).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(..)), None) if !value.contains(path::is_separator) => {}
+            _ => {
+                return Err(Error::new(ErrorKind::PermissionDenied,
+                    format!("meta value is not allowed in paths: {:?}", value)));
+            }
+        }
+
+        self.0.format(format)
+    }
+}
+
+impl<'a> IntoBoxedFormat for PathComponent<'a> {
+    fn to_boxed_format(&self) -> Box<FormatInto> {
+        self.0.to_boxed_format()
+    }
+}
+
+struct Writer {
+    wr: BufWriter<File>,
+    /// Number of records written since the last flush.
+    pending: usize,
+}
+
+impl Writer {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.pending = 0;
+        self.wr.flush()
+    }
+}
+
 /// Writes all messages into one or multiple files.
 ///
+/// The destination path is rendered per record using a pattern, which may reference both builtin
+/// fields and meta attributes, for example `/var/log/{tenant}.log` gives a file per tenant. If
+/// the record misses some meta attribute required by the pattern, a fallback path is used if
+/// specified, otherwise an error is returned.
+///
+/// Note, that the path pattern is not related to the message layout of the handle, which formats
+/// messages themselves. To avoid confusion it's configured using the `"path_pattern"` field, that
+/// is either a pattern string or a layout config, including references to named layouts.
+///
+/// Since meta attributes are usually filled with external data, each meta value substituted into
+/// a path must be a single normal path component, i.e. values containing separators, `..` or a
+/// root are rejected, while the pattern itself may contain anything. If the base directory is
+/// configured, the parent directory of each path is resolved, including symbolic links, and must
+/// be inside of the resolved base directory.
+///
+/// Writes are buffered and flushed according to the configured `FlushPolicy`, which is `Never` by
+/// default. All files are flushed on drop as well.
+///
+/// Opened files are cached until `reopen` is called, which is required for external rotation tools
+/// like `logrotate`, see also `FileOutput::reopen_on_sighup`.
+///
+/// The output is considered healthy unless the last attempt to open or to write a file has failed.
+/// Records rejected because of their paths don't affect the health.
+///
 /// # Note
 ///
 /// Double locking strategy was chosen to enable concurrent writing into different files from
 /// multiple threads.
 pub struct FileOutput {
-    pattern: PatternLayout,
-    // TODO: Replace `File` with `Writer` and add flushing policies.
-    files: Mutex<HashMap<PathBuf, Arc<Mutex<BufWriter<File>>>>>,
+    pattern: Box<Layout>,
+    fallback: Option<PathBuf>,
+    base: Option<PathBuf>,
+    policy: FlushPolicy,
+    files: Mutex<HashMap<PathBuf, Arc<Mutex<Writer>>>>,
+    healthy: AtomicBool,
+    generation: Generation,
 }
 
 impl FileOutput {
     pub fn new(pattern: &str) -> Result<FileOutput, ParseError> {
-        let pattern = PatternLayout::new(pattern)?;
+        Ok(FileOutput::with_layout(box PatternLayout::new(pattern)?))
+    }
 
-        let res = FileOutput {
-            pattern: pattern,
+    /// Constructs an output, that renders destination paths using the given layout.
+    pub fn with_layout(layout: Box<Layout>) -> FileOutput {
+        FileOutput {
+            pattern: layout,
+            fallback: None,
+            base: None,
+            policy: FlushPolicy::default(),
             files: Mutex::new(HashMap::new()),
+            healthy: AtomicBool::new(true),
+            generation: Generation::new(),
+        }
+    }
+
+    /// Sets the path used for records that miss meta attributes referenced by the path pattern.
+    pub fn fallback<P: Into<PathBuf>>(mut self, path: P) -> FileOutput {
+        self.fallback = Some(path.into());
+        self
+    }
+
+    /// Restricts all rendered paths to be inside of the given base directory.
+    pub fn base<P: Into<PathBuf>>(mut self, path: P) -> FileOutput {
+        self.base = Some(path.into());
+        self
+    }
+
+    /// Sets the policy of flushing buffered data into files.
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> FileOutput {
+        self.policy = policy;
+        self
+    }
+
+    /// Flushes buffered data of all opened files.
+    ///
+    /// All files are flushed even if some of them fail, in that case the first error is returned.
+    pub fn flush(&self) -> Result<(), Error> {
+        let files = self.files.lock().unwrap();
+
+        let mut result = Ok(());
+        for file in files.values() {
+            let rc = file.lock().unwrap().flush();
+            if result.is_ok() {
+                result = rc;
+            }
+        }
+
+        result
+    }
+
+    /// Flushes and closes all opened files, so they are reopened by their paths on next writes.
+    ///
+    /// External rotation tools usually rename log files and notify the process, without that the
+    /// output would continue writing into renamed files. Files, which are being written right now,
+    /// are closed after the write is complete.
+    ///
+    /// All files are flushed even if some of them fail, in that case the first error is returned.
+    pub fn reopen(&self) -> Result<(), Error> {
+        let files = mem::replace(&mut *self.files.lock().unwrap(), HashMap::new());
+
+        let mut result = Ok(());
+        for file in files.values() {
+            let rc = file.lock().unwrap().flush();
+            if result.is_ok() {
+                result = rc;
+            }
+        }
+
+        result
+    }
+
+    /// Installs the SIGHUP handler, after which all file outputs reopen their files before the
+    /// next write following the signal.
+    ///
+    /// The handler just increments a counter, which is checked by outputs on each write, because
+    /// neither locking nor I/O are allowed in signal handlers. Besides `FileOutput` the counter is
+    /// respected by `ShardedFileOutput` and `SizeRollingOutput`.
+    ///
+    /// # Note
+    ///
+    /// Signal handlers are process-global: this replaces any SIGHUP handler installed before,
+    /// including the default one terminating the process, and affects all outputs in the process,
+    /// not just this one. Interrupted system calls are restarted (`SA_RESTART`), so the rest of
+    /// the application isn't disturbed by `EINTR`.
+    #[cfg(unix)]
+    pub fn reopen_on_sighup() -> Result<(), Error> {
+        let rc = unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = on_sighup as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+
+            libc::sigaction(libc::SIGHUP, &action, ptr::null_mut())
         };
 
-        Ok(res)
+        if rc != 0 {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(())
     }
-}
 
-impl Output for FileOutput {
-    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+    fn path(&self, rec: &Record) -> Result<PathBuf, Error> {
+        let values = rec.iter().map(|meta| PathComponent(meta.value)).collect::<Vec<_>>();
+        let meta = rec.iter().zip(values.iter())
+            .map(|(meta, value)| Meta::new(meta.name, value))
+            .collect::<Vec<_>>();
+
         let mut buf = Vec::new();
-        self.pattern.format(rec, &mut buf).unwrap();
+        let mut result = Ok(());
+        rec.with_metalink(&MetaLink::new(&meta), |rec| {
+            result = self.pattern.format(rec, &mut buf);
+        });
 
-        let path = str::from_utf8(&buf).unwrap();
-        let path = Path::new(path);
+        match result {
+            Ok(()) => {}
+            Err(LayoutError::MetaNotFound(..)) if self.fallback.is_some() => {
+                return Ok(self.fallback.clone().unwrap());
+            }
+            Err(err) => return Err(err.into()),
+        }
 
+        let path = str::from_utf8(&buf)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+        let path = PathBuf::from(path);
+        self.validate(&path)?;
+
+        Ok(path)
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), Error> {
+        if let Some(ref base) = self.base {
+            let parent = match path.parent() {
+                Some(parent) if parent != Path::new("") => parent,
+                Some(..) | None => Path::new("."),
+            };
+
+            if !parent.canonicalize()?.starts_with(base.canonicalize()?) {
+                return Err(Error::new(ErrorKind::PermissionDenied,
+                    format!("path {} is outside of {}", path.display(), base.display())));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_path(&self, path: PathBuf, message: &[u8]) -> Result<(), Error> {
         let file = {
             let mut files = self.files.lock().unwrap();
 
-            // TODO: Not optimal, because of heap allocation every try.
-            match files.entry(path.to_path_buf()) {
+            match files.entry(path) {
                 Entry::Occupied(v) => v.get().clone(),
                 Entry::Vacant(v) => {
-                    let file = OpenOptions::new().append(true).create(true).open(path)?;
-                    v.insert(Arc::new(Mutex::new(BufWriter::new(file)))).clone()
+                    let file = OpenOptions::new().append(true).create(true).open(v.key())?;
+                    let writer = Writer {
+                        wr: BufWriter::new(file),
+                        pending: 0,
+                    };
+                    v.insert(Arc::new(Mutex::new(writer))).clone()
                 }
             }
         };
 
         let mut file = file.lock().unwrap();
-        file.write_all(message)?;
-        file.write_all(b"\n")
+        file.wr.write_all(message)?;
+        file.wr.write_all(b"\n")?;
+        file.pending += 1;
+
+        match self.policy {
+            FlushPolicy::EveryRecord => file.flush(),
+            FlushPolicy::EveryN(n) if file.pending >= n => file.flush(),
+            FlushPolicy::EveryN(..) | FlushPolicy::Never => Ok(()),
+        }
+    }
+}
+
+impl Output for FileOutput {
+    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+        if self.generation.changed() {
+            if let Err(..) = self.reopen() {
+                // Errors of closed files are not related to this record.
+            }
+        }
+
+        let path = self.path(rec)?;
+
+        let result = self.write_path(path, message);
+        self.healthy.store(result.is_ok(), Ordering::Relaxed);
+
+        result
+    }
+
+    fn healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FileOutput {
+    fn drop(&mut self) {
+        if let Err(..) = self.flush() {
+            // Nowhere to report the error.
+        }
     }
 }
 
@@ -73,14 +368,420 @@ impl Factory for FileOutput {
         "file"
     }
 
-    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
-        let path = cfg.find("path")
-            .ok_or("field \"path\" is required")?
-            .as_string()
-            .ok_or("field \"path\" must be a string")?;
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        // The "path" field is kept for compatibility, it accepts pattern strings only.
+        let mut res = match (cfg.find("path_pattern"), cfg.find("path")) {
+            (Some(pattern), ..) => {
+                match pattern.as_string() {
+                    Some(pattern) => FileOutput::new(pattern)?,
+                    None => FileOutput::with_layout(registry.layout(pattern)?),
+                }
+            }
+            (None, Some(path)) => {
+                FileOutput::new(path.as_string().ok_or("field \"path\" must be a string")?)?
+            }
+            (None, None) => return Err("field \"path_pattern\" is required".into()),
+        };
+
+        if let Some(fallback) = cfg.find("fallback") {
+            let fallback = fallback.as_string()
+                .ok_or("field \"fallback\" must be a string")?;
+            res = res.fallback(fallback);
+        }
+
+        if let Some(base) = cfg.find("base") {
+            let base = base.as_string()
+                .ok_or("field \"base\" must be a string")?;
+            res = res.base(base);
+        }
 
-        let res = FileOutput::new(path)?;
+        if let Some(flush) = cfg.find("flush") {
+            res = res.flush_policy(flush_policy(flush)?);
+        }
 
         Ok(box res)
     }
 }
+
+/// Parses the flush policy from the value of the "flush" field.
+fn flush_policy(flush: &Config) -> Result<FlushPolicy, Box<error::Error>> {
+    let policy = match (flush.as_string(), flush.as_u64()) {
+        (Some("every_record"), ..) => FlushPolicy::EveryRecord,
+        (Some("never"), ..) => FlushPolicy::Never,
+        (None, Some(0)) => return Err("field \"flush\" must be a positive integer".into()),
+        (None, Some(n)) => FlushPolicy::EveryN(n as usize),
+        _ => {
+            return Err(concat!("field \"flush\" must be either \"every_record\", ",
+                "\"never\" or a positive integer").into());
+        }
+    };
+
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::{ErrorKind, Read};
+    use std::path::PathBuf;
+    use std::sync::atomic::Ordering;
+
+    use serde_json;
+
+    use {Meta, MetaLink, Output, Record};
+    use support::tempdir;
+
+    use super::{flush_policy, FileOutput, FlushPolicy, Generation, GENERATION};
+
+    fn read(path: PathBuf) -> String {
+        let mut buf = String::new();
+        File::open(path).unwrap().read_to_string(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn write_per_tenant_files() {
+        let dir = tempdir("per-tenant");
+
+        {
+            let output = FileOutput::new(&format!("{}/{{tenant}}.log", dir.display())).unwrap();
+
+            let tenant = "first";
+            let meta = [Meta::new("tenant", &tenant)];
+            let metalink = MetaLink::new(&meta);
+            output.write(&Record::new(0, 0, "", &metalink), b"message#1").unwrap();
+
+            let tenant = "second";
+            let meta = [Meta::new("tenant", &tenant)];
+            let metalink = MetaLink::new(&meta);
+            output.write(&Record::new(0, 0, "", &metalink), b"message#2").unwrap();
+        }
+
+        assert_eq!("message#1\n", read(dir.join("first.log")));
+        assert_eq!("message#2\n", read(dir.join("second.log")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_fallback_when_meta_not_found() {
+        let dir = tempdir("fallback");
+
+        {
+            // The partially rendered path is a valid file path, which must never be used.
+            let output = FileOutput::new(&format!("{}/app-{{tenant}}.log", dir.display())).unwrap()
+                .fallback(dir.join("default.log"));
+
+            let metalink = MetaLink::new(&[]);
+            output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap();
+        }
+
+        assert_eq!("message\n", read(dir.join("default.log")));
+        assert_eq!(1, fs::read_dir(&dir).unwrap().count());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fail_write_when_meta_not_found_without_fallback() {
+        let dir = tempdir("no-fallback");
+
+        {
+            let output = FileOutput::new(&format!("{}/app-{{tenant}}.log", dir.display())).unwrap();
+
+            let metalink = MetaLink::new(&[]);
+            assert!(output.write(&Record::new(0, 0, "", &metalink), b"message").is_err());
+        }
+
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fail_write_path_traversal() {
+        let dir = tempdir("traversal");
+
+        let output = FileOutput::new(&format!("{}/{{tenant}}.log", dir.display())).unwrap();
+
+        let tenant = "../../etc/passwd";
+        let meta = [Meta::new("tenant", &tenant)];
+        let metalink = MetaLink::new(&meta);
+        let err = output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        assert!(fs::read_dir(&dir).unwrap().next().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fail_write_meta_with_separator() {
+        let dir = tempdir("separator");
+
+        let output = FileOutput::new(&format!("{}/{{tenant}}.log", dir.display())).unwrap();
+
+        for tenant in &["/tmp/blacklog-outside", "acme/logs", "..", ".", ""] {
+            let meta = [Meta::new("tenant", tenant)];
+            let metalink = MetaLink::new(&meta);
+            let err = output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+            assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        }
+
+        assert!(fs::read_dir(&dir).unwrap().next().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_pattern_with_parent_dir() {
+        let dir = tempdir("pattern-parent");
+        fs::create_dir_all(dir.join("app")).unwrap();
+
+        {
+            let output = FileOutput::new(&format!("{}/app/../{{tenant}}.log", dir.display()))
+                .unwrap()
+                .base(&dir);
+
+            let tenant = "acme";
+            let meta = [Meta::new("tenant", &tenant)];
+            let metalink = MetaLink::new(&meta);
+            output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap();
+        }
+
+        assert_eq!("message\n", read(dir.join("acme.log")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fail_write_outside_base() {
+        let dir = tempdir("base");
+        fs::create_dir_all(dir.join("base")).unwrap();
+
+        let output = FileOutput::new(&format!("{}/{{tenant}}.log", dir.display())).unwrap()
+            .base(dir.join("base"));
+
+        let tenant = "acme";
+        let meta = [Meta::new("tenant", &tenant)];
+        let metalink = MetaLink::new(&meta);
+        let err = output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        assert!(!dir.join("acme.log").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fail_write_outside_base_through_symlink() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir("base-symlink");
+        fs::create_dir_all(dir.join("base")).unwrap();
+        fs::create_dir_all(dir.join("outside")).unwrap();
+        symlink(dir.join("outside"), dir.join("base/link")).unwrap();
+
+        let output = FileOutput::new(&format!("{}/base/link/{{tenant}}.log", dir.display()))
+            .unwrap()
+            .base(dir.join("base"));
+
+        let tenant = "acme";
+        let meta = [Meta::new("tenant", &tenant)];
+        let metalink = MetaLink::new(&meta);
+        let err = output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        assert!(fs::read_dir(dir.join("outside")).unwrap().next().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn unhealthy_after_failed_open() {
+        let dir = tempdir("unhealthy");
+
+        let output = FileOutput::new(&format!("{}/{{dir}}/app.log", dir.display())).unwrap();
+        assert!(output.healthy());
+
+        let subdir = "missing";
+        let meta = [Meta::new("dir", &subdir)];
+        let metalink = MetaLink::new(&meta);
+        output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+        assert!(!output.healthy());
+
+        fs::create_dir_all(dir.join("missing")).unwrap();
+        output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap();
+
+        assert!(output.healthy());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn healthy_after_rejected_path() {
+        let dir = tempdir("healthy-rejected");
+
+        let output = FileOutput::new(&format!("{}/{{tenant}}.log", dir.display())).unwrap();
+
+        let tenant = "../escape";
+        let meta = [Meta::new("tenant", &tenant)];
+        let metalink = MetaLink::new(&meta);
+        output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+        assert!(output.healthy());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn write_messages(output: &FileOutput, count: usize) {
+        let metalink = MetaLink::new(&[]);
+        for id in 0..count {
+            output.write(&Record::new(0, 0, "", &metalink), format!("message#{}", id).as_bytes())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn flush_every_record() {
+        let dir = tempdir("flush-every-record");
+        let path = dir.join("app.log");
+
+        let output = FileOutput::new(&format!("{}", path.display())).unwrap()
+            .flush_policy(FlushPolicy::EveryRecord);
+        write_messages(&output, 1);
+
+        // The output is still alive, but the message is already visible to other readers.
+        assert_eq!("message#0\n", read(path));
+
+        drop(output);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn flush_every_n_records() {
+        let dir = tempdir("flush-every-n");
+        let path = dir.join("app.log");
+
+        let output = FileOutput::new(&format!("{}", path.display())).unwrap()
+            .flush_policy(FlushPolicy::EveryN(2));
+
+        write_messages(&output, 1);
+        assert_eq!("", read(path.clone()));
+
+        write_messages(&output, 1);
+        assert_eq!("message#0\nmessage#0\n", read(path));
+
+        drop(output);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn flush_explicitly() {
+        let dir = tempdir("flush-explicit");
+        let path = dir.join("app.log");
+
+        let output = FileOutput::new(&format!("{}", path.display())).unwrap();
+        write_messages(&output, 2);
+        assert_eq!("", read(path.clone()));
+
+        output.flush().unwrap();
+        assert_eq!("message#0\nmessage#1\n", read(path));
+
+        drop(output);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reopen_after_rename() {
+        let dir = tempdir("reopen");
+        let path = dir.join("app.log");
+
+        let output = FileOutput::new(&format!("{}", path.display())).unwrap()
+            .flush_policy(FlushPolicy::EveryRecord);
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        output.write(&rec, b"message#1").unwrap();
+        fs::rename(&path, dir.join("app.log.1")).unwrap();
+
+        // Without reopening the renamed file is still written.
+        output.write(&rec, b"message#2").unwrap();
+        assert!(!path.exists());
+
+        output.reopen().unwrap();
+        output.write(&rec, b"message#3").unwrap();
+
+        assert_eq!("message#1\nmessage#2\n", read(dir.join("app.log.1")));
+        assert_eq!("message#3\n", read(path));
+
+        drop(output);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn generation_changed_once() {
+        let generation = Generation::new();
+        assert!(!generation.changed());
+
+        // Pretend a signal has been received without touching the global counter, which is shared
+        // with other tests.
+        generation.0.store(GENERATION.load(Ordering::SeqCst).wrapping_sub(1), Ordering::SeqCst);
+
+        assert!(generation.changed());
+        assert!(!generation.changed());
+    }
+
+    #[test]
+    fn reopen_after_generation_changed() {
+        let dir = tempdir("reopen-generation");
+        let path = dir.join("app.log");
+
+        let output = FileOutput::new(&format!("{}", path.display())).unwrap()
+            .flush_policy(FlushPolicy::EveryRecord);
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        output.write(&rec, b"message#1").unwrap();
+        fs::rename(&path, dir.join("app.log.1")).unwrap();
+
+        output.generation.0.store(GENERATION.load(Ordering::SeqCst).wrapping_sub(1),
+            Ordering::SeqCst);
+        output.write(&rec, b"message#2").unwrap();
+
+        assert_eq!("message#1\n", read(dir.join("app.log.1")));
+        assert_eq!("message#2\n", read(path));
+
+        drop(output);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn flush_policy_from_config() {
+        let cfg = serde_json::from_str(r#""every_record""#).unwrap();
+        assert_eq!(FlushPolicy::EveryRecord, flush_policy(&cfg).unwrap());
+
+        let cfg = serde_json::from_str(r#""never""#).unwrap();
+        assert_eq!(FlushPolicy::Never, flush_policy(&cfg).unwrap());
+
+        let cfg = serde_json::from_str("16").unwrap();
+        assert_eq!(FlushPolicy::EveryN(16), flush_policy(&cfg).unwrap());
+    }
+
+    #[test]
+    fn fail_flush_policy_from_invalid_config() {
+        let cfg = serde_json::from_str("0").unwrap();
+        assert!(flush_policy(&cfg).is_err());
+
+        let cfg = serde_json::from_str(r#""always""#).unwrap();
+        assert!(flush_policy(&cfg).is_err());
+
+        let cfg = serde_json::from_str("-1").unwrap();
+        assert!(flush_policy(&cfg).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::io::Error;
+use std::sync::{Arc, Mutex};
+
+use {Output, Record};
+
+/// Accumulates all rendered messages in memory as is.
+///
+/// This output is intended for testing whole pipelines without touching files or sockets, giving
+/// an ability to assert exact bytes produced by handles and layouts. Unlike `InMemoryRingOutput`
+/// it never discards messages, so it shouldn't be used in production.
+///
+/// The output is cheaply clonable, all clones share the same storage, so it's possible to keep
+/// one clone for reading while another one is owned by a handle.
+///
+/// # Examples
+///
+/// ```
+/// use blacklog::{Handle, MetaLink, Record};
+/// use blacklog::handle::SyncHandle;
+/// use blacklog::layout::PatternLayout;
+/// use blacklog::output::MemoryOutput;
+///
+/// let output = MemoryOutput::new();
+/// let layout = PatternLayout::new("[{severity:d}] {message}").unwrap();
+/// let handle = SyncHandle::new(Box::new(layout), vec![Box::new(output.clone())]);
+///
+/// let metalink = MetaLink::new(&[]);
+/// let mut rec = Record::new(2, 42, "app", &metalink);
+/// rec.activate(format_args!("le message"));
+/// handle.handle(&mut rec).unwrap();
+///
+/// assert_eq!(vec![b"[2] le message".to_vec()], output.messages());
+/// ```
+#[derive(Clone, Default)]
+pub struct MemoryOutput {
+    messages: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl MemoryOutput {
+    pub fn new() -> MemoryOutput {
+        MemoryOutput::default()
+    }
+
+    /// Returns a copy of all captured messages in the order they were written.
+    pub fn messages(&self) -> Vec<Vec<u8>> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Discards all captured messages.
+    pub fn clear(&self) {
+        self.messages.lock().unwrap().clear();
+    }
+}
+
+impl Output for MemoryOutput {
+    fn write(&self, _rec: &Record, message: &[u8]) -> Result<(), Error> {
+        self.messages.lock().unwrap().push(message.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Handle, MetaLink, Output, Record};
+    use handle::SyncHandle;
+    use layout::PatternLayout;
+
+    use super::MemoryOutput;
+
+    #[test]
+    fn write_through_handle() {
+        let output = MemoryOutput::new();
+        let layout = PatternLayout::new("[{severity:d}] {message}").unwrap();
+        let handle = SyncHandle::new(box layout, vec![box output.clone()]);
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(2, 0, "", &metalink);
+        rec.activate(format_args!("file does not exist: {}", "favicon.ico"));
+        handle.handle(&mut rec).unwrap();
+
+        assert_eq!(vec![b"[2] file does not exist: favicon.ico".to_vec()], output.messages());
+    }
+
+    #[test]
+    fn clear() {
+        let output = MemoryOutput::new();
+
+        let metalink = MetaLink::new(&[]);
+        output.write(&Record::new(0, 0, "", &metalink), b"message#1").unwrap();
+        output.clear();
+        output.write(&Record::new(0, 0, "", &metalink), b"message#2").unwrap();
+
+        assert_eq!(vec![b"message#2".to_vec()], output.messages());
+    }
+}
@@ -1,14 +1,55 @@
 use super::Record;
 
+mod async;
+mod batch;
 mod file;
+mod memory;
+mod multi;
+mod ndjson;
 mod null;
+mod pipe;
+mod retry;
+mod ring;
+mod rolling;
+mod sharded;
+#[cfg(feature="rusqlite")]
+mod sqlite;
 mod term;
 
-pub use self::file::FileOutput;
+pub use self::async::AsyncOutput;
+pub use self::batch::JsonArrayOutput;
+pub use self::file::{FileOutput, FlushPolicy};
+pub use self::memory::MemoryOutput;
+pub use self::multi::MultiOutput;
+pub use self::ndjson::StdoutJson;
 pub use self::null::NullOutput;
+pub use self::pipe::PipeOutput;
+pub use self::retry::RetryOutput;
+pub use self::ring::InMemoryRingOutput;
+pub use self::rolling::SizeRollingOutput;
+pub use self::sharded::ShardedFileOutput;
+#[cfg(feature="rusqlite")]
+pub use self::sqlite::SqliteOutput;
 pub use self::term::Term;
 
 /// Outputs are responsible for delivering formatted log events to their destination.
 pub trait Output: Send + Sync {
     fn write(&self, rec: &Record, message: &[u8]) -> Result<(), ::std::io::Error>;
+
+    /// Returns `true` if this output consumes the record itself and ignores the formatted message.
+    ///
+    /// Structured sinks, like databases or in-process aggregators, are interested only in the
+    /// record with its meta information, not in pre-rendered bytes. Handles may skip formatting
+    /// entirely when all of their outputs are structured, passing an empty message instead.
+    fn structured(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if this output is currently able to deliver messages.
+    ///
+    /// This is a liveness probe, which never writes anything, intended for health endpoints to
+    /// report degraded sinks. Outputs, that can't fail or can't tell, are always healthy.
+    fn healthy(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,117 @@
+use std::error;
+use std::io::Error;
+
+use {Config, Output, Record, Registry};
+
+use factory::Factory;
+
+/// Fans out each message to several outputs.
+///
+/// The message is formatted once by the handle and then passed to all children sequentially.
+/// Writing continues even if some of them fail, after that the first error occurred is returned.
+pub struct MultiOutput {
+    outputs: Vec<Box<Output>>,
+}
+
+impl MultiOutput {
+    pub fn new(outputs: Vec<Box<Output>>) -> MultiOutput {
+        MultiOutput {
+            outputs: outputs,
+        }
+    }
+}
+
+impl Output for MultiOutput {
+    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+        let mut result = Ok(());
+
+        for output in &self.outputs {
+            if let Err(err) = output.write(rec, message) {
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn structured(&self) -> bool {
+        !self.outputs.is_empty() && self.outputs.iter().all(|output| output.structured())
+    }
+
+    fn healthy(&self) -> bool {
+        self.outputs.iter().all(|output| output.healthy())
+    }
+}
+
+impl Factory for MultiOutput {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "multi"
+    }
+
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        let outputs = cfg.find("outputs")
+            .ok_or("field \"outputs\" is required")?
+            .as_array()
+            .ok_or("field \"outputs\" must be an array")?
+            .iter()
+            .map(|o| registry.output(o))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(box MultiOutput::new(outputs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind};
+
+    use {Handle, MetaLink, Output, Record};
+    use handle::SyncHandle;
+    use layout::PatternLayout;
+    use output::InMemoryRingOutput;
+
+    use super::MultiOutput;
+
+    struct FailingOutput;
+
+    impl Output for FailingOutput {
+        fn write(&self, _rec: &Record, _message: &[u8]) -> Result<(), Error> {
+            Err(Error::new(ErrorKind::Other, "failed"))
+        }
+    }
+
+    #[test]
+    fn write_to_all_outputs() {
+        let first = InMemoryRingOutput::new(10);
+        let second = InMemoryRingOutput::new(10);
+
+        let output = MultiOutput::new(vec![box first.clone(), box second.clone()]);
+        let handle = SyncHandle::new(box PatternLayout::new("[{severity}] {message}").unwrap(),
+            vec![box output]);
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("value"));
+        handle.handle(&mut rec).unwrap();
+
+        assert_eq!(vec!["[0] value"], first.lines());
+        assert_eq!(vec!["[0] value"], second.lines());
+    }
+
+    #[test]
+    fn write_to_all_outputs_despite_errors() {
+        let ring = InMemoryRingOutput::new(10);
+
+        let output = MultiOutput::new(vec![box FailingOutput, box ring.clone()]);
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        assert!(output.write(&rec, b"value").is_err());
+        assert_eq!(vec!["value"], ring.lines());
+    }
+}
@@ -0,0 +1,170 @@
+use std::error;
+use std::io::{self, LineWriter, Stdout, Write};
+use std::sync::Mutex;
+
+use {Config, Layout, Output, Record, Registry};
+
+use factory::Factory;
+use layout::JsonLayout;
+
+/// Writes records into the standard output as newline-delimited JSON (NDJSON).
+///
+/// This is the conventional way of logging for containerized deployments, where the environment
+/// collects the standard output of a process. Each record is rendered as a compact JSON object
+/// followed by exactly one newline and written with a single call under the output's lock, so
+/// lines from concurrent threads are never interleaved.
+///
+/// Writes go through a `LineWriter`, which passes each line to the underlying writer at once and
+/// flushes it, so records are never held back in the buffer.
+///
+/// The output is structured, i.e. it renders records itself, so the layout of the owning handle
+/// is never used and can be anything.
+pub struct StdoutJson<W: Write + Send = Stdout> {
+    layout: JsonLayout,
+    wr: Mutex<LineWriter<W>>,
+}
+
+impl StdoutJson<Stdout> {
+    pub fn new() -> StdoutJson<Stdout> {
+        StdoutJson::with_writer(io::stdout())
+    }
+}
+
+impl<W: Write + Send> StdoutJson<W> {
+    /// Constructs an output, that writes NDJSON into the given writer instead of stdout.
+    pub fn with_writer(wr: W) -> StdoutJson<W> {
+        StdoutJson {
+            layout: JsonLayout::new(),
+            wr: Mutex::new(LineWriter::new(wr)),
+        }
+    }
+
+    /// Renders the given record into the buffer as a single newline-terminated JSON line.
+    fn frame(&self, rec: &Record, buf: &mut Vec<u8>) -> Result<(), io::Error> {
+        self.layout.format(rec, buf)?;
+        buf.push(b'\n');
+        Ok(())
+    }
+}
+
+impl Default for StdoutJson<Stdout> {
+    fn default() -> StdoutJson<Stdout> {
+        StdoutJson::new()
+    }
+}
+
+impl<W: Write + Send> Output for StdoutJson<W> {
+    fn write(&self, rec: &Record, _message: &[u8]) -> Result<(), io::Error> {
+        let mut buf = Vec::with_capacity(256);
+        self.frame(rec, &mut buf)?;
+
+        self.wr.lock().unwrap().write_all(&buf)
+    }
+
+    fn structured(&self) -> bool {
+        true
+    }
+}
+
+impl Factory for StdoutJson<Stdout> {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "stdout_json"
+    }
+
+    fn from(_cfg: &Config, _registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        Ok(box StdoutJson::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::str::from_utf8;
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{self, Value};
+
+    use {Meta, MetaLink, Output, Record};
+
+    use super::StdoutJson;
+
+    /// Writer, that shares its content, so it can be inspected while the output is alive.
+    #[derive(Clone)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Shared {
+        fn get(&self) -> String {
+            from_utf8(&self.0.lock().unwrap()[..]).unwrap().into()
+        }
+    }
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn frame_single_line() {
+        let output = StdoutJson::new();
+
+        let text = "multi\nline";
+        let meta = [Meta::new("text", &text)];
+        let metalink = MetaLink::new(&meta);
+
+        let mut buf = Vec::new();
+        for message in &["first", "second\nwith a newline"] {
+            let mut rec = Record::new(2, 10, "app", &metalink);
+            rec.activate(format_args!("{}", message));
+            output.frame(&rec, &mut buf).unwrap();
+        }
+
+        let out = from_utf8(&buf[..]).unwrap();
+        assert!(out.ends_with('\n'));
+
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+
+        let first: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(Some("first"), first.find("message").and_then(|v| v.as_string()));
+        assert_eq!(Some("multi\nline"), first.find("text").and_then(|v| v.as_string()));
+
+        let second: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(Some("second\nwith a newline"),
+            second.find("message").and_then(|v| v.as_string()));
+    }
+
+    #[test]
+    fn write_line_per_record() {
+        let wr = Shared(Arc::new(Mutex::new(Vec::new())));
+        let output = StdoutJson::with_writer(wr.clone());
+
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(2, 10, "app", &metalink);
+        rec.activate(format_args!("first"));
+        output.write(&rec, &[]).unwrap();
+
+        // The line is passed through without waiting for the buffer to fill up.
+        let out = wr.get();
+        assert_eq!(1, out.lines().count());
+        assert!(out.ends_with('\n'));
+
+        let mut rec = Record::new(2, 10, "app", &metalink);
+        rec.activate(format_args!("second"));
+        output.write(&rec, &[]).unwrap();
+
+        let out = wr.get();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+
+        let second: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(Some("second"), second.find("message").and_then(|v| v.as_string()));
+    }
+}
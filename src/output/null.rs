@@ -20,6 +20,7 @@ use factory::Factory;
 ///     let out = NullOutput;
 ///
 ///     assert!(out.write(&record!(0), &[]).is_ok());
+///     assert!(out.healthy());
 /// }
 /// ```
 pub struct NullOutput;
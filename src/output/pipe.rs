@@ -0,0 +1,211 @@
+use std::error;
+use std::io::{Error, ErrorKind, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+use {Config, Output, Record, Registry};
+
+use factory::Factory;
+
+/// Pipes messages into the standard input of a spawned child process, one message per line.
+///
+/// This allows to integrate with existing log processors, like `logger` or custom scripts, which
+/// read lines from their standard input. The command is spawned once on construction, while its
+/// standard output and error are inherited from the current process.
+///
+/// If the child exits, the next write fails with a broken pipe, after which the child is reaped
+/// and the command is spawned again to write the message once more. Errors of respawning are
+/// returned to the caller and respawning is repeated on each subsequent write.
+///
+/// Dropping the output closes the child's standard input and waits for it to exit, so the child
+/// is able to process all pending lines.
+pub struct PipeOutput {
+    command: String,
+    args: Vec<String>,
+    child: Mutex<Option<Child>>,
+}
+
+impl PipeOutput {
+    /// Spawns the given command with the given arguments, returning an error if it fails.
+    pub fn new<I>(command: &str, args: I) -> Result<PipeOutput, Error>
+        where I: IntoIterator,
+              I::Item: Into<String>
+    {
+        let res = PipeOutput {
+            command: command.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            child: Mutex::new(None),
+        };
+
+        *res.child.lock().unwrap() = Some(res.spawn()?);
+
+        Ok(res)
+    }
+
+    fn spawn(&self) -> Result<Child, Error> {
+        Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .spawn()
+    }
+
+    fn write_line(child: &mut Child, message: &[u8]) -> Result<(), Error> {
+        let stdin = child.stdin.as_mut().expect("stdin must be piped");
+        stdin.write_all(message)?;
+        stdin.write_all(b"\n")
+    }
+
+    fn reap(mut child: Child) {
+        if let Err(..) = child.kill() {
+            // Already exited.
+        }
+
+        if let Err(..) = child.wait() {
+            // Nothing to do with a child, that can't be waited for.
+        }
+    }
+}
+
+impl Output for PipeOutput {
+    fn write(&self, _rec: &Record, message: &[u8]) -> Result<(), Error> {
+        let mut child = self.child.lock().unwrap();
+
+        if let Some(ref mut child) = *child {
+            match PipeOutput::write_line(child, message) {
+                Ok(()) => return Ok(()),
+                Err(ref err) if err.kind() == ErrorKind::BrokenPipe => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        // The child has either died or failed to respawn previously.
+        if let Some(prev) = child.take() {
+            PipeOutput::reap(prev);
+        }
+
+        let mut next = self.spawn()?;
+        let result = PipeOutput::write_line(&mut next, message);
+        *child = Some(next);
+
+        result
+    }
+
+    fn healthy(&self) -> bool {
+        self.child.lock().unwrap().is_some()
+    }
+}
+
+impl Drop for PipeOutput {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.lock().unwrap().take() {
+            // Closing stdin signals the child, that there will be no more lines.
+            drop(child.stdin.take());
+
+            if let Err(..) = child.wait() {
+                // Nowhere to report the error.
+            }
+        }
+    }
+}
+
+impl Factory for PipeOutput {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "pipe"
+    }
+
+    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        let command = cfg.find("command")
+            .ok_or("field \"command\" is required")?
+            .as_string()
+            .ok_or("field \"command\" must be a string")?;
+
+        let args = match cfg.find("args") {
+            Some(args) => {
+                args.as_array()
+                    .ok_or("field \"args\" must be an array")?
+                    .iter()
+                    .map(|arg| arg.as_string().ok_or("field \"args\" must contain strings only"))
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            None => Vec::new(),
+        };
+
+        Ok(box PipeOutput::new(command, args)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    use {MetaLink, Output, Record};
+    use support::tempdir;
+
+    use super::PipeOutput;
+
+    fn read(path: PathBuf) -> String {
+        let mut buf = String::new();
+        File::open(path).unwrap().read_to_string(&mut buf).unwrap();
+        buf
+    }
+
+    fn cat(path: &PathBuf) -> PipeOutput {
+        let path = format!("{}", path.display());
+        PipeOutput::new("sh", vec!["-c", r#"exec cat >> "$0""#, &path[..]]).unwrap()
+    }
+
+    #[test]
+    fn write_lines() {
+        let dir = tempdir("pipe-lines");
+        let path = dir.join("out.log");
+
+        {
+            let output = cat(&path);
+
+            let metalink = MetaLink::new(&[]);
+            output.write(&Record::new(0, 0, "", &metalink), b"message#1").unwrap();
+            output.write(&Record::new(0, 0, "", &metalink), b"message#2").unwrap();
+        }
+
+        assert_eq!("message#1\nmessage#2\n", read(path));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn respawn_after_child_died() {
+        let dir = tempdir("pipe-respawn");
+        let path = dir.join("out.log");
+
+        {
+            let output = cat(&path);
+
+            let metalink = MetaLink::new(&[]);
+            output.write(&Record::new(0, 0, "", &metalink), b"message#1").unwrap();
+
+            {
+                let mut child = output.child.lock().unwrap();
+                let child = child.as_mut().unwrap();
+                child.kill().unwrap();
+                child.wait().unwrap();
+            }
+
+            output.write(&Record::new(0, 0, "", &metalink), b"message#2").unwrap();
+            assert!(output.healthy());
+        }
+
+        // The first message may be lost, because the child could be killed before reading it.
+        assert!(read(path).ends_with("message#2\n"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fail_spawn_nonexistent_command() {
+        assert!(PipeOutput::new("/nonexistent/blacklog-pipe", Vec::<String>::new()).is_err());
+    }
+}
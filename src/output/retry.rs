@@ -0,0 +1,197 @@
+use std::cmp;
+use std::error;
+use std::io::{Error, ErrorKind};
+use std::thread;
+use std::time::Duration;
+
+use {Config, Output, Record, Registry};
+
+use factory::Factory;
+
+/// Wraps an output, retrying failed writes caused by transient errors with exponential backoff.
+///
+/// Only `Interrupted`, `WouldBlock`, `TimedOut`, `ConnectionReset` and `ConnectionAborted` errors
+/// are considered as transient, all others are returned immediately, as like as the last error
+/// after all attempts are exhausted.
+///
+/// # Note
+///
+/// Backoff sleeping blocks the calling thread, which in the case of synchronous logger is the
+/// thread that logs. That's why retrying is opt-in per output and the delay is capped.
+pub struct RetryOutput {
+    output: Box<Output>,
+    attempts: usize,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryOutput {
+    /// Wraps the given output, retrying 3 times starting from 1ms delay up to 100ms.
+    pub fn new(output: Box<Output>) -> RetryOutput {
+        RetryOutput {
+            output: output,
+            attempts: 3,
+            backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(100),
+        }
+    }
+
+    /// Sets the maximum number of retries after the first failed attempt.
+    pub fn attempts(mut self, attempts: usize) -> RetryOutput {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Sets the initial delay, that is doubled after each failed attempt, but never exceeds the
+    /// given maximum.
+    pub fn backoff(mut self, backoff: Duration, max: Duration) -> RetryOutput {
+        self.backoff = backoff;
+        self.max_backoff = max;
+        self
+    }
+
+    fn is_transient(kind: ErrorKind) -> bool {
+        match kind {
+            ErrorKind::Interrupted |
+            ErrorKind::WouldBlock |
+            ErrorKind::TimedOut |
+            ErrorKind::ConnectionReset |
+            ErrorKind::ConnectionAborted => true,
+            _ => false,
+        }
+    }
+}
+
+impl Output for RetryOutput {
+    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+        let mut delay = cmp::min(self.backoff, self.max_backoff);
+        let mut attempt = 0;
+
+        loop {
+            match self.output.write(rec, message) {
+                Ok(()) => return Ok(()),
+                Err(ref err) if attempt < self.attempts &&
+                    RetryOutput::is_transient(err.kind()) =>
+                {
+                    thread::sleep(delay);
+                    delay = cmp::min(delay * 2, self.max_backoff);
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn structured(&self) -> bool {
+        self.output.structured()
+    }
+
+    fn healthy(&self) -> bool {
+        self.output.healthy()
+    }
+}
+
+impl Factory for RetryOutput {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "retry"
+    }
+
+    fn from(cfg: &Config, registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        let output = registry.output(cfg.find("output").ok_or("field \"output\" is required")?)?;
+
+        let mut res = RetryOutput::new(output);
+
+        if let Some(attempts) = cfg.find("attempts") {
+            let attempts = attempts.as_u64().ok_or("field \"attempts\" must be an integer")?;
+            res = res.attempts(attempts as usize);
+        }
+
+        if let Some(backoff) = cfg.find("backoff") {
+            let backoff = backoff.as_u64().ok_or("field \"backoff\" must be an integer")?;
+            let max = match cfg.find("max_backoff") {
+                Some(max) => max.as_u64().ok_or("field \"max_backoff\" must be an integer")?,
+                None => 100,
+            };
+            res = res.backoff(Duration::from_millis(backoff), Duration::from_millis(max));
+        }
+
+        Ok(box res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use {MetaLink, Output, Record};
+
+    use super::RetryOutput;
+
+    /// Fails with the given error kind the specified number of times, then succeeds.
+    struct FlakyOutput {
+        kind: ErrorKind,
+        failures: usize,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Output for FlakyOutput {
+        fn write(&self, _rec: &Record, _message: &[u8]) -> Result<(), Error> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                Err(Error::new(self.kind, "flaky"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn output(kind: ErrorKind, failures: usize) -> (RetryOutput, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let output = FlakyOutput {
+            kind: kind,
+            failures: failures,
+            calls: calls.clone(),
+        };
+        let output = RetryOutput::new(box output)
+            .backoff(Duration::from_millis(1), Duration::from_millis(2));
+
+        (output, calls)
+    }
+
+    #[test]
+    fn write_after_transient_failures() {
+        let (output, calls) = output(ErrorKind::Interrupted, 2);
+
+        let metalink = MetaLink::new(&[]);
+        output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap();
+
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn fail_write_after_attempts_exhausted() {
+        let (output, calls) = output(ErrorKind::WouldBlock, 10);
+        let output = output.attempts(2);
+
+        let metalink = MetaLink::new(&[]);
+        let err = output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+        assert_eq!(ErrorKind::WouldBlock, err.kind());
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn fail_write_immediately_on_permanent_error() {
+        let (output, calls) = output(ErrorKind::PermissionDenied, 1);
+
+        let metalink = MetaLink::new(&[]);
+        let err = output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap_err();
+
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+}
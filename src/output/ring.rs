@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::io::Error;
+use std::sync::{Arc, Mutex};
+
+use {Output, Record};
+
+/// Keeps the last N rendered messages in memory for introspection.
+///
+/// This is useful for services, that expose the most recent log lines via some kind of debug
+/// endpoint. Nothing is written anywhere else, older messages are just discarded.
+///
+/// The output is cheaply clonable, all clones share the same storage, so it's possible to keep
+/// one clone for reading while another one is owned by a handle.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::Output;
+/// use blacklog::output::InMemoryRingOutput;
+///
+/// fn main() {
+///     let output = InMemoryRingOutput::new(100);
+///     output.write(&record!(0), b"le message").unwrap();
+///
+///     assert_eq!(vec!["le message".to_owned()], output.lines());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct InMemoryRingOutput {
+    capacity: usize,
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl InMemoryRingOutput {
+    /// Constructs an output, that retains at most the given number of the most recent messages.
+    pub fn new(capacity: usize) -> InMemoryRingOutput {
+        InMemoryRingOutput {
+            capacity: capacity,
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Returns a snapshot of retained messages ordered from the oldest to the newest one.
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+impl Output for InMemoryRingOutput {
+    fn write(&self, _rec: &Record, message: &[u8]) -> Result<(), Error> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+
+        let line = String::from_utf8_lossy(message).into_owned();
+
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {MetaLink, Output, Record};
+
+    use super::InMemoryRingOutput;
+
+    #[test]
+    fn write_retains_last_lines() {
+        let output = InMemoryRingOutput::new(3);
+
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+        for id in 0..5 {
+            output.write(&rec, format!("message#{}", id).as_bytes()).unwrap();
+        }
+
+        assert_eq!(vec!["message#2", "message#3", "message#4"], output.lines());
+    }
+
+    #[test]
+    fn write_shared_between_clones() {
+        let output = InMemoryRingOutput::new(3);
+        let reader = output.clone();
+
+        let metalink = MetaLink::new(&[]);
+        output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap();
+
+        assert_eq!(vec!["message"], reader.lines());
+    }
+
+    #[test]
+    fn write_with_zero_capacity() {
+        let output = InMemoryRingOutput::new(0);
+
+        let metalink = MetaLink::new(&[]);
+        output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap();
+
+        assert!(output.lines().is_empty());
+    }
+}
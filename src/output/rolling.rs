@@ -0,0 +1,308 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::error;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use factory::Factory;
+use layout::Layout;
+use layout::pattern::{ParseError, PatternLayout};
+use output::Output;
+use output::file::Generation;
+use registry::{Config, Registry};
+use record::Record;
+
+/// A single file, that is rotated when its size exceeds the limit.
+struct Rolling {
+    path: PathBuf,
+    /// Currently opened file, if any. It's closed while rotating and reopened lazily.
+    file: Option<BufWriter<File>>,
+    /// Number of bytes written into the current file, including its initial size.
+    size: u64,
+}
+
+impl Rolling {
+    fn new(path: PathBuf) -> Rolling {
+        Rolling {
+            path: path,
+            file: None,
+            size: 0,
+        }
+    }
+
+    /// Returns the path of the backup with the given number, i.e. `app.log.1`.
+    fn backup(&self, id: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{}", id));
+        PathBuf::from(path)
+    }
+
+    fn open(&mut self, truncate: bool) -> Result<&mut BufWriter<File>, Error> {
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .append(!truncate)
+                .write(true)
+                .truncate(truncate)
+                .create(true)
+                .open(&self.path)?;
+
+            self.size = file.metadata()?.len();
+            self.file = Some(BufWriter::new(file));
+        }
+
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    fn rotate(&mut self, backups: usize) -> Result<(), Error> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+
+        if backups > 0 {
+            for id in (1..backups).rev() {
+                let path = self.backup(id);
+                if path.exists() {
+                    fs::rename(path, self.backup(id + 1))?;
+                }
+            }
+
+            fs::rename(&self.path, self.backup(1))?;
+        }
+
+        self.open(true)?;
+
+        Ok(())
+    }
+
+    fn write(&mut self, message: &[u8], max_size: u64, backups: usize) -> Result<(), Error> {
+        self.open(false)?;
+
+        let len = message.len() as u64 + 1;
+        if self.size > 0 && self.size + len > max_size {
+            self.rotate(backups)?;
+        }
+
+        {
+            let file = self.open(false)?;
+            file.write_all(message)?;
+            file.write_all(b"\n")?;
+        }
+
+        self.size += len;
+
+        Ok(())
+    }
+}
+
+/// Writes all messages into one or multiple files, rotating them by size.
+///
+/// The destination path is rendered per record using a pattern, exactly like `FileOutput` does,
+/// except that there is no fallback, so records missing meta attributes required by the pattern
+/// give an error.
+///
+/// When writing a message would make a file larger than the configured maximum size, the file is
+/// rotated first: `app.log.1` is renamed to `app.log.2` and so on up to the configured number of
+/// backups, the oldest backup is overwritten, the current file becomes `app.log.1` and a fresh
+/// empty file is created. With zero backups the file is just truncated. A message larger than the
+/// maximum size is still written into an empty file as is.
+///
+/// Sizes of existing files are taken into account when opening them, so rotation continues
+/// properly after restart.
+///
+/// # Note
+///
+/// Like `FileOutput`, the double locking strategy is used to enable concurrent writing into
+/// different files from multiple threads, and files are reopened after SIGHUP, if
+/// `FileOutput::reopen_on_sighup` has been called.
+pub struct SizeRollingOutput {
+    pattern: PatternLayout,
+    max_size: u64,
+    backups: usize,
+    files: Mutex<HashMap<PathBuf, Arc<Mutex<Rolling>>>>,
+    generation: Generation,
+}
+
+impl SizeRollingOutput {
+    /// Constructs an output, that rotates files when they exceed the given size in bytes, keeping
+    /// the given number of backups.
+    pub fn new(pattern: &str, max_size: u64, backups: usize) ->
+        Result<SizeRollingOutput, ParseError>
+    {
+        let res = SizeRollingOutput {
+            pattern: PatternLayout::new(pattern)?,
+            max_size: max_size,
+            backups: backups,
+            files: Mutex::new(HashMap::new()),
+            generation: Generation::new(),
+        };
+
+        Ok(res)
+    }
+
+    fn path(&self, rec: &Record) -> Result<PathBuf, Error> {
+        let mut buf = Vec::new();
+        self.pattern.format(rec, &mut buf)?;
+
+        let path = str::from_utf8(&buf)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+        let path = PathBuf::from(path);
+        validate(&path)?;
+
+        Ok(path)
+    }
+}
+
+fn validate(path: &Path) -> Result<(), Error> {
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(Error::new(ErrorKind::PermissionDenied,
+            format!("path traversal is not allowed: {}", path.display())));
+    }
+
+    Ok(())
+}
+
+impl Output for SizeRollingOutput {
+    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+        let path = self.path(rec)?;
+
+        let file = {
+            let mut files = self.files.lock().unwrap();
+
+            if self.generation.changed() {
+                // Dropped files are flushed and closed, sizes are taken again on reopening.
+                files.clear();
+            }
+
+            match files.entry(path) {
+                Entry::Occupied(v) => v.get().clone(),
+                Entry::Vacant(v) => {
+                    let file = Rolling::new(v.key().clone());
+                    v.insert(Arc::new(Mutex::new(file))).clone()
+                }
+            }
+        };
+
+        let mut file = file.lock().unwrap();
+        file.write(message, self.max_size, self.backups)
+    }
+}
+
+impl Factory for SizeRollingOutput {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "rolling.size"
+    }
+
+    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        let path = cfg.find("path")
+            .ok_or("field \"path\" is required")?
+            .as_string()
+            .ok_or("field \"path\" must be a string")?;
+
+        let max_size = cfg.find("max_size")
+            .ok_or("field \"max_size\" is required")?
+            .as_u64()
+            .ok_or("field \"max_size\" must be an integer")?;
+
+        let backups = match cfg.find("backups") {
+            Some(backups) => backups.as_u64().ok_or("field \"backups\" must be an integer")?,
+            None => 1,
+        };
+
+        Ok(box SizeRollingOutput::new(path, max_size, backups as usize)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    use {MetaLink, Output, Record};
+    use support::tempdir;
+
+    use super::SizeRollingOutput;
+
+    fn read(path: PathBuf) -> String {
+        let mut buf = String::new();
+        File::open(path).unwrap().read_to_string(&mut buf).unwrap();
+        buf
+    }
+
+    fn write(output: &SizeRollingOutput, message: &str) {
+        let metalink = MetaLink::new(&[]);
+        output.write(&Record::new(0, 0, "", &metalink), message.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn rotate_when_exceeded() {
+        let dir = tempdir("rolling-size");
+
+        {
+            let output = SizeRollingOutput::new(&format!("{}/app.log", dir.display()), 20, 2)
+                .unwrap();
+
+            for id in 1..7 {
+                write(&output, &format!("message#{}", id));
+            }
+        }
+
+        assert_eq!("message#5\nmessage#6\n", read(dir.join("app.log")));
+        assert_eq!("message#3\nmessage#4\n", read(dir.join("app.log.1")));
+        assert_eq!("message#1\nmessage#2\n", read(dir.join("app.log.2")));
+
+        write(&SizeRollingOutput::new(&format!("{}/app.log", dir.display()), 20, 2).unwrap(),
+            "message#7");
+
+        // The size of the existing file is taken into account after reopening.
+        assert_eq!("message#7\n", read(dir.join("app.log")));
+        assert_eq!("message#5\nmessage#6\n", read(dir.join("app.log.1")));
+        assert_eq!("message#3\nmessage#4\n", read(dir.join("app.log.2")));
+        assert!(!dir.join("app.log.3").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn truncate_without_backups() {
+        let dir = tempdir("rolling-size-no-backups");
+
+        {
+            let output = SizeRollingOutput::new(&format!("{}/app.log", dir.display()), 10, 0)
+                .unwrap();
+
+            write(&output, "message#1");
+            write(&output, "message#2");
+        }
+
+        assert_eq!("message#2\n", read(dir.join("app.log")));
+        assert!(!dir.join("app.log.1").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_oversized_message() {
+        let dir = tempdir("rolling-size-oversized");
+
+        {
+            let output = SizeRollingOutput::new(&format!("{}/app.log", dir.display()), 4, 1)
+                .unwrap();
+
+            write(&output, "message#1");
+            write(&output, "message#2");
+        }
+
+        assert_eq!("message#2\n", read(dir.join("app.log")));
+        assert_eq!("message#1\n", read(dir.join("app.log.1")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
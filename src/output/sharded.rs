@@ -0,0 +1,222 @@
+use std::error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use factory::Factory;
+use output::Output;
+use output::file::Generation;
+use registry::{Config, Registry};
+use record::Record;
+
+/// Writes all messages into a fixed set of files, distributing them by the thread id.
+///
+/// For extreme throughput a single file becomes a bottleneck, because every thread must acquire
+/// the same lock before writing. This output splits writes across N files, each with its own
+/// mutex, which significantly reduces lock contention.
+///
+/// Files are named by inserting a shard number before the extension, i.e. `app.log` gives
+/// `app.0.log`, `app.1.log` and so on. Records from the same thread always land in the same file,
+/// so their relative order is preserved.
+///
+/// Shard files are reopened after SIGHUP, if `FileOutput::reopen_on_sighup` has been called.
+pub struct ShardedFileOutput {
+    path: PathBuf,
+    shards: Vec<Mutex<BufWriter<File>>>,
+    generation: Generation,
+}
+
+impl ShardedFileOutput {
+    /// Opens (or creates) the given number of shard files derived from the given path.
+    pub fn new<P: AsRef<Path>>(path: P, shards: usize) -> Result<ShardedFileOutput, Error> {
+        if shards == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "number of shards must be positive"));
+        }
+
+        let shards = (0..shards)
+            .map(|id| open(path.as_ref(), id).map(Mutex::new))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let res = ShardedFileOutput {
+            path: path.as_ref().to_path_buf(),
+            shards: shards,
+            generation: Generation::new(),
+        };
+
+        Ok(res)
+    }
+
+    /// Flushes all shard files and reopens them by their paths.
+    ///
+    /// All shards are reopened even if some of them fail, in that case the first error is
+    /// returned and the failed shards continue writing into their old files.
+    pub fn reopen(&self) -> Result<(), Error> {
+        let mut result = Ok(());
+        for (id, file) in self.shards.iter().enumerate() {
+            let rc = open(&self.path, id).and_then(|wr| {
+                let mut file = file.lock().unwrap();
+                file.flush()?;
+                *file = wr;
+                Ok(())
+            });
+
+            if result.is_ok() {
+                result = rc;
+            }
+        }
+
+        result
+    }
+
+    fn select(&self, thread: usize) -> usize {
+        // Thread ids are usually aligned pointers, so mix all of their bits before taking the
+        // remainder.
+        let hash = (thread as u64).wrapping_mul(0x9e3779b97f4a7c15);
+        ((hash >> 32) % self.shards.len() as u64) as usize
+    }
+}
+
+fn open(path: &Path, id: usize) -> Result<BufWriter<File>, Error> {
+    let file = OpenOptions::new().append(true).create(true).open(shard(path, id))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Returns the path of the shard with the given id.
+fn shard(path: &Path, id: usize) -> PathBuf {
+    let name = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => {
+            format!("{}.{}.{}", stem.to_string_lossy(), id, ext.to_string_lossy())
+        }
+        (Some(stem), None) => format!("{}.{}", stem.to_string_lossy(), id),
+        (None, ..) => format!("{}", id),
+    };
+
+    path.with_file_name(name)
+}
+
+impl Output for ShardedFileOutput {
+    fn write(&self, rec: &Record, message: &[u8]) -> Result<(), Error> {
+        if self.generation.changed() {
+            if let Err(..) = self.reopen() {
+                // Shards failed to reopen keep writing into their old files.
+            }
+        }
+
+        let mut file = self.shards[self.select(rec.thread())].lock().unwrap();
+        file.write_all(message)?;
+        file.write_all(b"\n")
+    }
+}
+
+impl Factory for ShardedFileOutput {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "sharded_file"
+    }
+
+    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        let path = cfg.find("path")
+            .ok_or("field \"path\" is required")?
+            .as_string()
+            .ok_or("field \"path\" must be a string")?;
+
+        let shards = cfg.find("shards")
+            .ok_or("field \"shards\" is required")?
+            .as_u64()
+            .ok_or("field \"shards\" must be an unsigned integer")?;
+
+        Ok(box ShardedFileOutput::new(path, shards as usize)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    use {MetaLink, Output, Record};
+    use support::tempdir;
+
+    use super::{shard, ShardedFileOutput};
+
+    fn read(path: PathBuf) -> String {
+        let mut buf = String::new();
+        File::open(path).unwrap().read_to_string(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn shard_path() {
+        assert_eq!(Path::new("/var/log/app.0.log"), shard(Path::new("/var/log/app.log"), 0));
+        assert_eq!(Path::new("/var/log/app.3.log"), shard(Path::new("/var/log/app.log"), 3));
+        assert_eq!(Path::new("/var/log/app.1"), shard(Path::new("/var/log/app"), 1));
+    }
+
+    #[test]
+    fn fail_new_with_zero_shards() {
+        assert!(ShardedFileOutput::new("/tmp/app.log", 0).is_err());
+    }
+
+    #[test]
+    fn write_distributed() {
+        let dir = tempdir("sharded");
+
+        {
+            let output = Arc::new(ShardedFileOutput::new(dir.join("app.log"), 4).unwrap());
+
+            // Keep all threads alive at the same time to guarantee distinct thread ids.
+            let barrier = Arc::new(Barrier::new(16));
+            let threads = (0..16).map(|_| {
+                let output = output.clone();
+                let barrier = barrier.clone();
+
+                thread::spawn(move || {
+                    let metalink = MetaLink::new(&[]);
+                    output.write(&Record::new(0, 0, "", &metalink), b"message").unwrap();
+                    barrier.wait();
+                })
+            }).collect::<Vec<_>>();
+
+            for thread in threads {
+                thread.join().unwrap();
+            }
+        }
+
+        let counts = (0..4)
+            .map(|id| read(dir.join(format!("app.{}.log", id))).lines().count())
+            .collect::<Vec<_>>();
+
+        assert_eq!(16, counts.iter().sum::<usize>());
+        assert!(counts.iter().filter(|&&count| count > 0).count() > 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reopen_after_rename() {
+        let dir = tempdir("sharded-reopen");
+
+        {
+            let output = ShardedFileOutput::new(dir.join("app.log"), 1).unwrap();
+
+            let metalink = MetaLink::new(&[]);
+            let rec = Record::new(0, 0, "", &metalink);
+
+            output.write(&rec, b"message#1").unwrap();
+            fs::rename(dir.join("app.0.log"), dir.join("app.0.log.1")).unwrap();
+
+            output.reopen().unwrap();
+            output.write(&rec, b"message#2").unwrap();
+        }
+
+        assert_eq!("message#1\n", read(dir.join("app.0.log.1")));
+        assert_eq!("message#2\n", read(dir.join("app.0.log")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::error;
+use std::io::{Error, ErrorKind};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rusqlite::{self, Connection};
+use serde_json::{self, Value};
+
+use {Config, Format, Formatter, Output, Record, Registry};
+
+use factory::Factory;
+
+struct State {
+    conn: Connection,
+    /// Number of rows inserted within the currently open transaction.
+    pending: usize,
+    /// Time of the last commit.
+    flushed: Instant,
+}
+
+/// A structured output, that inserts a row per record into a SQLite table.
+///
+/// This makes logs queryable via SQL, which is handy for small tools. The table is created if it
+/// doesn't exist with the following columns: `timestamp` (RFC 3339 string), `severity`, `module`,
+/// `line`, `message` and `meta`. The latter is a JSON object with all meta attributes formatted as
+/// strings.
+///
+/// For throughput rows are inserted in batches within a transaction, which is committed either
+/// when the batch is full or when the flush interval has passed since the last commit. Note, that
+/// the interval is checked on writes only, so the last batch may stay uncommitted until the next
+/// record, an explicit flush or drop.
+///
+/// Available only with the `rusqlite` feature enabled.
+pub struct SqliteOutput {
+    table: String,
+    batch: usize,
+    interval: Duration,
+    state: Mutex<State>,
+}
+
+fn sql_error(err: rusqlite::Error) -> Error {
+    Error::new(ErrorKind::Other, format!("{}", err))
+}
+
+impl SqliteOutput {
+    /// Opens the database at the given path, creating the table if required.
+    pub fn open(path: &str, table: &str) -> Result<SqliteOutput, Error> {
+        SqliteOutput::new(Connection::open(path).map_err(sql_error)?, table)
+    }
+
+    /// Constructs an output using the given connection, creating the table if required.
+    ///
+    /// By default rows are committed in batches of 64 or at least once per second. The table name
+    /// must consist of ASCII alphanumeric characters and underscores only.
+    pub fn new(conn: Connection, table: &str) -> Result<SqliteOutput, Error> {
+        let valid = table.chars().all(|c| match c {
+            'a'...'z' | 'A'...'Z' | '0'...'9' | '_' => true,
+            _ => false,
+        });
+
+        if table.is_empty() || !valid {
+            return Err(Error::new(ErrorKind::InvalidInput,
+                format!("invalid table name \"{}\"", table)));
+        }
+
+        conn.execute_batch(&format!("CREATE TABLE IF NOT EXISTS {} (
+            timestamp TEXT NOT NULL,
+            severity INTEGER NOT NULL,
+            module TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            message TEXT NOT NULL,
+            meta TEXT NOT NULL
+        )", table)).map_err(sql_error)?;
+
+        let res = SqliteOutput {
+            table: table.into(),
+            batch: 64,
+            interval: Duration::from_secs(1),
+            state: Mutex::new(State {
+                conn: conn,
+                pending: 0,
+                flushed: Instant::now(),
+            }),
+        };
+
+        Ok(res)
+    }
+
+    /// Sets the maximum number of rows inserted within a single transaction.
+    pub fn batch(mut self, batch: usize) -> SqliteOutput {
+        self.batch = batch;
+        self
+    }
+
+    /// Sets the maximum time between commits.
+    pub fn interval(mut self, interval: Duration) -> SqliteOutput {
+        self.interval = interval;
+        self
+    }
+
+    /// Commits all pending rows.
+    pub fn flush(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        SqliteOutput::commit(&mut state)
+    }
+
+    fn commit(state: &mut State) -> Result<(), Error> {
+        if state.pending > 0 {
+            state.pending = 0;
+            state.conn.execute_batch("COMMIT").map_err(sql_error)?;
+        }
+
+        state.flushed = Instant::now();
+
+        Ok(())
+    }
+
+    fn meta(rec: &Record) -> Result<String, Error> {
+        let mut map = BTreeMap::new();
+
+        for meta in rec.iter() {
+            let mut buf = Vec::new();
+            meta.value.format(&mut Formatter::new(&mut buf, Default::default()))?;
+            let value = String::from_utf8(buf)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+            map.insert(meta.name.to_string(), Value::String(value));
+        }
+
+        serde_json::to_string(&Value::Object(map))
+            .map_err(|err| Error::new(ErrorKind::Other, format!("{}", err)))
+    }
+}
+
+impl Output for SqliteOutput {
+    fn write(&self, rec: &Record, _message: &[u8]) -> Result<(), Error> {
+        let meta = SqliteOutput::meta(rec)?;
+
+        let mut state = self.state.lock().unwrap();
+
+        if state.pending == 0 {
+            state.conn.execute_batch("BEGIN").map_err(sql_error)?;
+        }
+
+        state.pending += 1;
+        state.conn.execute(&format!("INSERT INTO {} VALUES (?, ?, ?, ?, ?, ?)", self.table), &[
+            &format!("{}", rec.datetime().format("%+")),
+            &rec.severity(),
+            &rec.module(),
+            &(rec.line() as i64),
+            &rec.message(),
+            &meta,
+        ]).map_err(sql_error)?;
+
+        if state.pending >= self.batch || state.flushed.elapsed() >= self.interval {
+            SqliteOutput::commit(&mut state)?;
+        }
+
+        Ok(())
+    }
+
+    fn structured(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for SqliteOutput {
+    fn drop(&mut self) {
+        if let Err(..) = self.flush() {
+            // Nowhere to report the error.
+        }
+    }
+}
+
+impl Factory for SqliteOutput {
+    type Item = Output;
+
+    fn ty() -> &'static str {
+        "sqlite"
+    }
+
+    fn from(cfg: &Config, _registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
+        let path = cfg.find("path")
+            .ok_or("field \"path\" is required")?
+            .as_string()
+            .ok_or("field \"path\" must be a string")?;
+
+        let table = match cfg.find("table") {
+            Some(table) => table.as_string().ok_or("field \"table\" must be a string")?,
+            None => "logs",
+        };
+
+        let mut res = SqliteOutput::open(path, table)?;
+
+        if let Some(batch) = cfg.find("batch") {
+            let batch = batch.as_u64().ok_or("field \"batch\" must be an integer")?;
+            res = res.batch(batch as usize);
+        }
+
+        if let Some(interval) = cfg.find("interval") {
+            let interval = interval.as_u64().ok_or("field \"interval\" must be an integer")?;
+            res = res.interval(Duration::from_millis(interval));
+        }
+
+        Ok(box res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use {Meta, MetaLink, Output, Record};
+
+    use super::SqliteOutput;
+
+    fn write(output: &SqliteOutput, sev: i32, message: &str) {
+        let path = "/var/www";
+        let meta = [Meta::new("path", &path)];
+        let metalink = MetaLink::new(&meta);
+        let mut rec = Record::new(sev, 42, "app", &metalink);
+        rec.activate(format_args!("{}", message));
+
+        output.write(&rec, &[]).unwrap();
+    }
+
+    fn count(output: &SqliteOutput) -> i64 {
+        let state = output.state.lock().unwrap();
+        state.conn.query_row("SELECT COUNT(*) FROM logs", &[], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn insert_and_query() {
+        let output = SqliteOutput::new(Connection::open_in_memory().unwrap(), "logs").unwrap();
+
+        write(&output, 1, "first");
+        write(&output, 2, "second");
+        write(&output, 4, "third");
+        output.flush().unwrap();
+
+        let state = output.state.lock().unwrap();
+        let mut stmt = state.conn
+            .prepare("SELECT severity, module, line, message, meta FROM logs WHERE severity >= 2")
+            .unwrap();
+        let rows = stmt.query_map(&[], |row| {
+            let sev: i32 = row.get(0);
+            let module: String = row.get(1);
+            let line: i64 = row.get(2);
+            let message: String = row.get(3);
+            let meta: String = row.get(4);
+            (sev, module, line, message, meta)
+        }).unwrap().map(|row| row.unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(2, rows.len());
+        assert_eq!((2, "app".to_string(), 42, "second".to_string(),
+            r#"{"path":"/var/www"}"#.to_string()), rows[0]);
+        assert_eq!(4, rows[1].0);
+        assert_eq!("third", rows[1].3);
+    }
+
+    #[test]
+    fn commit_on_full_batch() {
+        let output = SqliteOutput::new(Connection::open_in_memory().unwrap(), "logs").unwrap()
+            .batch(2);
+
+        write(&output, 0, "first");
+        write(&output, 0, "second");
+        write(&output, 0, "third");
+
+        assert_eq!(1, output.state.lock().unwrap().pending);
+        assert_eq!(3, count(&output));
+    }
+
+    #[test]
+    fn fail_invalid_table_name() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert!(SqliteOutput::new(conn, "logs; DROP TABLE logs").is_err());
+    }
+}
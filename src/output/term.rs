@@ -1,16 +1,36 @@
 use std::error;
-use std::io::Write;
+use std::io::{self, Stdout, Write};
 
 use {Config, Output, Record, Registry};
 
 use factory::Factory;
 
-pub struct Term;
+/// Writes all messages into the standard output.
+///
+/// The standard output handle is acquired once during construction and held for the output's
+/// lifetime, so each write only locks it. Note, that the handle is line buffered, so there is no
+/// need in explicit flushing.
+pub struct Term {
+    stdout: Stdout,
+}
+
+impl Term {
+    pub fn new() -> Term {
+        Term {
+            stdout: io::stdout(),
+        }
+    }
+}
+
+impl Default for Term {
+    fn default() -> Term {
+        Term::new()
+    }
+}
 
 impl Output for Term {
-    fn write(&self, _rec: &Record, message: &[u8]) -> Result<(), ::std::io::Error> {
-        let stdout = ::std::io::stdout();
-        let mut wr = stdout.lock();
+    fn write(&self, _rec: &Record, message: &[u8]) -> Result<(), io::Error> {
+        let mut wr = self.stdout.lock();
         wr.write_all(message)?;
         wr.write_all(b"\n")
     }
@@ -24,6 +44,6 @@ impl Factory for Term {
     }
 
     fn from(_cfg: &Config, _registry: &Registry) -> Result<Box<Output>, Box<error::Error>> {
-        Ok(box Term)
+        Ok(box Term::new())
     }
 }
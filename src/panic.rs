@@ -0,0 +1,88 @@
+use std::any::Any;
+use std::panic::{self, PanicInfo};
+use std::sync::Arc;
+use std::thread;
+
+use log::LogLevel;
+
+use {Backtrace, Logger, Meta, MetaLink, Record};
+
+/// Installs a panic hook, that logs panics through the given logger with `Error` severity.
+///
+/// The message of a record is the panic payload if it's a string. The line of a panic location is
+/// recorded as the record's line, while the source file and the thread name are attached as `file`
+/// and `thread` meta attributes respectively. The module of a record is left empty, because it's
+/// unknown.
+///
+/// The previously installed hook is called after logging, so by default panics are still printed
+/// into the standard error.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use blacklog::install_panic_hook;
+/// use blacklog::testing::TestLogger;
+///
+/// install_panic_hook(Arc::new(TestLogger::new()));
+/// ```
+pub fn install_panic_hook(logger: Arc<Logger + Sync>) {
+    install(logger, false)
+}
+
+/// Installs a panic hook, that logs panics with a captured stack backtrace attached as the
+/// `backtrace` meta attribute.
+///
+/// See `install_panic_hook` for details.
+pub fn install_panic_hook_with_backtrace(logger: Arc<Logger + Sync>) {
+    install(logger, true)
+}
+
+fn install(logger: Arc<Logger + Sync>, backtrace: bool) {
+    let prev = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        log_panic(&*logger, info, backtrace);
+        prev(info);
+    }));
+}
+
+fn payload(payload: &(Any + Send)) -> &str {
+    match payload.downcast_ref::<&'static str>() {
+        Some(message) => message,
+        None => {
+            match payload.downcast_ref::<String>() {
+                Some(message) => &message[..],
+                None => "Box<Any>",
+            }
+        }
+    }
+}
+
+fn log_panic(logger: &Logger, info: &PanicInfo, backtrace: bool) {
+    let (file, line) = match info.location() {
+        Some(location) => (location.file().to_string(), location.line()),
+        None => (String::new(), 0),
+    };
+
+    let thread = thread::current().name().unwrap_or("<unnamed>").to_string();
+
+    let meta = [
+        Meta::new("file", &file),
+        Meta::new("thread", &thread),
+    ];
+    let metalink = MetaLink::new(&meta);
+
+    let bt = Backtrace;
+    let with_backtrace = [Meta::new("backtrace", &bt)];
+    let extra = if backtrace {
+        &with_backtrace[..]
+    } else {
+        &with_backtrace[..0]
+    };
+    let metalink = MetaLink::with_link(extra, &metalink);
+
+    let mut rec = Record::new(LogLevel::Error, line, "", &metalink);
+    logger.log(&mut rec, format_args!("{}", payload(info.payload())));
+}
@@ -0,0 +1,43 @@
+//! Fast non-cryptographic random numbers generation.
+
+use std::cell::Cell;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use thread;
+
+thread_local!(static STATE: Cell<u64> = Cell::new(seed()));
+
+fn seed() -> u64 {
+    let duration = SystemTime::now().duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0));
+    let seed = duration.as_secs() ^ (duration.subsec_nanos() as u64) << 32 ^ thread::id() as u64;
+
+    // Xorshift state must never be zero.
+    if seed == 0 { 0x9e3779b97f4a7c15 } else { seed }
+}
+
+/// Returns the next pseudo-random number using thread-local xorshift64* generator.
+///
+/// Each thread has its own generator seeded with the current time and thread id, so there is no
+/// synchronization at all. Don't use it for anything related to security.
+pub fn next() -> u64 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+
+        x.wrapping_mul(0x2545f4914f6cdd1d)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next;
+
+    #[test]
+    fn next_differs() {
+        assert!(next() != next());
+    }
+}
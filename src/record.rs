@@ -1,34 +1,124 @@
 use std::fmt::Arguments;
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::slice;
 
 use chrono::{DateTime, UTC};
 use chrono::naive::datetime::NaiveDateTime;
 
-use {MetaBuf, MetaLink};
+use {ByteSize, MetaBuf, MetaLink};
 
 use meta::{Meta, MetaLinkIter};
-use meta::format::Formatter;
+use meta::format::{Format, FormatInto, Formatter};
 use severity::Severity;
 
 /// Logging event context contains an information about where the event was created including the
 /// source code location and thread id.
+///
+/// The context is captured once at record creation and is never changed afterwards. Its getters,
+/// like the getters of `Record`, are considered stable and can be relied on by external layouts.
+///
+/// The only exception is the thread id, which is resolved lazily, because many loggers never use
+/// it. Records can't leave the thread they were created in until activated, so the id is resolved
+/// on demand until then, and captured on activation.
 #[derive(Debug, Copy, Clone)]
-struct Context {
+pub struct Context {
     /// The line number on which the logging event was created.
     line: u32,
     /// The module path where the logging event was created.
     module: &'static str,
-    /// The thread id where the logging event was created.
+    /// The source file where the logging event was created.
+    file: &'static str,
+    /// The thread id where the logging event was created, zero if not captured yet.
     thread: usize,
 }
 
 impl Context {
     #[inline]
-    fn new(line: u32, module: &'static str) -> Self {
+    fn new(line: u32, module: &'static str, file: &'static str) -> Self {
         Context {
             line: line,
             module: module,
-            thread: super::thread::id(),
+            file: file,
+            thread: 0,
+        }
+    }
+
+    /// Returns a copy of this context with the thread id captured.
+    #[inline]
+    fn resolved(&self) -> Context {
+        Context {
+            thread: self.thread(),
+            .. *self
+        }
+    }
+
+    /// Returns the line number on which the logging event was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let rec = Record::new(0, 42, "app", &metalink);
+    ///
+    /// assert_eq!(42, rec.context().line());
+    /// ```
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns the module path where the logging event was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let rec = Record::new(0, 42, "app", &metalink);
+    ///
+    /// assert_eq!("app", rec.context().module());
+    /// ```
+    pub fn module(&self) -> &'static str {
+        self.module
+    }
+
+    /// Returns the source file path where the logging event was created.
+    ///
+    /// The path is empty if the record was created without specifying a file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let rec = Record::new_with_file(0, 42, "app", "src/main.rs", &metalink);
+    ///
+    /// assert_eq!("src/main.rs", rec.context().file());
+    /// ```
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// Returns the thread id where the logging event was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let rec = Record::new(0, 42, "app", &metalink);
+    ///
+    /// assert_eq!(rec.thread(), rec.context().thread());
+    /// ```
+    pub fn thread(&self) -> usize {
+        match self.thread {
+            0 => super::thread::id(),
+            thread => thread,
         }
     }
 }
@@ -52,7 +142,8 @@ pub struct Record<'a> {
     sev: i32,
     // TODO: Not sure about naming.
     sevfn: fn(i32, &mut Formatter) -> Result<(), ::std::io::Error>,
-    message: Cow<'static, str>,
+    /// Formatted message, which is borrowed by records passed through `with_meta`.
+    message: Cow<'a, str>,
     timestamp: Option<DateTime<UTC>>,
     context: Context,
     metalink: &'a MetaLink<'a>, // TODO: Naming?
@@ -65,7 +156,7 @@ fn sevfn<T: Severity>(sev: i32, format: &mut Formatter) -> Result<(), ::std::io:
 #[macro_export]
 macro_rules! record (
     ($sev:expr, {$($name:ident: $val:expr,)*}) => {
-        $crate::Record::new($sev, line!(), module_path!(),
+        $crate::Record::new_with_file($sev, line!(), module_path!(), file!(),
             &$crate::MetaLink::new(&[
                 $($crate::Meta::new(stringify!($name), &$val)),*
             ])
@@ -79,48 +170,197 @@ macro_rules! record (
 impl<'a> Record<'a> {
     pub fn new<T>(sev: T, line: u32, module: &'static str, metalink: &'a MetaLink<'a>) -> Record<'a>
         where T: Severity + 'static
+    {
+        Record::new_with_file(sev, line, module, "", metalink)
+    }
+
+    /// Constructs a new inactive record, that also knows the source file it was created in.
+    ///
+    /// This is what logging macros use, passing `file!()` alongside `line!()` and
+    /// `module_path!()`.
+    pub fn new_with_file<T>(sev: T, line: u32, module: &'static str, file: &'static str,
+                            metalink: &'a MetaLink<'a>) -> Record<'a>
+        where T: Severity + 'static
     {
         Record {
             sev: sev.as_i32(),
             sevfn: sevfn::<T>,
             message: Cow::Borrowed(""),
             timestamp: None,
-            context: Context::new(line, module),
+            context: Context::new(line, module, file),
             metalink: metalink,
         }
     }
 
     /// Returns a severity number as `i32` that was set during this record creation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let rec = Record::new(2, 42, "app", &metalink);
+    ///
+    /// assert_eq!(2, rec.severity());
+    /// ```
     pub fn severity(&self) -> i32 {
         self.sev
     }
 
     // TODO: Not sure about naming. Maybe better to return severity object with .num() and format()
     //       methods.
+    /// Returns the function, that formats severities using the severity type this record was
+    /// created with.
+    ///
+    /// Layouts shouldn't call it directly, but either use `severity_name` or format severities
+    /// through a `SevMap`, which allows context-aware severity names.
+    #[deprecated(since = "0.2.0",
+                 note = "format severities through `SevMap` or use `severity_name` instead")]
     pub fn severity_format(&self) -> fn(i32, &mut Formatter) -> Result<(), ::std::io::Error> {
         self.sevfn
     }
 
+    /// Returns a string representation of the severity, rendered by the severity type this record
+    /// was created with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate blacklog;
+    /// extern crate log;
+    ///
+    /// use blacklog::{MetaLink, Record};
+    /// use log::LogLevel;
+    ///
+    /// fn main() {
+    ///     let metalink = MetaLink::new(&[]);
+    ///     let rec = Record::new(LogLevel::Warn, 42, "app", &metalink);
+    ///
+    ///     assert_eq!("Warn", rec.severity_name());
+    /// }
+    /// ```
+    pub fn severity_name(&self) -> Cow<str> {
+        let mut buf = Vec::with_capacity(8);
+
+        if (self.sevfn)(self.sev, &mut Formatter::new(&mut buf, Default::default())).is_err() {
+            return Cow::Owned(self.sev.to_string());
+        }
+
+        match String::from_utf8(buf) {
+            Ok(name) => Cow::Owned(name),
+            Err(err) => Cow::Owned(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        }
+    }
+
+    /// Returns a formatted message.
+    ///
+    /// The message is empty until the record is activated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let mut rec = Record::new(0, 42, "app", &metalink);
+    /// assert_eq!("", rec.message());
+    ///
+    /// rec.activate(format_args!("le message"));
+    /// assert_eq!("le message", rec.message());
+    /// ```
     pub fn message(&self) -> &str {
         &self.message
     }
 
+    /// Returns `true` if this record has been activated, i.e. its message is formatted.
+    ///
+    /// Loggers activate records before passing them to handles, so the message of an inactive
+    /// record is always empty. Layouts may use this to tell a missing message from an empty one.
+    /// Note, that `restamp` makes a record active as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let mut rec = Record::new(0, 42, "app", &metalink);
+    /// assert!(!rec.is_active());
+    ///
+    /// rec.activate(format_args!("le message"));
+    /// assert!(rec.is_active());
+    /// ```
+    pub fn is_active(&self) -> bool {
+        self.timestamp.is_some()
+    }
+
+    /// Returns a timestamp of the record activation.
+    ///
+    /// For inactive records the Unix epoch is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let mut rec = Record::new(0, 42, "app", &metalink);
+    /// assert_eq!(0, rec.datetime().timestamp());
+    ///
+    /// rec.activate(format_args!("le message"));
+    /// assert!(rec.datetime().timestamp() > 0);
+    /// ```
     pub fn datetime(&self) -> DateTime<UTC> {
         self.timestamp.unwrap_or_else(|| {
             DateTime::from_utc(NaiveDateTime::from_timestamp(0, 0), UTC)
         })
     }
 
+    /// Returns the context where this record was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blacklog::{MetaLink, Record};
+    ///
+    /// let metalink = MetaLink::new(&[]);
+    /// let rec = Record::new(0, 42, "app", &metalink);
+    /// let context = rec.context();
+    ///
+    /// assert_eq!(rec.line(), context.line());
+    /// assert_eq!(rec.module(), context.module());
+    /// ```
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Returns the line number on which this record was created.
+    ///
+    /// This is a shortcut for `rec.context().line()`.
     pub fn line(&self) -> u32 {
         self.context.line
     }
 
+    /// Returns the module path where this record was created.
+    ///
+    /// This is a shortcut for `rec.context().module()`.
     pub fn module(&self) -> &'static str {
         self.context.module
     }
 
+    /// Returns the source file path where this record was created.
+    ///
+    /// This is a shortcut for `rec.context().file()`.
+    pub fn file(&self) -> &'static str {
+        self.context.file
+    }
+
+    /// Returns the thread id where this record was created.
+    ///
+    /// This is a shortcut for `rec.context().thread()`.
     pub fn thread(&self) -> usize {
-        self.context.thread
+        self.context.thread()
     }
 
     /// Returns an iterator over the meta attributes of a record.
@@ -132,15 +372,148 @@ impl<'a> Record<'a> {
         self.metalink.iter()
     }
 
+    /// Collects the meta attributes of a record into a map by their names.
+    ///
+    /// This is intended for layouts and outputs, that look up many distinct attributes, where
+    /// building the map once is cheaper than searching through the list for each name. Attributes
+    /// with the same name override each other in the iteration order, i.e. the last one wins.
+    pub fn meta_map(&self) -> HashMap<&'static str, &'a FormatInto> {
+        self.iter().map(|meta| (meta.name, meta.value)).collect()
+    }
+
+    /// Calls the given function with a copy of this record, that has the given meta attributes
+    /// appended to its own ones.
+    ///
+    /// This allows to attach additional meta information, which lives shorter than the record
+    /// itself. The copy borrows the message of this record, so nothing is allocated. Note, that
+    /// activating the copy doesn't activate this record.
+    pub fn with_meta<F>(&self, meta: &[Meta], f: F)
+        where F: FnOnce(&mut Record)
+    {
+        self.with_metalink(&MetaLink::with_link(meta, self.metalink), f)
+    }
+
+    /// Calls the given function with a copy of this record, that has the given meta attributes
+    /// prepended to its own ones, i.e. they come first while iterating.
+    ///
+    /// Unlike `with_meta`, this copies references to all attributes of the record into a new
+    /// list, because links can't be inserted before the borrowed ones.
+    pub fn with_meta_prepended<F>(&self, meta: &[Meta], f: F)
+        where F: FnOnce(&mut Record)
+    {
+        let meta = meta.iter().chain(self.iter()).cloned().collect::<Vec<Meta>>();
+        self.with_metalink(&MetaLink::new(&meta), f)
+    }
+
+    /// Calls the given function with a copy of this record, that has the given meta attributes
+    /// instead of its own ones.
+    ///
+    /// This allows to substitute attributes, for example to decorate their values.
+    pub fn with_metalink<F>(&self, metalink: &MetaLink, f: F)
+        where F: FnOnce(&mut Record)
+    {
+        let mut rec = Record {
+            sev: self.sev,
+            sevfn: self.sevfn,
+            message: Cow::Borrowed(&*self.message),
+            timestamp: self.timestamp,
+            context: self.context,
+            metalink: metalink,
+        };
+
+        f(&mut rec)
+    }
+
+    /// Activates this record by formatting its message and capturing the current time.
+    ///
+    /// Activating an already active record formats the message again, but keeps its timestamp, so
+    /// a record passed through several loggers is seen with the same time by all of them. Use
+    /// `restamp` to update the timestamp explicitly.
     pub fn activate<'b>(&mut self, format: Arguments<'b>) {
+        self.activate_with_limit(format, None)
+    }
+
+    /// Activates this record like `activate`, but limits the formatted message to the given
+    /// number of bytes.
+    ///
+    /// Longer messages are cut on the nearest UTF-8 character boundary not exceeding the limit,
+    /// followed by a marker with the size of the dropped part, like `…[truncated 4.0 KiB]`. Note,
+    /// that the marker itself is not counted in the limit.
+    pub fn activate_with_limit<'b>(&mut self, format: Arguments<'b>, limit: Option<usize>) {
         // TODO: Performance!
-        self.message = Cow::Owned(format!("{}", format));
+        let mut message = format!("{}", format);
+
+        if let Some(limit) = limit {
+            truncate(&mut message, limit);
+        }
+
+        self.message = Cow::Owned(message);
+        self.context = self.context.resolved();
+
+        if self.timestamp.is_none() {
+            self.timestamp = Some(UTC::now());
+        }
+    }
+
+    /// Replaces the timestamp of this record with the current time.
+    ///
+    /// Asynchronous loggers use it to implement `Timestamping::Handling` policy. Note, that this
+    /// also makes an inactive record to appear active, but with an empty message.
+    pub fn restamp(&mut self) {
         self.timestamp = Some(UTC::now());
     }
 }
 
+/// Truncates the given message to at most `limit` bytes, appending the truncation marker.
+fn truncate(message: &mut String, limit: usize) {
+    if message.len() <= limit {
+        return;
+    }
+
+    let mut end = limit;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let dropped = ByteSize((message.len() - end) as u64);
+    message.truncate(end);
+
+    let mut buf = Vec::new();
+    dropped.format(&mut Formatter::new(&mut buf, Default::default()))
+        .expect("writing into a vector never fails");
+
+    message.push_str("…[truncated ");
+    message.push_str(&String::from_utf8_lossy(&buf));
+    message.push(']');
+}
+
+/// Describes the point in time, which is captured as a record timestamp.
+///
+/// Synchronous loggers handle records right where they are logged, so both policies are
+/// equivalent for them. Asynchronous loggers may handle records significantly later, for example
+/// because of the queue backlog.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Timestamping {
+    /// The timestamp reflects the time the event occurred, i.e. the time of the logging call.
+    ///
+    /// This is the default policy, because it's required for audit logs.
+    Event,
+    /// The timestamp reflects the time the record is passed to handles.
+    Handling,
+}
+
+impl Default for Timestamping {
+    fn default() -> Timestamping {
+        Timestamping::Event
+    }
+}
+
 // TODO: impl ExactSizeIterator, DoubleEndedIterator, IntoIterator, FromIterator.
 
+/// Owned logging event, which is detached from the stack frame it was created in.
+///
+/// Owned records are obtained from activated records and are useful for deferred processing, like
+/// queueing or batching. Its getters mirror the ones of `Record`.
 pub struct RecordBuf {
     timestamp: DateTime<UTC>,
     sev: i32,
@@ -152,6 +525,54 @@ pub struct RecordBuf {
 }
 
 impl RecordBuf {
+    /// Returns a severity number as `i32` that was set during the original record creation.
+    pub fn severity(&self) -> i32 {
+        self.sev
+    }
+
+    /// Returns a formatted message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns a timestamp of the original record activation.
+    pub fn datetime(&self) -> DateTime<UTC> {
+        self.timestamp
+    }
+
+    /// Returns the context where the original record was created.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Returns the line number on which the original record was created.
+    pub fn line(&self) -> u32 {
+        self.context.line
+    }
+
+    /// Returns the module path where the original record was created.
+    pub fn module(&self) -> &'static str {
+        self.context.module
+    }
+
+    /// Returns the source file path where the original record was created.
+    pub fn file(&self) -> &'static str {
+        self.context.file
+    }
+
+    /// Returns the thread id where the original record was created.
+    pub fn thread(&self) -> usize {
+        self.context.thread
+    }
+
+    /// Returns an iterator over the meta attributes in the same order as `Record::iter` does.
+    pub fn meta_iter(&self) -> MetaBufIter {
+        MetaBufIter {
+            iter: self.meta.iter(),
+        }
+    }
+
+    /// Calls the given function with a borrowed record, which is built from this one.
     pub fn borrow_and<F: Fn(&mut Record)>(&self, f: F) {
         let meta = self.meta.iter().map(Into::into).collect::<Vec<Meta>>();
         let metalink = MetaLink::new(&meta);
@@ -169,14 +590,27 @@ impl RecordBuf {
     }
 }
 
+/// An iterator over the meta attributes of an owned record.
+pub struct MetaBufIter<'a> {
+    iter: slice::Iter<'a, MetaBuf>,
+}
+
+impl<'a> Iterator for MetaBufIter<'a> {
+    type Item = Meta<'a>;
+
+    fn next(&mut self) -> Option<Meta<'a>> {
+        self.iter.next().map(Into::into)
+    }
+}
+
 impl<'a> From<&'a Record<'a>> for RecordBuf {
     fn from(val: &'a Record<'a>) -> RecordBuf {
         RecordBuf {
             timestamp: val.timestamp.unwrap(),
             sev: val.sev,
             sevfn: val.sevfn,
-            context: val.context,
-            message: val.message.clone(),
+            context: val.context.resolved(),
+            message: Cow::Owned(val.message.clone().into_owned()),
             meta: From::from(val.metalink),
         }
     }
@@ -184,7 +618,12 @@ impl<'a> From<&'a Record<'a>> for RecordBuf {
 
 #[cfg(test)]
 mod tests {
+    use std::iter;
+
+    use log::LogLevel;
+
     use {Meta, MetaLink};
+    use meta::format::{Format, FormatInto, Formatter};
     use super::*;
 
     #[test]
@@ -192,6 +631,14 @@ mod tests {
         assert_eq!(0, Record::new(0, 0, "", &MetaLink::new(&[])).severity());
     }
 
+    #[test]
+    fn severity_name() {
+        let metalink = MetaLink::new(&[]);
+
+        assert_eq!("Error", Record::new(LogLevel::Error, 0, "", &metalink).severity_name());
+        assert_eq!("4", Record::new(4, 0, "", &metalink).severity_name());
+    }
+
     #[test]
     fn iter() {
         assert_eq!(4, Record::new(0, 0, "", &MetaLink::new(&[
@@ -202,6 +649,72 @@ mod tests {
         ])).iter().count());
     }
 
+    #[test]
+    fn meta_map_last_wins() {
+        fn format(value: &FormatInto) -> String {
+            let mut buf = Vec::new();
+            value.format(&mut Formatter::new(&mut buf, Default::default())).unwrap();
+            String::from_utf8(buf).unwrap()
+        }
+
+        let metalink = MetaLink::new(&[
+            Meta::new("n#1", &"v#1"),
+            Meta::new("n#2", &"v#2"),
+        ]);
+        let meta = [Meta::new("n#2", &"v#3"), Meta::new("n#3", &"v#4")];
+
+        Record::new(0, 0, "", &metalink).with_meta(&meta, |rec| {
+            let map = rec.meta_map();
+
+            assert_eq!(3, map.len());
+            assert_eq!("v#1", format(map["n#1"]));
+            assert_eq!("v#3", format(map["n#2"]));
+            assert_eq!("v#4", format(map["n#3"]));
+        });
+    }
+
+    #[test]
+    fn activate_with_limit() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        let message = iter::repeat('x').take(100 * 1024).collect::<String>();
+        rec.activate_with_limit(format_args!("{}", message), Some(1024));
+
+        let marker = "…[truncated 99.0 KiB]";
+        assert!(rec.message().ends_with(marker));
+        // The marker is not counted in the limit.
+        assert_eq!(1024 + marker.len(), rec.message().len());
+        assert_eq!(1024, rec.message().chars().take_while(|&ch| ch == 'x').count());
+    }
+
+    #[test]
+    fn activate_with_limit_on_char_boundary() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate_with_limit(format_args!("日本語"), Some(4));
+
+        assert_eq!("日…[truncated 6 B]", rec.message());
+    }
+
+    #[test]
+    fn activate_with_limit_keeps_short_message() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate_with_limit(format_args!("le message"), Some(10));
+
+        assert_eq!("le message", rec.message());
+    }
+
+    #[test]
+    fn is_active() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        assert!(!rec.is_active());
+
+        rec.activate(format_args!(""));
+        assert!(rec.is_active());
+    }
+
     #[test]
     fn iter_with_nested_lists() {
         fn run(rec: &Record) {
@@ -223,6 +736,93 @@ mod tests {
         run(&Record::new(0, 0, "", &metalink2));
     }
 
+    #[test]
+    fn with_meta() {
+        let v = 42;
+        let meta1 = &[Meta::new("n#1", &v)];
+        let metalink = MetaLink::new(meta1);
+        let rec = Record::new(1, 2, "mod", &metalink);
+
+        let meta2 = &[Meta::new("n#2", &v)];
+        let mut called = false;
+        rec.with_meta(meta2, |rec| {
+            assert_eq!(1, rec.severity());
+            assert_eq!(2, rec.line());
+
+            let mut iter = rec.iter();
+            assert_eq!("n#1", iter.next().unwrap().name);
+            assert_eq!("n#2", iter.next().unwrap().name);
+            assert!(iter.next().is_none());
+
+            called = true;
+        });
+
+        assert!(called);
+        assert_eq!(1, rec.iter().count());
+    }
+
+    #[test]
+    fn with_meta_prepended() {
+        let v = 42;
+        let meta1 = &[Meta::new("n#1", &v)];
+        let metalink1 = MetaLink::new(meta1);
+        let meta2 = &[Meta::new("n#2", &v)];
+        let metalink2 = MetaLink::with_link(meta2, &metalink1);
+        let rec = Record::new(1, 2, "mod", &metalink2);
+
+        let meta0 = &[Meta::new("n#0", &v)];
+        let mut called = false;
+        rec.with_meta_prepended(meta0, |rec| {
+            assert_eq!(1, rec.severity());
+
+            let names = rec.iter().map(|meta| meta.name).collect::<Vec<_>>();
+            assert_eq!(vec!["n#0", "n#1", "n#2"], names);
+
+            called = true;
+        });
+
+        assert!(called);
+        assert_eq!(2, rec.iter().count());
+    }
+
+    #[test]
+    fn thread_of_inactive_record() {
+        let metalink = MetaLink::new(&[]);
+        let rec = Record::new(0, 0, "", &metalink);
+
+        assert_eq!(::thread::id(), rec.thread());
+        assert_eq!(::thread::id(), rec.context().thread());
+    }
+
+    #[test]
+    fn thread_captured_on_activation() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+        rec.activate(format_args!("message"));
+
+        let owned = RecordBuf::from(&rec);
+        let thread = ::std::thread::spawn(move || {
+            owned.borrow_and(|rec| assert!(rec.thread() != ::thread::id()));
+            owned.thread()
+        }).join().unwrap();
+
+        assert_eq!(::thread::id(), thread);
+    }
+
+    #[test]
+    fn activate_twice_keeps_timestamp() {
+        let metalink = MetaLink::new(&[]);
+        let mut rec = Record::new(0, 0, "", &metalink);
+
+        rec.activate(format_args!("message"));
+        let timestamp = rec.datetime();
+
+        ::std::thread::sleep(::std::time::Duration::from_millis(1));
+        rec.activate(format_args!("message"));
+
+        assert_eq!(timestamp, rec.datetime());
+    }
+
     #[test]
     fn to_owned() {
         let v = 42;
@@ -247,4 +847,28 @@ mod tests {
             assert_eq!("n#2", iter.next().unwrap().name);
         });
     }
+
+    #[test]
+    fn owned_getters() {
+        let v = 42;
+        let meta = &[Meta::new("n#1", &v), Meta::new("n#2", &v)];
+        let metalist = MetaLink::new(meta);
+
+        let mut rec = Record::new(1, 2, "mod", &metalist);
+        rec.activate(format_args!("message"));
+
+        let owned = RecordBuf::from(&rec);
+        drop(rec);
+
+        assert_eq!(1, owned.severity());
+        assert_eq!("message", owned.message());
+        assert!(owned.datetime().timestamp() > 0);
+        assert_eq!(2, owned.line());
+        assert_eq!("mod", owned.module());
+        assert_eq!(::thread::id(), owned.thread());
+        assert_eq!(owned.line(), owned.context().line());
+
+        let names = owned.meta_iter().map(|meta| meta.name).collect::<Vec<_>>();
+        assert_eq!(vec!["n#1", "n#2"], names);
+    }
 }
@@ -1,15 +1,22 @@
 use std::collections::HashMap;
 use std::error::Error;
+use std::io::Read;
 
-use serde_json::Value;
+use log::LogLevel;
+use serde_json::{self, Value};
 
-use {Handle, Layout, Logger, Output};
+use {Filter, Handle, Layout, Logger, Output, Severity};
 
 use factory::Factory;
-use layout::{PatternLayout};
-use logger::{SyncLogger};
-use output::{FileOutput, NullOutput, Term};
-use handle::{SyncHandle};
+use filter::{ThrottleFilter, TimeWindowFilter};
+use layout::{AccessLogLayout, CombinedLayout, JsonLayout, LogfmtLayout, PatternLayout,
+             RelativeTimeLayout};
+use logger::{SyncLogger, TeeLogger};
+use output::{AsyncOutput, FileOutput, MultiOutput, NullOutput, PipeOutput, RetryOutput,
+             ShardedFileOutput, SizeRollingOutput, StdoutJson, Term};
+use handle::{AsyncHandle, SyncHandle};
+#[cfg(feature="rusqlite")]
+use output::SqliteOutput;
 
 pub type Config = Value;
 
@@ -17,6 +24,9 @@ type FnFactory<T> = Fn(&Config, &Registry) -> Result<Box<T>, Box<Error>>;
 
 #[derive(Default)]
 pub struct Registry {
+    /// Layout configs shared between components, which refer them by name.
+    named_layouts: HashMap<String, Config>,
+    filters: HashMap<&'static str, Box<FnFactory<Filter>>>,
     layouts: HashMap<&'static str, Box<FnFactory<Layout>>>,
     outputs: HashMap<&'static str, Box<FnFactory<Output>>>,
     handles: HashMap<&'static str, Box<FnFactory<Handle>>>,
@@ -27,19 +37,42 @@ impl Registry {
     pub fn new() -> Registry {
         let mut result = Registry::default();
 
+        result.add_filter::<ThrottleFilter>();
+        result.add_filter::<TimeWindowFilter>();
+
+        result.add_layout::<AccessLogLayout>();
+        result.add_layout::<CombinedLayout>();
+        result.add_layout::<JsonLayout>();
+        result.add_layout::<LogfmtLayout>();
         result.add_layout::<PatternLayout>();
+        result.add_layout::<RelativeTimeLayout>();
 
+        result.add_output::<AsyncOutput>();
         result.add_output::<FileOutput>();
+        result.add_output::<MultiOutput>();
         result.add_output::<NullOutput>();
+        result.add_output::<PipeOutput>();
+        result.add_output::<RetryOutput>();
+        result.add_output::<ShardedFileOutput>();
+        result.add_output::<SizeRollingOutput>();
+        #[cfg(feature="rusqlite")]
+        result.add_output::<SqliteOutput>();
+        result.add_output::<StdoutJson>();
         result.add_output::<Term>();
 
+        result.add_handle::<AsyncHandle>();
         result.add_handle::<SyncHandle>();
 
         result.add_logger::<SyncLogger>();
+        result.add_logger::<TeeLogger>();
 
         result
     }
 
+    fn add_filter<T: Factory<Item=Filter> + 'static>(&mut self) {
+        Registry::add_component::<T, Filter>(&mut self.filters);
+    }
+
     fn add_layout<T: Factory<Item=Layout> + 'static>(&mut self) {
         Registry::add_component::<T, Layout>(&mut self.layouts);
     }
@@ -64,7 +97,45 @@ impl Registry {
         });
     }
 
+    pub fn filter(&self, cfg: &Config) -> Result<Box<Filter>, Box<Error>> {
+        let ty = Registry::ty(cfg)?;
+        let func = self.filters.get(ty)
+            .ok_or_else(|| format!("filter \"{}\" not found", ty))?;
+        func(cfg, self)
+    }
+
+    /// Registers named layouts from the `"layouts"` section of the given config.
+    ///
+    /// The section must be an object, which maps layout names to their configs. After that any
+    /// layout config may be replaced with a reference like `{"ref": "name"}`, which allows to share
+    /// a single layout between several handles or outputs, for example a message layout of a
+    /// handle and a path pattern of a file output.
+    pub fn with_layouts(mut self, cfg: &Config) -> Result<Registry, Box<Error>> {
+        let layouts = cfg.find("layouts")
+            .ok_or("section \"layouts\" is required")?
+            .as_object()
+            .ok_or("section \"layouts\" must be an object")?;
+
+        for (name, cfg) in layouts {
+            // Prohibit references to references to avoid cycles.
+            if cfg.find("ref").is_some() {
+                return Err(format!("named layout \"{}\" must not be a reference", name).into());
+            }
+
+            self.named_layouts.insert(name.clone(), cfg.clone());
+        }
+
+        Ok(self)
+    }
+
     pub fn layout(&self, cfg: &Config) -> Result<Box<Layout>, Box<Error>> {
+        if let Some(name) = cfg.find("ref") {
+            let name = name.as_string().ok_or("field \"ref\" must be a string")?;
+            let cfg = self.named_layouts.get(name)
+                .ok_or_else(|| format!("named layout \"{}\" not found", name))?;
+            return self.layout(cfg);
+        }
+
         let ty = Registry::ty(cfg)?;
         let func = self.layouts.get(ty)
             .ok_or_else(|| format!("layout \"{}\" not found", ty))?;
@@ -92,7 +163,59 @@ impl Registry {
         func(cfg, self)
     }
 
-    // TODO: fn filter(&self, cfg: &Config) -> Result<Box<Filter>, Box<Error>>;
+    /// Constructs named loggers from the `"loggers"` section of the given config.
+    ///
+    /// The section must be an object, which maps logger names to their configs. This allows a
+    /// single document to describe the whole logging topology, for example a logger per subsystem.
+    pub fn loggers(&self, cfg: &Config) -> Result<HashMap<String, Box<Logger>>, Box<Error>> {
+        let loggers = cfg.find("loggers")
+            .ok_or("section \"loggers\" is required")?
+            .as_object()
+            .ok_or("section \"loggers\" must be an object")?;
+
+        let mut result = HashMap::new();
+        for (name, cfg) in loggers {
+            let logger = self.logger(cfg)
+                .map_err(|err| format!("failed to construct logger \"{}\": {}", name, err))?;
+            result.insert(name.clone(), logger);
+        }
+
+        Ok(result)
+    }
+
+    /// Constructs a logger from the JSON config read from the given reader.
+    pub fn logger_from_reader<R: Read>(&self, rd: R) -> Result<Box<Logger>, Box<Error>> {
+        let cfg: Config = serde_json::from_reader(rd)?;
+        self.logger(&cfg)
+    }
+
+    /// Constructs a logger from the given JSON config string.
+    pub fn logger_from_str(&self, cfg: &str) -> Result<Box<Logger>, Box<Error>> {
+        let cfg: Config = serde_json::from_str(cfg)?;
+        self.logger(&cfg)
+    }
+
+    /// Parses a severity level from the given config value into its numeric representation.
+    ///
+    /// Levels can be specified either as numbers or as case-insensitive names, i.e. `trace`,
+    /// `debug`, `info`, `warn` and `error`, which are mapped using `log::LogLevel` severities.
+    pub fn level(cfg: &Config) -> Result<i32, Box<Error>> {
+        if let Some(level) = cfg.as_i64() {
+            return Ok(level as i32);
+        }
+
+        let level = cfg.as_string()
+            .ok_or("level must be either a number or a string")?;
+
+        if let Ok(level) = level.parse::<i32>() {
+            return Ok(level);
+        }
+
+        level.parse::<LogLevel>()
+            .map(|level| level.as_i32())
+            .map_err(|()| format!("unknown level \"{}\"", level).into())
+    }
+
     // TODO: fn mutant(&self, cfg: &Config) -> Result<Box<Mutant>, Box<Error>>;
 
     // TODO: Give a way to register user-defined components.
@@ -103,3 +226,101 @@ impl Registry {
             .ok_or("field \"type\" must be a string")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, Value};
+
+    use super::Registry;
+
+    #[test]
+    fn logger_from_str() {
+        let cfg = r#"{"type": "sync", "handlers": [{
+            "type": "sync",
+            "layout": {"type": "pattern", "pattern": "{message}"},
+            "outputs": [{"type": "null"}]
+        }]}"#;
+
+        assert!(Registry::new().logger_from_str(cfg).is_ok());
+    }
+
+    #[test]
+    fn logger_from_reader() {
+        let cfg = r#"{"type": "sync", "handlers": []}"#;
+
+        assert!(Registry::new().logger_from_reader(cfg.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn fail_logger_from_invalid_json() {
+        assert!(Registry::new().logger_from_str(r#"{"type": "sync", "#).is_err());
+    }
+
+    #[test]
+    fn loggers() {
+        let cfg = serde_json::from_str(r#"{"loggers": {
+            "http": {"type": "sync", "handlers": []},
+            "db": {"type": "sync", "handlers": []}
+        }}"#).unwrap();
+
+        let loggers = Registry::new().loggers(&cfg).unwrap();
+
+        let mut names = loggers.keys().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(vec!["db", "http"], names);
+    }
+
+    #[test]
+    fn fail_loggers_with_invalid_logger() {
+        let cfg = serde_json::from_str(r#"{"loggers": {
+            "http": {"type": "sync", "handlers": []},
+            "db": {"type": "unknown"}
+        }}"#).unwrap();
+
+        assert!(Registry::new().loggers(&cfg).is_err());
+    }
+
+    #[test]
+    fn layout_by_ref() {
+        let cfg = serde_json::from_str(r#"{"layouts": {
+            "message": {"type": "pattern", "pattern": "{message}"}
+        }}"#).unwrap();
+
+        let registry = Registry::new().with_layouts(&cfg).unwrap();
+
+        let cfg = serde_json::from_str(r#"{"ref": "message"}"#).unwrap();
+        assert!(registry.layout(&cfg).is_ok());
+
+        let cfg = serde_json::from_str(r#"{"ref": "unknown"}"#).unwrap();
+        assert!(registry.layout(&cfg).is_err());
+    }
+
+    #[test]
+    fn fail_named_layout_ref() {
+        let cfg = serde_json::from_str(r#"{"layouts": {
+            "message": {"ref": "message"}
+        }}"#).unwrap();
+
+        assert!(Registry::new().with_layouts(&cfg).is_err());
+    }
+
+    #[test]
+    fn level_from_name() {
+        assert_eq!(4, Registry::level(&Value::String("error".into())).unwrap());
+        assert_eq!(3, Registry::level(&Value::String("Warn".into())).unwrap());
+        assert_eq!(0, Registry::level(&Value::String("TRACE".into())).unwrap());
+    }
+
+    #[test]
+    fn level_from_number() {
+        assert_eq!(2, Registry::level(&Value::I64(2)).unwrap());
+        assert_eq!(2, Registry::level(&Value::U64(2)).unwrap());
+        assert_eq!(42, Registry::level(&Value::String("42".into())).unwrap());
+    }
+
+    #[test]
+    fn fail_level_unknown() {
+        assert!(Registry::level(&Value::String("fatal".into())).is_err());
+        assert!(Registry::level(&Value::Bool(true)).is_err());
+    }
+}
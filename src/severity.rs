@@ -1,9 +1,27 @@
+//! Severity levels and their mappings.
+//!
+//! The crate uses a single numeric scale, where greater numbers are more severe. Named levels are
+//! provided as constants, which are used consistently by the `log::LogLevel` bridge, level names in
+//! configs, syslog priorities and terminal colors. Custom severity types are free to use any other
+//! numbers, but then they should provide their own color and syslog mappings.
+
 use log::LogLevel;
 
 use meta::format::{Format, Formatter};
 
 pub type Error = ::std::io::Error;
 
+/// The most verbose level, used for fine-grained tracing.
+pub const TRACE: i32 = 0;
+/// Debugging information.
+pub const DEBUG: i32 = 1;
+/// Normal operational messages.
+pub const INFO: i32 = 2;
+/// Potentially harmful situations.
+pub const WARN: i32 = 3;
+/// Errors, the most severe level.
+pub const ERROR: i32 = 4;
+
 pub trait Severity {
     /// Returns an integer severity representation.
     fn as_i32(&self) -> i32;
@@ -12,6 +30,10 @@ pub trait Severity {
         where Self: Sized;
 }
 
+/// Plain integer severity.
+///
+/// Integers have no level names, so both numeric and string representations give digits. Use
+/// `log::LogLevel` or a custom `Severity` implementation if named levels are required.
 impl Severity for i32 {
     fn as_i32(&self) -> i32 {
         *self
@@ -24,14 +46,52 @@ impl Severity for i32 {
     }
 }
 
+/// Translates the given severity into the syslog priority number.
+///
+/// Syslog uses the inverse ordering, where lower numbers are more severe: 0 stands for emergency
+/// and 7 for debug. The mapping follows the crate's severity levels:
+///
+/// | Severity    | Syslog priority |
+/// |-------------|-----------------|
+/// | ERROR (4)   | 3 (error)       |
+/// | WARN (3)    | 4 (warning)     |
+/// | INFO (2)    | 6 (info)        |
+/// | DEBUG (1)   | 7 (debug)       |
+/// | TRACE (0)   | 7 (debug)       |
+///
+/// Severities above `ERROR` are mapped to 3, below `TRACE` - to 7.
+pub fn syslog_priority(sev: i32) -> i32 {
+    match sev {
+        sev if sev >= ERROR => 3,
+        WARN => 4,
+        INFO => 6,
+        _ => 7,
+    }
+}
+
+/// Returns the 256-color ANSI terminal palette index for the given severity.
+///
+/// Used to colorize severities both by the `Dev` handle and by colored pattern layouts. Errors
+/// are red, warnings are yellow, infos are green and debug messages are light green. Severities
+/// above `ERROR` are colored as errors, while traces and severities below them are light yellow.
+pub fn severity_color(sev: i32) -> u8 {
+    match sev {
+        sev if sev >= ERROR => 9,
+        WARN => 3,
+        INFO => 2,
+        DEBUG => 10,
+        _ => 11,
+    }
+}
+
 impl Severity for LogLevel {
     fn as_i32(&self) -> i32 {
         match *self {
-            LogLevel::Error => 4,
-            LogLevel::Warn  => 3,
-            LogLevel::Info  => 2,
-            LogLevel::Debug => 1,
-            LogLevel::Trace => 0,
+            LogLevel::Error => ERROR,
+            LogLevel::Warn  => WARN,
+            LogLevel::Info  => INFO,
+            LogLevel::Debug => DEBUG,
+            LogLevel::Trace => TRACE,
         }
     }
 
@@ -39,12 +99,53 @@ impl Severity for LogLevel {
         where Self: Sized
     {
         match val {
-            4 => format.write_str("Error"),
-            3 => format.write_str("Warn"),
-            2 => format.write_str("Info"),
-            1 => format.write_str("Debug"),
-            0 => format.write_str("Trace"),
+            ERROR => format.write_str("Error"),
+            WARN => format.write_str("Warn"),
+            INFO => format.write_str("Info"),
+            DEBUG => format.write_str("Debug"),
+            TRACE => format.write_str("Trace"),
             val => val.format(format),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use log::LogLevel;
+
+    use super::*;
+
+    #[test]
+    fn syslog() {
+        assert_eq!(3, syslog_priority(LogLevel::Error.as_i32()));
+        assert_eq!(4, syslog_priority(LogLevel::Warn.as_i32()));
+        assert_eq!(6, syslog_priority(LogLevel::Info.as_i32()));
+        assert_eq!(7, syslog_priority(LogLevel::Debug.as_i32()));
+        assert_eq!(7, syslog_priority(LogLevel::Trace.as_i32()));
+    }
+
+    #[test]
+    fn syslog_out_of_range() {
+        assert_eq!(3, syslog_priority(42));
+        assert_eq!(7, syslog_priority(-1));
+    }
+
+    #[test]
+    fn log_level_bridge() {
+        assert_eq!(ERROR, LogLevel::Error.as_i32());
+        assert_eq!(WARN, LogLevel::Warn.as_i32());
+        assert_eq!(INFO, LogLevel::Info.as_i32());
+        assert_eq!(DEBUG, LogLevel::Debug.as_i32());
+        assert_eq!(TRACE, LogLevel::Trace.as_i32());
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(9, severity_color(ERROR));
+        assert_eq!(3, severity_color(WARN));
+        assert_eq!(2, severity_color(INFO));
+        assert_eq!(10, severity_color(DEBUG));
+        assert_eq!(11, severity_color(TRACE));
+        assert_eq!(9, severity_color(42));
+    }
+}
@@ -0,0 +1,21 @@
+//! Helpers shared between unit and integration tests.
+//!
+//! Integration tests include this file using the `#[path]` attribute, so it must not depend on
+//! anything from the crate itself.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Creates an empty temporary directory with the given name, unique for the current process.
+///
+/// Leftovers from previous runs are removed first, because file outputs usually append to existing
+/// files. The directory is not removed automatically.
+pub fn tempdir(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("blacklog-{}-{}", name, unsafe { ::libc::getpid() }));
+    if let Err(..) = fs::remove_dir_all(&path) {
+        // Usually the directory just doesn't exist yet.
+    }
+    fs::create_dir_all(&path).unwrap();
+    path
+}
@@ -0,0 +1,106 @@
+//! Utilities for testing applications, that use logging.
+
+use std::fmt::Arguments;
+use std::sync::{Arc, Mutex};
+
+use {Format, Formatter, Logger, Record, RecordBuf};
+
+/// A logger, that captures all records in memory for further inspection.
+///
+/// Unlike matching rendered text this allows to assert that specific records were emitted with
+/// specific meta information. Meta values are compared by their default string representation.
+///
+/// The logger is cheaply clonable, all clones share the same storage, so it's possible to keep
+/// one clone for assertions while another one is passed to the code under test.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate blacklog;
+///
+/// use blacklog::Logger;
+/// use blacklog::testing::TestLogger;
+///
+/// fn main() {
+///     let log = TestLogger::new();
+///
+///     log!(log, 3, "file does not exist: {}", ["favicon.ico"], {
+///         path: "/var/www",
+///     });
+///
+///     assert_eq!(vec!["file does not exist: favicon.ico"], log.records_at(3));
+///     assert!(log.contains_meta("path", "/var/www"));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TestLogger {
+    records: Arc<Mutex<Vec<RecordBuf>>>,
+}
+
+impl TestLogger {
+    pub fn new() -> TestLogger {
+        TestLogger {
+            records: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns the number of captured records.
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no records were captured.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns messages of all captured records in the order they were logged.
+    pub fn messages(&self) -> Vec<String> {
+        self.records.lock().unwrap().iter().map(|rec| rec.message().into()).collect()
+    }
+
+    /// Returns messages of captured records with the given severity in the order they were
+    /// logged.
+    pub fn records_at(&self, sev: i32) -> Vec<String> {
+        self.records.lock().unwrap().iter()
+            .filter(|rec| rec.severity() == sev)
+            .map(|rec| rec.message().into())
+            .collect()
+    }
+
+    /// Returns `true` if at least one captured record has a meta attribute with the given name,
+    /// which is formatted into the given value.
+    pub fn contains_meta(&self, name: &str, value: &str) -> bool {
+        self.records.lock().unwrap().iter().any(|rec| {
+            rec.meta_iter().any(|meta| {
+                if meta.name != name {
+                    return false;
+                }
+
+                let mut buf = Vec::new();
+                match meta.value.format(&mut Formatter::new(&mut buf, Default::default())) {
+                    Ok(()) => buf == value.as_bytes(),
+                    Err(..) => false,
+                }
+            })
+        })
+    }
+
+    /// Removes all captured records.
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+}
+
+impl Default for TestLogger {
+    fn default() -> TestLogger {
+        TestLogger::new()
+    }
+}
+
+impl Logger for TestLogger {
+    fn log<'a, 'b>(&self, rec: &mut Record<'a>, args: Arguments<'b>) {
+        rec.activate(args);
+        self.records.lock().unwrap().push(RecordBuf::from(&*rec));
+    }
+}
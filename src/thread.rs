@@ -1,26 +1,44 @@
+#[cfg(unix)]
 use libc;
 
+#[cfg(not(unix))]
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+thread_local!(static ID: usize = __new_id());
+
+/// Returns the current thread id.
+///
+/// The id is guaranteed to be nonzero and unique among all threads running simultaneously on all
+/// platforms. On unix it's the value of `pthread_self`, elsewhere a sequential number assigned to
+/// a thread on its first call. In both cases the id is obtained once per thread and then cached
+/// in a thread-local, so subsequent calls are cheap.
 #[inline]
 pub fn id() -> usize {
-    __get_id()
+    ID.with(|id| *id)
 }
 
 #[cfg(unix)]
 #[inline]
-fn __get_id() -> usize {
+fn __new_id() -> usize {
     unsafe {
         libc::pthread_self() as usize
     }
 }
 
-#[cfg(not(all(unix)))]
+#[cfg(not(unix))]
+static COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+#[cfg(not(unix))]
 #[inline]
-fn __get_id() -> usize {
-    0
+fn __new_id() -> usize {
+    COUNTER.fetch_add(1, Ordering::Relaxed) + 1
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
     use super::{id};
 
     #[test]
@@ -28,6 +46,40 @@ mod tests {
         assert!(id() > 0);
     }
 
+    #[test]
+    fn test_id_stable() {
+        assert_eq!(id(), id());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_id_cached_matches_pthread_self() {
+        assert_eq!(super::__new_id(), id());
+    }
+
+    #[test]
+    fn test_id_distinct_threads() {
+        // Both threads are kept alive simultaneously to prevent ids reusing.
+        let barrier = Arc::new(Barrier::new(2));
+
+        let threads: Vec<_> = (0..2).map(|_| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                let id = id();
+                barrier.wait();
+                id
+            })
+        }).collect();
+
+        let ids: Vec<usize> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+
+        assert!(ids[0] > 0);
+        assert!(ids[1] > 0);
+        assert!(ids[0] != ids[1]);
+        assert!(ids[0] != id());
+        assert!(ids[1] != id());
+    }
+
     #[cfg(feature="benchmark")]
     use test::{self, Bencher};
 
@@ -40,6 +92,16 @@ mod tests {
         });
     }
 
+    /// Obtains the id without caching for comparison with `bench_id`.
+    #[cfg(feature="benchmark")]
+    #[bench]
+    fn bench_id_uncached(b: &mut Bencher) {
+        b.iter(|| {
+            let id = super::__new_id();
+            test::black_box(id);
+        });
+    }
+
     #[cfg(feature="benchmark")]
     #[bench]
     fn name(b: &mut Bencher) {
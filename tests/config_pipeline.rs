@@ -0,0 +1,236 @@
+extern crate libc;
+extern crate serde_json;
+#[macro_use] extern crate blacklog;
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use blacklog::Registry;
+
+#[path = "../src/support.rs"]
+mod support;
+
+use support::tempdir;
+
+fn read(path: PathBuf) -> String {
+    let mut buf = String::new();
+    File::open(path).unwrap().read_to_string(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn log_into_file_configured_from_json() {
+    let dir = tempdir("file");
+
+    let config = format!(r#"{{
+        "type": "sync",
+        "handlers": [
+            {{
+                "type": "sync",
+                "layout": {{
+                    "type": "pattern",
+                    "pattern": "{{severity:d}} [{{path}}] {{message}}"
+                }},
+                "outputs": [
+                    {{
+                        "type": "file",
+                        "path": "{}/app.log"
+                    }}
+                ]
+            }}
+        ]
+    }}"#, dir.display());
+
+    let path = dir.join("config.json");
+    File::create(&path).unwrap().write_all(config.as_bytes()).unwrap();
+
+    let cfg = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+
+    {
+        let log = Registry::new().logger(&cfg).unwrap();
+
+        log!(log, 1, "file does not exist: {}", ["favicon.ico"], {
+            path: "/var/www",
+        });
+        log!(log, 4, "permission denied", {
+            path: "/etc",
+        });
+
+        // Dropping the logger flushes all buffered outputs.
+    }
+
+    assert_eq!("1 [/var/www] file does not exist: favicon.ico\n4 [/etc] permission denied\n",
+        read(dir.join("app.log")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn log_into_multiple_handles_and_outputs() {
+    let dir = tempdir("multiple");
+
+    let cfg = serde_json::from_str(&format!(r#"{{
+        "type": "sync",
+        "handlers": [
+            {{
+                "type": "sync",
+                "layout": {{
+                    "type": "pattern",
+                    "pattern": "{{message}}"
+                }},
+                "outputs": [
+                    {{
+                        "type": "file",
+                        "path": "{dir}/first.log"
+                    }},
+                    {{
+                        "type": "file",
+                        "path": "{dir}/second.log"
+                    }}
+                ]
+            }},
+            {{
+                "type": "sync",
+                "layout": {{
+                    "type": "pattern",
+                    "pattern": "{{severity:d}}: {{message}}"
+                }},
+                "outputs": [
+                    {{
+                        "type": "file",
+                        "path": "{dir}/third.log"
+                    }},
+                    {{
+                        "type": "null"
+                    }}
+                ]
+            }}
+        ]
+    }}"#, dir = dir.display())).unwrap();
+
+    {
+        let log = Registry::new().logger(&cfg).unwrap();
+
+        log!(log, 2, "nginx/1.6 configured");
+    }
+
+    assert_eq!("nginx/1.6 configured\n", read(dir.join("first.log")));
+    assert_eq!("nginx/1.6 configured\n", read(dir.join("second.log")));
+    assert_eq!("2: nginx/1.6 configured\n", read(dir.join("third.log")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn log_into_named_loggers() {
+    let dir = tempdir("named");
+
+    let cfg = serde_json::from_str(&format!(r#"{{
+        "loggers": {{
+            "http": {{
+                "type": "sync",
+                "handlers": [
+                    {{
+                        "type": "sync",
+                        "layout": {{"type": "pattern", "pattern": "http: {{message}}"}},
+                        "outputs": [{{"type": "file", "path": "{dir}/http.log"}}]
+                    }}
+                ]
+            }},
+            "db": {{
+                "type": "sync",
+                "handlers": [
+                    {{
+                        "type": "sync",
+                        "layout": {{"type": "pattern", "pattern": "db: {{message}}"}},
+                        "outputs": [{{"type": "file", "path": "{dir}/db.log"}}]
+                    }}
+                ]
+            }}
+        }}
+    }}"#, dir = dir.display())).unwrap();
+
+    {
+        let loggers = Registry::new().loggers(&cfg).unwrap();
+        assert_eq!(2, loggers.len());
+
+        let http = &loggers["http"];
+        let db = &loggers["db"];
+
+        log!(http, 2, "GET /index.html");
+        log!(db, 2, "connection established");
+        log!(http, 2, "POST /upload");
+    }
+
+    assert_eq!("http: GET /index.html\nhttp: POST /upload\n", read(dir.join("http.log")));
+    assert_eq!("db: connection established\n", read(dir.join("db.log")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn log_into_file_with_named_layouts() {
+    let dir = tempdir("named-layouts");
+
+    let cfg = serde_json::from_str(&format!(r#"{{
+        "layouts": {{
+            "message": {{"type": "pattern", "pattern": "[{{tenant}}] {{message}}"}},
+            "path": {{"type": "pattern", "pattern": "{dir}/{{tenant}}.log"}}
+        }},
+        "type": "sync",
+        "handlers": [
+            {{
+                "type": "sync",
+                "layout": {{"ref": "message"}},
+                "outputs": [{{"type": "file", "path_pattern": {{"ref": "path"}}}}]
+            }}
+        ]
+    }}"#, dir = dir.display())).unwrap();
+
+    {
+        let log = Registry::new().with_layouts(&cfg).unwrap().logger(&cfg).unwrap();
+
+        log!(log, 2, "GET /index.html", {
+            tenant: "acme",
+        });
+        log!(log, 2, "POST /upload", {
+            tenant: "initech",
+        });
+    }
+
+    assert_eq!("[acme] GET /index.html\n", read(dir.join("acme.log")));
+    assert_eq!("[initech] POST /upload\n", read(dir.join("initech.log")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn log_into_file_through_async_handle() {
+    let dir = tempdir("async-handle");
+
+    let cfg = serde_json::from_str(&format!(r#"{{
+        "type": "sync",
+        "handlers": [
+            {{
+                "type": "async",
+                "capacity": 16,
+                "layout": {{"type": "pattern", "pattern": "{{severity:d}}: {{message}}"}},
+                "outputs": [{{"type": "file", "path_pattern": "{dir}/app.log"}}]
+            }}
+        ]
+    }}"#, dir = dir.display())).unwrap();
+
+    {
+        let log = Registry::new().logger(&cfg).unwrap();
+
+        log!(log, 2, "GET /index.html");
+        log!(log, 4, "POST /upload");
+
+        // Dropping the logger waits for the background thread to write all records.
+    }
+
+    assert_eq!("2: GET /index.html\n4: POST /upload\n", read(dir.join("app.log")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -1,10 +1,20 @@
+extern crate chrono;
 #[macro_use] extern crate blacklog;
 
-use std::sync::Arc;
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
 
-use blacklog::{Handle, Logger, Record};
-use blacklog::logger::SyncLogger;
+use chrono::{DateTime, UTC};
+
+use blacklog::{Filter, Format, Handle, Logger, MetaBuf, MetaLink, Record, Timestamping};
+use blacklog::filter::FilterAction;
+use blacklog::logger::{ActorLogger, LoggerBuilder, SeverityFilteredLoggerAdapter, SyncLogger,
+                       TeeLogger};
+use blacklog::testing::TestLogger;
 
 #[test]
 fn log_only_message() {
@@ -79,6 +89,433 @@ fn log_calls_handle_after_reset() {
     assert_eq!(2, counter.load(Ordering::SeqCst));
 }
 
+struct DenyHandle;
+
+impl Handle for DenyHandle {
+    fn filter(&self, _rec: &Record) -> FilterAction {
+        FilterAction::Deny
+    }
+
+    fn handle(&self, _rec: &mut Record) -> Result<(), ::std::io::Error> {
+        panic!("denied record must not be handled");
+    }
+}
+
+/// Formatting argument, that panics if evaluated.
+struct Unreachable;
+
+impl Display for Unreachable {
+    fn fmt(&self, _fmt: &mut Formatter) -> Result<(), fmt::Error> {
+        panic!("message must not be formatted");
+    }
+}
+
+#[test]
+fn log_does_not_activate_when_all_handles_deny() {
+    let log = SyncLogger::new(vec![Box::new(DenyHandle), Box::new(DenyHandle)]);
+
+    log!(log, 0, "{}", Unreachable);
+}
+
+#[test]
+fn log_activates_for_accepting_handle_after_denying_one() {
+    let handle = MockHandle::new();
+    let counter = handle.counter();
+    let log = SyncLogger::new(vec![Box::new(DenyHandle), Box::new(handle)]);
+
+    log!(log, 0, "file does not exist: {}", "/var/www/favicon.ico");
+
+    assert_eq!(1, counter.load(Ordering::SeqCst));
+}
+
+struct FileHandle {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Handle for FileHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        assert_eq!(file!(), rec.file());
+        assert!(rec.file().ends_with("logger.rs"));
+
+        self.counter.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn log_captures_source_file() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let log = SyncLogger::new(vec![Box::new(FileHandle { counter: counter.clone() })]);
+
+    log!(log, 0, "file does not exist: {}", "/var/www/favicon.ico");
+
+    assert_eq!(1, counter.load(Ordering::SeqCst));
+}
+
+struct DenyFilter;
+
+impl Filter for DenyFilter {
+    fn filter(&self, _rec: &Record) -> FilterAction {
+        FilterAction::Deny
+    }
+}
+
+#[test]
+fn log_does_not_activate_when_logger_filter_denies() {
+    let handle = MockHandle::new();
+    let counter = handle.counter();
+    let log = SyncLogger::new(vec![Box::new(handle)]).with_filter(Box::new(DenyFilter));
+
+    log!(log, 0, "{}", Unreachable);
+
+    assert_eq!(0, counter.load(Ordering::SeqCst));
+}
+
+struct ServiceHandle {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Handle for ServiceHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        let names = rec.iter().map(|meta| meta.name).collect::<Vec<_>>();
+        assert_eq!(vec!["service", "path"], names);
+        self.counter.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn log_with_default_meta() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let handle = ServiceHandle { counter: counter.clone() };
+    let log = SyncLogger::with_defaults(vec![Box::new(handle)], vec![
+        MetaBuf::new("service", Box::new("api")),
+    ]);
+
+    log!(log, 0, "file does not exist: /var/www/favicon.ico", {
+        path: "/var/www",
+    });
+    log!(log, 0, "file does not exist: /var/www/robots.txt", {
+        path: "/var/www",
+    });
+
+    assert_eq!(2, counter.load(Ordering::SeqCst));
+}
+
+struct ShadowHandle {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Handle for ShadowHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        let names = rec.iter().map(|meta| meta.name).collect::<Vec<_>>();
+        assert_eq!(vec!["service", "service"], names);
+
+        let meta = rec.meta_map();
+        let mut buf = Vec::new();
+        meta["service"].format(&mut blacklog::Formatter::new(&mut buf, Default::default()))?;
+        assert_eq!(b"worker", &buf[..]);
+        self.counter.fetch_add(1, Ordering::SeqCst);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn log_with_default_meta_shadowed() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let handle = ShadowHandle { counter: counter.clone() };
+    let log = SyncLogger::with_defaults(vec![Box::new(handle)], vec![
+        MetaBuf::new("service", Box::new("api")),
+    ]);
+
+    log!(log, 0, "file does not exist: /var/www/favicon.ico", {
+        service: "worker",
+    });
+
+    assert_eq!(1, counter.load(Ordering::SeqCst));
+}
+
+struct TupleHandle {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Handle for TupleHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        let meta = rec.iter().find(|meta| meta.name == "point").unwrap();
+
+        let mut buf = Vec::new();
+        meta.value.format(&mut blacklog::Formatter::new(&mut buf, Default::default()))?;
+        assert_eq!(b"(1, two)", &buf[..]);
+
+        self.counter.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn log_with_tuple_meta() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let log = SyncLogger::new(vec![Box::new(TupleHandle { counter: counter.clone() })]);
+
+    log!(log, 0, "moved", {
+        point: (1, "two"),
+    });
+
+    assert_eq!(1, counter.load(Ordering::SeqCst));
+}
+
+/// Returns the id of the current thread, as it's seen by records.
+fn thread_id() -> usize {
+    Record::new(0, 0, "", &MetaLink::new(&[])).thread()
+}
+
+/// Sends both the record's thread id and the id of the thread it is handled in.
+struct ThreadHandle {
+    tx: Mutex<Sender<(usize, usize)>>,
+}
+
+impl Handle for ThreadHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        self.tx.lock().unwrap().send((rec.thread(), thread_id())).unwrap();
+        Ok(())
+    }
+}
+
+#[test]
+fn actor_log_preserves_producer_thread_id() {
+    let (tx, rx) = mpsc::channel();
+    let log = ActorLogger::new(vec![Box::new(ThreadHandle { tx: Mutex::new(tx) })]);
+
+    let producer = thread::Builder::new().name("producer".into()).spawn(move || {
+        log!(log, 0, "file does not exist: /var/www/favicon.ico");
+        thread_id()
+    }).unwrap().join().unwrap();
+
+    let (actual, worker) = rx.recv().unwrap();
+    assert_eq!(producer, actual);
+    assert!(worker != actual);
+}
+
+/// Sends both the record's timestamp and the time it is handled at, after waiting for the gate.
+struct TimestampHandle {
+    gate: Arc<Mutex<()>>,
+    tx: Mutex<Sender<(DateTime<UTC>, DateTime<UTC>)>>,
+}
+
+impl Handle for TimestampHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        let _guard = self.gate.lock().unwrap();
+        self.tx.lock().unwrap().send((rec.datetime(), UTC::now())).unwrap();
+        Ok(())
+    }
+}
+
+/// Logs a record, which waits in the queue for a while because the handle is blocked by the
+/// previous one, returning the record's timestamp and the time it was handled at.
+fn log_delayed(timestamping: Timestamping) -> (DateTime<UTC>, DateTime<UTC>) {
+    let gate = Arc::new(Mutex::new(()));
+    let (tx, rx) = mpsc::channel();
+
+    let log = ActorLogger::with_timestamping(vec![Box::new(TimestampHandle {
+        gate: gate.clone(),
+        tx: Mutex::new(tx),
+    })], timestamping);
+
+    {
+        let _guard = gate.lock().unwrap();
+        log!(log, 0, "blocks the handle");
+        log!(log, 0, "waits in the queue");
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    rx.recv().unwrap();
+    rx.recv().unwrap()
+}
+
+#[test]
+fn actor_log_keeps_event_time() {
+    let (timestamp, handled) = log_delayed(Timestamping::Event);
+
+    assert!(handled - timestamp >= chrono::Duration::milliseconds(50));
+}
+
+#[test]
+fn actor_log_with_handling_time() {
+    let (timestamp, handled) = log_delayed(Timestamping::Handling);
+
+    assert!(timestamp <= handled);
+    assert!(handled - timestamp < chrono::Duration::milliseconds(50));
+}
+
+/// Sends the message and the timestamp of each handled record.
+struct ChannelHandle {
+    tx: Mutex<Sender<(String, DateTime<UTC>)>>,
+}
+
+impl Handle for ChannelHandle {
+    fn handle(&self, rec: &mut Record) -> Result<(), ::std::io::Error> {
+        self.tx.lock().unwrap().send((rec.message().into(), rec.datetime())).unwrap();
+        Ok(())
+    }
+}
+
+#[test]
+fn tee_log_feeds_all_loggers() {
+    let (tx, rx) = mpsc::channel();
+
+    let sync = SyncLogger::new(vec![Box::new(ChannelHandle { tx: Mutex::new(tx.clone()) })]);
+    let actor = ActorLogger::new(vec![Box::new(ChannelHandle { tx: Mutex::new(tx) })]);
+    let log = TeeLogger::new(vec![Box::new(sync), Box::new(actor)]);
+
+    log!(log, 0, "file does not exist: {}", ["favicon.ico"]);
+
+    let (first, first_timestamp) = rx.recv().unwrap();
+    let (second, second_timestamp) = rx.recv().unwrap();
+
+    assert_eq!("file does not exist: favicon.ico", first);
+    assert_eq!("file does not exist: favicon.ico", second);
+
+    // Each logger activates the record, but all of them observe the same timestamp.
+    assert_eq!(first_timestamp, second_timestamp);
+}
+
+#[test]
+fn tee_log_with_defaults_in_one_logger() {
+    let (tx, rx) = mpsc::channel();
+
+    let handle = ChannelHandle { tx: Mutex::new(tx) };
+    let sync = SyncLogger::with_defaults(vec![Box::new(handle)], vec![
+        MetaBuf::new("service", Box::new("api")),
+    ]);
+    let test = TestLogger::new();
+    let log = TeeLogger::new(vec![Box::new(sync), Box::new(test.clone())]);
+
+    log!(log, 0, "file does not exist: {}", ["favicon.ico"]);
+
+    let (message, _) = rx.recv().unwrap();
+    assert_eq!("file does not exist: favicon.ico", message);
+
+    // Defaults are attached to a copy of the record, so the next logger activates the original
+    // one on its own and doesn't see them.
+    assert_eq!(vec!["file does not exist: favicon.ico"], test.messages());
+    assert!(!test.contains_meta("service", "api"));
+}
+
+#[test]
+fn log_truncates_long_messages() {
+    let (tx, rx) = mpsc::channel();
+
+    let log = SyncLogger::new(vec![Box::new(ChannelHandle { tx: Mutex::new(tx) })])
+        .with_max_message_len(1024);
+
+    let message = ::std::iter::repeat('x').take(100 * 1024).collect::<String>();
+    log!(log, 0, "{}", [message]);
+
+    let (message, _) = rx.recv().unwrap();
+    let marker = "…[truncated 99.0 KiB]";
+
+    assert!(message.ends_with(marker));
+    assert_eq!(1024 + marker.len(), message.len());
+}
+
+#[test]
+fn build_logger_with_threshold_and_filter() {
+    let (tx, rx) = mpsc::channel();
+
+    let log = LoggerBuilder::new()
+        .handle(Box::new(ChannelHandle { tx: Mutex::new(tx) }))
+        .threshold(2)
+        .filter(Box::new(|rec: &Record| {
+            if rec.iter().any(|meta| meta.name == "secret") {
+                FilterAction::Deny
+            } else {
+                FilterAction::Neutral
+            }
+        }))
+        .build();
+
+    log!(log, 1, "below the threshold");
+    log!(log, 3, "denied by the filter", {
+        secret: "42",
+    });
+    log!(log, 2, "accepted");
+
+    assert!(!log.enabled(1));
+    assert!(log.enabled(2));
+
+    drop(log);
+    let messages = rx.iter().map(|(message, _)| message).collect::<Vec<_>>();
+    assert_eq!(vec!["accepted"], messages);
+}
+
+/// Notifies that a record has been taken by the worker, then waits for the gate.
+struct GateHandle {
+    gate: Arc<Mutex<()>>,
+    tx: Mutex<Sender<()>>,
+}
+
+impl Handle for GateHandle {
+    fn handle(&self, _rec: &mut Record) -> Result<(), ::std::io::Error> {
+        self.tx.lock().unwrap().send(()).unwrap();
+        let _guard = self.gate.lock().unwrap();
+        Ok(())
+    }
+}
+
+#[test]
+fn actor_log_pending() {
+    let gate = Arc::new(Mutex::new(()));
+    let (tx, rx) = mpsc::channel();
+
+    let log = ActorLogger::new(vec![Box::new(GateHandle {
+        gate: gate.clone(),
+        tx: Mutex::new(tx),
+    })]);
+
+    assert_eq!(0, log.pending());
+
+    {
+        let _guard = gate.lock().unwrap();
+
+        // The first record is taken by the worker, which is then blocked.
+        log!(log, 0, "first");
+        rx.recv().unwrap();
+        assert_eq!(0, log.pending());
+
+        log!(log, 0, "second");
+        log!(log, 0, "third");
+        assert_eq!(2, log.pending());
+    }
+
+    rx.recv().unwrap();
+    rx.recv().unwrap();
+    assert_eq!(0, log.pending());
+}
+
+#[test]
+fn enabled_by_default() {
+    let log = SyncLogger::new(vec![]);
+
+    assert!(log.enabled(0));
+    assert!(log_enabled!(log, -1));
+}
+
+#[test]
+fn enabled_reflects_severity_threshold() {
+    let log = SeverityFilteredLoggerAdapter::new(SyncLogger::new(vec![]));
+    log.filter(2);
+
+    assert!(!log.enabled(1));
+    assert!(log.enabled(2));
+    assert!(log.enabled(3));
+
+    log.filter(4);
+    assert!(!log_enabled!(log, 3));
+    assert!(log_enabled!(log, 4));
+}
+
 // #[test]
 // fn log_macro_use() {
 //     let log = SyncLogger::new(vec![]);
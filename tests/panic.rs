@@ -0,0 +1,21 @@
+extern crate blacklog;
+
+use std::panic;
+use std::sync::Arc;
+
+use blacklog::install_panic_hook;
+use blacklog::testing::TestLogger;
+
+#[test]
+fn log_panics() {
+    let log = TestLogger::new();
+    install_panic_hook(Arc::new(log.clone()));
+
+    let result = panic::catch_unwind(|| {
+        panic!("file does not exist: {}", "favicon.ico");
+    });
+
+    assert!(result.is_err());
+    assert_eq!(vec!["file does not exist: favicon.ico"], log.records_at(4));
+    assert!(log.contains_meta("file", file!()));
+}
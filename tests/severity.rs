@@ -0,0 +1,37 @@
+extern crate blacklog;
+extern crate log;
+extern crate serde_json;
+
+use log::LogLevel;
+
+use blacklog::{severity_color, syslog_priority, Layout, MetaLink, Record, Registry, Severity};
+use blacklog::layout::PatternLayout;
+use blacklog::severity::{DEBUG, ERROR, INFO, TRACE, WARN};
+
+fn format(pattern: &str, level: LogLevel) -> String {
+    let metalink = MetaLink::new(&[]);
+    let mut rec = Record::new(level, 0, "", &metalink);
+    rec.activate(format_args!(""));
+
+    PatternLayout::new(pattern).unwrap().format_string(&rec).unwrap()
+}
+
+#[test]
+fn all_components_agree_on_levels() {
+    let levels = [
+        (LogLevel::Error, ERROR, "error", "Error", 3, 9),
+        (LogLevel::Warn, WARN, "warn", "Warn", 4, 3),
+        (LogLevel::Info, INFO, "info", "Info", 6, 2),
+        (LogLevel::Debug, DEBUG, "debug", "Debug", 7, 10),
+        (LogLevel::Trace, TRACE, "trace", "Trace", 7, 11),
+    ];
+
+    for &(level, sev, name, display, syslog, color) in levels.iter() {
+        assert_eq!(sev, level.as_i32());
+        assert_eq!(sev, Registry::level(&serde_json::Value::String(name.into())).unwrap());
+        assert_eq!(format!("{} {} {}", display, sev, syslog),
+            format("{severity} {severity:d} {severity:syslog}", level));
+        assert_eq!(syslog, syslog_priority(sev));
+        assert_eq!(color, severity_color(sev));
+    }
+}
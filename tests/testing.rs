@@ -0,0 +1,64 @@
+#[macro_use] extern crate blacklog;
+
+use std::thread;
+
+use blacklog::Logger;
+use blacklog::testing::TestLogger;
+
+#[test]
+fn capture_records() {
+    let log = TestLogger::new();
+    assert!(log.is_empty());
+
+    log!(log, 2, "nginx/1.6 configured");
+    log!(log, 4, "file does not exist: {}", "/var/www/favicon.ico");
+
+    assert_eq!(2, log.len());
+    assert_eq!(vec!["nginx/1.6 configured", "file does not exist: /var/www/favicon.ico"],
+        log.messages());
+}
+
+#[test]
+fn query_records_at_severity() {
+    let log = TestLogger::new();
+
+    log!(log, 1, "debug#1");
+    log!(log, 4, "error");
+    log!(log, 1, "debug#2");
+
+    assert_eq!(vec!["debug#1", "debug#2"], log.records_at(1));
+    assert_eq!(vec!["error"], log.records_at(4));
+    assert!(log.records_at(3).is_empty());
+}
+
+#[test]
+fn query_meta() {
+    let log = TestLogger::new();
+
+    log!(log, 2, "request processed", {
+        path: "/index.html",
+        status: 200,
+    });
+
+    assert!(log.contains_meta("path", "/index.html"));
+    assert!(log.contains_meta("status", "200"));
+    assert!(!log.contains_meta("status", "404"));
+    assert!(!log.contains_meta("method", "GET"));
+}
+
+#[test]
+fn clones_share_records() {
+    let log = TestLogger::new();
+
+    {
+        let log = log.clone();
+        thread::spawn(move || {
+            log!(log, 2, "from another thread");
+        }).join().unwrap();
+    }
+
+    assert_eq!(vec!["from another thread"], log.messages());
+
+    log.clear();
+    assert!(log.is_empty());
+}